@@ -0,0 +1,25 @@
+// Keeps the app working without a connection. Every request goes to the network first, so
+// that updates arrive as soon as possible, and falls back to the last copy in the cache.
+
+const CACHE = "cryptid-finder";
+
+self.addEventListener("install", () => self.skipWaiting());
+self.addEventListener("activate", (event) => event.waitUntil(self.clients.claim()));
+
+self.addEventListener("fetch", (event) => {
+    const request = event.request;
+    if (request.method !== "GET" || new URL(request.url).origin !== self.location.origin) {
+        return;
+    }
+    event.respondWith(
+        fetch(request)
+            .then((response) => {
+                if (response.ok) {
+                    const copy = response.clone();
+                    caches.open(CACHE).then((cache) => cache.put(request, copy));
+                }
+                return response;
+            })
+            .catch(() => caches.match(request, { ignoreSearch: true }))
+    );
+});
@@ -0,0 +1,121 @@
+//! Counting the combinations of clues that could be in play, given what is known about the players.
+
+use std::collections::HashMap;
+
+use hexx::Hex;
+use itertools::Itertools;
+
+use crate::model::{Clue, Map, TileMask};
+
+/// Stop looking at combinations after this many steps, to keep the app responsive.
+const MAX_STEPS: usize = 500_000;
+
+/// Result of looking at every combination of one possible clue per player.
+/// A combination is consistent if the clues are all different and point to exactly one tile.
+#[derive(Debug, Clone, Default)]
+pub struct Analysis {
+    /// Number of consistent combinations.
+    pub combinations: usize,
+    /// Number of consistent combinations that point to each tile.
+    pub counts: HashMap<Hex, usize>,
+    /// False if there were too many combinations to look at all of them.
+    pub complete: bool,
+}
+
+impl Analysis {
+    /// Look at all combinations of one clue out of every list.
+    pub fn new(map: &Map, possible_clues: &[Vec<Clue>]) -> Self {
+        let positions = map.tiles.iter().map(|t| t.position).collect_vec();
+        // Players with few possible clues first, to rule out combinations early.
+        let players = possible_clues
+            .iter()
+            .sorted_by_key(|clues| clues.len())
+            .map(|clues| {
+                clues
+                    .iter()
+                    .map(|&clue| (clue, map.mask(clue)))
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        let mut search = Search {
+            players: &players,
+            chosen: Vec::new(),
+            tile_counts: vec![0; positions.len()],
+            steps: 0,
+        };
+        let complete = search.run(&TileMask::from_fn(positions.len(), |_| true));
+
+        let counts: HashMap<Hex, usize> = positions
+            .into_iter()
+            .zip(search.tile_counts)
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        Self {
+            combinations: counts.values().sum(),
+            counts,
+            complete,
+        }
+    }
+
+    /// Tiles with the most consistent combinations first.
+    pub fn candidates(&self) -> Vec<(Hex, usize)> {
+        self.counts
+            .iter()
+            .map(|(&hex, &count)| (hex, count))
+            .sorted_by_key(|&(hex, count)| (std::cmp::Reverse(count), hex.x, hex.y))
+            .collect()
+    }
+
+    /// Share of the consistent combinations that point to the tile.
+    pub fn chance(&self, hex: Hex) -> f64 {
+        if self.combinations == 0 {
+            return 0.0;
+        }
+        self.counts.get(&hex).copied().unwrap_or_default() as f64 / self.combinations as f64
+    }
+}
+
+struct Search<'a> {
+    /// Possible clues of every player, with the tiles they apply to.
+    players: &'a [Vec<(Clue, TileMask)>],
+    /// Clues of the combination that is currently being built.
+    chosen: Vec<Clue>,
+    /// Number of consistent combinations for every tile.
+    tile_counts: Vec<usize>,
+    steps: usize,
+}
+
+impl Search<'_> {
+    /// Try every clue of the next player on the tiles that remain. Returns false if it gave up.
+    fn run(&mut self, remaining: &TileMask) -> bool {
+        let Some(clues) = self.players.get(self.chosen.len()) else {
+            if let Some(tile) = remaining.single() {
+                self.tile_counts[tile] += 1;
+            }
+            return true;
+        };
+
+        for (clue, covered) in clues {
+            self.steps += 1;
+            if self.steps > MAX_STEPS {
+                return false;
+            }
+            if self.chosen.contains(clue) {
+                // Every player has a different clue.
+                continue;
+            }
+            let next = remaining.and(covered);
+            if next.is_empty() {
+                continue;
+            }
+            self.chosen.push(*clue);
+            let complete = self.run(&next);
+            self.chosen.pop();
+            if !complete {
+                return false;
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,213 @@
+//! Which keys and mouse buttons do what. Every action can be bound to another key or mouse
+//! button in the settings, and the bindings are kept for the next start.
+
+use std::fmt;
+
+use notan::{app::App, egui, prelude::*};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::{
+    i18n::{tr, tr_name},
+    prefs, report,
+};
+
+/// Where the bindings the user changed are kept.
+const FILE: &str = "bindings.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
+pub enum Action {
+    /// Click tiles and drag structures, pieces and paint.
+    Select,
+    /// Drag the map.
+    Pan,
+    #[strum(serialize = "Zoom in")]
+    ZoomIn,
+    #[strum(serialize = "Zoom out")]
+    ZoomOut,
+    Cancel,
+    #[strum(serialize = "Answer yes")]
+    AnswerYes,
+    #[strum(serialize = "Answer no")]
+    AnswerNo,
+    #[strum(serialize = "Next hint")]
+    NextHint,
+    #[strum(serialize = "Next turn")]
+    NextTurn,
+    #[strum(serialize = "Table display")]
+    TableDisplay,
+    Fullscreen,
+}
+
+impl Action {
+    fn default_input(self) -> Input {
+        match self {
+            Action::Select | Action::Pan => Input::Mouse(MouseButton::Left),
+            Action::ZoomIn => Input::Key(KeyCode::Equals),
+            Action::ZoomOut => Input::Key(KeyCode::Minus),
+            Action::Cancel => Input::Key(KeyCode::Escape),
+            Action::AnswerYes => Input::Key(KeyCode::Y),
+            Action::AnswerNo => Input::Key(KeyCode::N),
+            Action::NextHint => Input::Key(KeyCode::H),
+            Action::NextTurn => Input::Key(KeyCode::F3),
+            Action::TableDisplay => Input::Key(KeyCode::F2),
+            Action::Fullscreen => Input::Key(KeyCode::F11),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Input {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Input::Key(key) => write!(f, "{key:?}"),
+            Input::Mouse(MouseButton::Left) => write!(f, "{}", tr("Left mouse button")),
+            Input::Mouse(MouseButton::Right) => write!(f, "{}", tr("Right mouse button")),
+            Input::Mouse(MouseButton::Middle) => write!(f, "{}", tr("Middle mouse button")),
+            Input::Mouse(MouseButton::Other(n)) => write!(f, "{} {n}", tr("Mouse button")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Bindings {
+    inputs: Vec<(Action, Input)>,
+    /// Action that gets the next key or mouse button the user presses.
+    waiting: Option<Action>,
+    /// True while the user types into a text field. Keys don't trigger actions then.
+    pub typing: bool,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self {
+            inputs: Action::iter().map(|a| (a, a.default_input())).collect(),
+            waiting: None,
+            typing: false,
+        }
+    }
+}
+
+impl Bindings {
+    /// The bindings of the last session. Actions that were not stored keep their default.
+    pub fn load() -> Self {
+        let mut bindings = Self::default();
+        let Some(json) = prefs::load(FILE) else {
+            return bindings;
+        };
+        match serde_json::from_str::<Vec<(Action, Input)>>(&json) {
+            Ok(stored) => {
+                for (action, input) in &mut bindings.inputs {
+                    if let Some((_, i)) = stored.iter().find(|(a, _)| a == action) {
+                        *input = *i;
+                    }
+                }
+            }
+            Err(e) => report::log(format!("Could not read the bindings: {e}")),
+        }
+        bindings
+    }
+
+    /// Keep the bindings for the next start.
+    fn store(&self) {
+        let result = serde_json::to_string(&self.inputs)
+            .map_err(|e| e.to_string())
+            .and_then(|json| prefs::store(FILE, &json));
+        if let Err(e) = result {
+            report::log(format!("Could not remember the bindings: {e}"));
+        }
+    }
+
+    pub fn input(&self, action: Action) -> Input {
+        self.inputs
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map_or(action.default_input(), |(_, input)| *input)
+    }
+
+    /// The input of the action, unless it should be ignored right now.
+    fn active(&self, action: Action) -> Option<Input> {
+        match self.input(action) {
+            _ if self.waiting.is_some() => None,
+            Input::Key(_) if self.typing => None,
+            input => Some(input),
+        }
+    }
+
+    /// True in the frame the input of the action went down.
+    pub fn was_pressed(&self, app: &App, action: Action) -> bool {
+        match self.active(action) {
+            Some(Input::Key(key)) => app.keyboard.was_pressed(key),
+            Some(Input::Mouse(button)) => app.mouse.was_pressed(button),
+            None => false,
+        }
+    }
+
+    /// True while the input of the action is held.
+    pub fn is_down(&self, app: &App, action: Action) -> bool {
+        match self.active(action) {
+            Some(Input::Key(key)) => app.keyboard.is_down(key),
+            Some(Input::Mouse(button)) => app.mouse.is_down(button),
+            None => false,
+        }
+    }
+
+    /// True in the frame the input of the action was let go.
+    pub fn was_released(&self, app: &App, action: Action) -> bool {
+        match self.active(action) {
+            Some(Input::Key(key)) => app.keyboard.was_released(key),
+            Some(Input::Mouse(button)) => app.mouse.was_released(button),
+            None => false,
+        }
+    }
+
+    /// Bind the key or mouse button the user pressed, if an action is waiting for one. Call
+    /// once per frame, before anything else looks at the input.
+    pub fn capture(&mut self, app: &App) {
+        let Some(action) = self.waiting else {
+            return;
+        };
+        let key = app.keyboard.pressed.iter().next().map(|k| Input::Key(*k));
+        let button = app.mouse.pressed.iter().next().map(|b| Input::Mouse(*b));
+        let Some(input) = key.or(button) else {
+            return;
+        };
+        for (a, i) in &mut self.inputs {
+            if *a == action {
+                *i = input;
+            }
+        }
+        self.waiting = None;
+        self.store();
+    }
+
+    pub fn gui(&mut self, ui: &mut egui::Ui) {
+        let typing = self.typing;
+        egui::Grid::new("bindings").show(ui, |ui| {
+            for action in Action::iter() {
+                ui.label(tr_name(action));
+                let text = if self.waiting == Some(action) {
+                    tr("Press a key or mouse button").to_owned()
+                } else {
+                    self.input(action).to_string()
+                };
+                if ui.button(text).clicked() {
+                    self.waiting = Some(action);
+                }
+                ui.end_row();
+            }
+        });
+        if ui.button(tr("Reset")).clicked() {
+            *self = Self {
+                typing,
+                ..Self::default()
+            };
+            self.store();
+        }
+    }
+}
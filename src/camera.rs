@@ -0,0 +1,191 @@
+//! Where the map is shown in the window. With smooth motion the map glides on for a moment
+//! after it is let go, and zoom steps of the mouse wheel are spread over a few frames. The
+//! layout of the camera converts between tiles and pixels, for drawing as well as for finding
+//! the tile under the mouse.
+
+use hexx::{HexLayout, HexOrientation};
+use notan::{egui, math::Vec2};
+
+use crate::{i18n::tr, model::Tile};
+
+/// Smallest and largest radius of the tiles.
+const MIN_RADIUS: f32 = 8.0;
+const MAX_RADIUS: f32 = 1024.0;
+/// How quickly a gliding map slows down. Higher values stop it sooner.
+const FRICTION: f32 = 6.0;
+/// How quickly the zoom reaches its target. Higher values reach it sooner.
+const ZOOM_RATE: f32 = 15.0;
+/// Gliding stops below this speed in pixels per second.
+const MIN_SPEED: f32 = 10.0;
+/// Pixels of the map that stay in the window, when it is dragged away or zoomed out.
+const KEEP_VISIBLE: f32 = 96.0;
+
+#[derive(Debug, Clone)]
+pub struct Camera {
+    /// Offset to draw the tiles at. Used for dragging with mouse.
+    pub offset: Vec2,
+    /// Radius of the tiles to draw.
+    pub tile_radius: f32,
+    /// Radius the zoom is moving towards.
+    target_radius: f32,
+    /// Pixels per second the map moves on its own.
+    velocity: Vec2,
+    /// True while the user holds the map.
+    held: bool,
+    /// Time the map was last dragged.
+    last_drag: f32,
+    /// Time of the last update.
+    last_update: f32,
+    /// False moves and zooms the map immediately.
+    pub smooth: bool,
+    /// True if the map is turned by 180°, for players on the other side of the table.
+    rotated: bool,
+    /// True if the map is shown mirrored from left to right.
+    mirrored: bool,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            tile_radius: 64.0,
+            target_radius: 64.0,
+            velocity: Vec2::ZERO,
+            held: false,
+            last_drag: 0.0,
+            last_update: 0.0,
+            smooth: true,
+            rotated: false,
+            mirrored: false,
+        }
+    }
+}
+
+impl Camera {
+    pub fn gui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.smooth, tr("Smooth panning and zooming"));
+        // Flip the offset along, so that the middle of the window shows the same spot.
+        if ui
+            .checkbox(&mut self.rotated, tr("Turn the map around"))
+            .changed()
+        {
+            self.offset = -self.offset;
+        }
+        if ui
+            .checkbox(&mut self.mirrored, tr("Mirror the map"))
+            .changed()
+        {
+            self.offset.x = -self.offset.x;
+        }
+    }
+
+    /// Where the tiles are in a window of the size. Turning and mirroring the map flips the
+    /// signs of the hex size, which leaves the tiles themselves as they are.
+    pub fn layout(&self, window_size: Vec2) -> HexLayout {
+        let mut flip = if self.rotated { -Vec2::ONE } else { Vec2::ONE };
+        if self.mirrored {
+            flip.x = -flip.x;
+        }
+        HexLayout {
+            orientation: HexOrientation::flat(),
+            origin: window_size * 0.5 + self.offset,
+            hex_size: flip * self.tile_radius,
+        }
+    }
+
+    /// Put the center of the tiles in the center of the window.
+    pub fn center_on(&mut self, tiles: &[Tile]) {
+        self.offset = match tile_bounds(tiles, &self.layout(Vec2::ZERO)) {
+            Some((min, max)) => self.offset - (min + max) * 0.5,
+            None => Vec2::ZERO,
+        };
+        self.stop();
+    }
+
+    /// Move the map just far enough that part of it is in the window.
+    pub fn keep_visible(&mut self, tiles: &[Tile], window_size: Vec2) {
+        let Some((min, max)) = tile_bounds(tiles, &self.layout(window_size)) else {
+            return;
+        };
+        let keep = Vec2::splat(KEEP_VISIBLE);
+        let radius = Vec2::splat(self.tile_radius);
+        // How far the edges of the map are beyond the part of the window it must reach into.
+        let too_far_right = (min - radius - (window_size - keep)).max(Vec2::ZERO);
+        let too_far_left = (keep - (max + radius)).max(Vec2::ZERO);
+        let clamped = self.offset - too_far_right + too_far_left;
+        if clamped != self.offset {
+            self.offset = clamped;
+            self.stop();
+        }
+    }
+
+    /// Zoom in for positive steps and out for negative ones.
+    pub fn zoom(&mut self, steps: f32) {
+        self.target_radius = (self.target_radius + steps * 0.1).clamp(MIN_RADIUS, MAX_RADIUS);
+        if !self.smooth {
+            self.tile_radius = self.target_radius;
+        }
+    }
+
+    /// The user moved the map by delta since the last frame. Call every frame the map is held,
+    /// even if the mouse did not move.
+    pub fn drag(&mut self, delta: Vec2, time: f32) {
+        self.offset += delta;
+        let elapsed = time - self.last_drag;
+        self.last_drag = time;
+        if !self.held {
+            // The map was just grabbed, there is no earlier movement to compare with.
+            self.held = true;
+            self.velocity = Vec2::ZERO;
+        } else if elapsed > 0.0 {
+            // Average over a few frames, so that a single jittery frame doesn't fling the map.
+            self.velocity = self.velocity.lerp(delta / elapsed, 0.5);
+        }
+    }
+
+    /// The user let go of the map.
+    pub fn release(&mut self) {
+        self.held = false;
+        if !self.smooth {
+            self.velocity = Vec2::ZERO;
+        }
+    }
+
+    /// Stop gliding, for example because the map was moved somewhere else.
+    pub fn stop(&mut self) {
+        self.velocity = Vec2::ZERO;
+    }
+
+    /// Move the map and the zoom on. Call once per frame.
+    pub fn update(&mut self, time: f32) {
+        let elapsed = (time - self.last_update).max(0.0);
+        self.last_update = time;
+
+        if !self.held {
+            self.offset += self.velocity * elapsed;
+            self.velocity *= (-FRICTION * elapsed).exp();
+            if self.velocity.length() < MIN_SPEED {
+                self.velocity = Vec2::ZERO;
+            }
+        }
+
+        if self.smooth {
+            let step = 1.0 - (-ZOOM_RATE * elapsed).exp();
+            self.tile_radius += (self.target_radius - self.tile_radius) * step;
+            if (self.target_radius - self.tile_radius).abs() < 0.01 {
+                self.tile_radius = self.target_radius;
+            }
+        } else {
+            self.tile_radius = self.target_radius;
+        }
+    }
+}
+
+/// Smallest and largest position of the tile centers, or None without tiles.
+pub fn tile_bounds(tiles: &[Tile], layout: &HexLayout) -> Option<(Vec2, Vec2)> {
+    let positions = tiles.iter().map(|t| layout.hex_to_world_pos(t.position));
+    let (min, max) = positions.fold((Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)), |acc, p| {
+        (acc.0.min(p), acc.1.max(p))
+    });
+    (min.x <= max.x).then_some((min, max))
+}
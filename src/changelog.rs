@@ -0,0 +1,83 @@
+//! What changed in every version of the app. The notes are shown once after the app was
+//! updated, and can be opened again from the panel.
+
+use notan::egui::{self, Align2};
+
+use crate::{
+    i18n::{tr, trf},
+    prefs, report,
+};
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// The version of which the user last saw the notes.
+const SEEN_FILE: &str = "version.txt";
+
+struct Release {
+    version: &'static str,
+    notes: &'static [&'static str],
+}
+
+/// Newest release first.
+const RELEASES: &[Release] = &[Release {
+    version: "0.1.0",
+    notes: &[
+        "Follow a game on the map and rule out tiles with the answers of every player.",
+        "Hints suggest questions, and the clues each player might have are listed.",
+        "House rules and custom clues change what clues are possible.",
+        "Export the map as an image, or a bug report when deductions look wrong.",
+    ],
+}];
+
+#[derive(Debug)]
+pub struct Changelog {
+    open: bool,
+}
+
+impl Default for Changelog {
+    fn default() -> Self {
+        Self {
+            open: prefs::load(SEEN_FILE).as_deref().map(str::trim) != Some(VERSION),
+        }
+    }
+}
+
+impl Changelog {
+    pub fn gui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(trf("Version {}", &[&VERSION])).weak());
+            if ui.small_button(tr("What's new")).clicked() {
+                self.open = true;
+            }
+        });
+    }
+
+    /// Show the notes of all releases, if they are open.
+    pub fn overlay(&mut self, ctx: &egui::Context) {
+        let mut open = self.open;
+        let mut close = false;
+        egui::Window::new(tr("What's new"))
+            .open(&mut open)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                for release in RELEASES {
+                    ui.label(egui::RichText::new(trf("Version {}", &[&release.version])).strong());
+                    for note in release.notes {
+                        ui.add(egui::Label::new(format!("• {}", tr(note))).wrap(true));
+                    }
+                }
+                if ui.button(tr("Close")).clicked() {
+                    close = true;
+                }
+            });
+        let open = open && !close;
+        if self.open && !open {
+            if let Err(e) = prefs::store(SEEN_FILE, VERSION) {
+                report::log(format!("Could not remember the version: {e}"));
+            }
+        }
+        self.open = open;
+    }
+}
@@ -0,0 +1,190 @@
+//! Reading clues from text like "within two spaces of a standing stone", so that users
+//! don't have to click through the combo boxes. Small typos are tolerated.
+
+use itertools::Itertools;
+use strum::IntoEnumIterator;
+
+use crate::{
+    i18n::{tr, trf},
+    model::{Animal, Clue, ClueKind, ClueTarget, Map, StructureColor, StructureKind, Terrain},
+};
+
+/// Words that name the parts of a clue, in English and German.
+const TERRAINS: &[(Terrain, &[&str])] = &[
+    (Terrain::Desert, &["desert", "wüste"]),
+    (Terrain::Forest, &["forest", "wald"]),
+    (Terrain::Water, &["water", "wasser"]),
+    (Terrain::Swamp, &["swamp", "sumpf"]),
+    (Terrain::Mountain, &["mountain", "gebirge", "berg"]),
+];
+const ANIMALS: &[(Animal, &[&str])] = &[
+    (Animal::Bear, &["bear", "bär", "bären"]),
+    (Animal::Cougar, &["cougar", "puma"]),
+];
+const STRUCTURE_KINDS: &[(StructureKind, &[&str])] = &[
+    (StructureKind::Shack, &["shack", "hütte"]),
+    (StructureKind::Stone, &["stone", "menhir", "stein"]),
+];
+const STRUCTURE_COLORS: &[(StructureColor, &[&str])] = &[
+    (StructureColor::White, &["white", "weiß", "weiss", "weißen"]),
+    (StructureColor::Green, &["green", "grün", "grünen"]),
+    (StructureColor::Blue, &["blue", "blau", "blauen"]),
+    (StructureColor::Black, &["black", "schwarz", "schwarzen"]),
+];
+/// Words for animals in general, as in "within one space of either animal territory".
+const ANY_ANIMAL: &[&str] = &["animal", "animals", "tier", "tiere", "tieren"];
+const NEGATIONS: &[&str] = &["not", "isn", "nicht", "kein", "keinem"];
+const DISTANCES: &[(u32, &[&str])] = &[
+    (1, &["1", "one", "ein", "eins", "einem", "einen"]),
+    (2, &["2", "two", "zwei"]),
+    (3, &["3", "three", "drei"]),
+];
+
+/// Read a clue from a text, with the animals and house rules of the map. Words that don't
+/// belong to a clue are ignored.
+pub fn parse_clue(text: &str, map: &Map) -> Result<Clue, String> {
+    let rules = &map.rules;
+    let all_animals = map.animals();
+    let words = text
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_owned)
+        .collect_vec();
+
+    let terrains = Terrain::iter()
+        .filter(|&t| mentioned(&words, TERRAINS, t))
+        .collect_vec();
+    let mut animals = all_animals
+        .iter()
+        .copied()
+        .filter(|&a| animal_mentioned(&words, a))
+        .collect_vec();
+    let kinds = StructureKind::iter()
+        .filter(|&k| mentioned(&words, STRUCTURE_KINDS, k))
+        .collect_vec();
+    let colors = StructureColor::iter()
+        .filter(|&c| mentioned(&words, STRUCTURE_COLORS, c))
+        .collect_vec();
+    let any_animal = words.iter().any(|w| matches_any(w, ANY_ANIMAL));
+    if any_animal && animals.is_empty() {
+        animals = all_animals.clone();
+    }
+
+    let topics = [
+        !terrains.is_empty(),
+        !animals.is_empty(),
+        !kinds.is_empty(),
+        !colors.is_empty(),
+    ];
+    if topics.iter().filter(|&&t| t).count() > 1 {
+        return Err(tr(
+            "A clue is only about terrain, animals, a type of structure or a structure color.",
+        )
+        .to_owned());
+    }
+
+    let kind = match (
+        terrains.as_slice(),
+        animals.as_slice(),
+        kinds.as_slice(),
+        colors.as_slice(),
+    ) {
+        ([terrain], ..) => ClueKind::within(ClueTarget::Terrain(*terrain), rules),
+        ([a, b], ..) => ClueKind::TwoTerrains(*a, *b),
+        (_, [animal], ..) => ClueKind::within(ClueTarget::Animal(*animal), rules),
+        (_, [_, _, ..], ..) if animals.len() == all_animals.len() => {
+            ClueKind::within(ClueTarget::EitherAnimal, rules)
+        }
+        (_, _, [kind], _) => ClueKind::within(ClueTarget::StructureKind(*kind), rules),
+        (_, _, _, [color]) => ClueKind::within(ClueTarget::StructureColor(*color), rules),
+        ([], [], [], []) => {
+            return Err(trf(
+                "Found no terrain, animal or structure in \"{}\".",
+                &[&text.trim()],
+            ))
+        }
+        _ => return Err(tr("A clue names at most two terrains and one structure.").to_owned()),
+    };
+
+    // Catch clues that don't exist in the game, like "within three spaces of water".
+    // "One of two terrains" mentions numbers that are not distances.
+    let distances = DISTANCES
+        .iter()
+        .filter(|(_, names)| words.iter().any(|w| names.contains(&w.as_str())))
+        .map(|(distance, _)| *distance)
+        .collect_vec();
+    if let ClueKind::WithinN { n: expected, .. } = kind {
+        if !distances.is_empty() && !distances.contains(&expected) {
+            return Err(trf(
+                "This clue only exists with a distance of {}.",
+                &[&expected],
+            ));
+        }
+    }
+
+    Ok(Clue {
+        kind,
+        inverted: words.iter().any(|w| NEGATIONS.contains(&w.as_str())),
+    })
+}
+
+/// Returns true if one of the words is a name of the value.
+fn mentioned<T: PartialEq>(words: &[String], names: &[(T, &[&str])], value: T) -> bool {
+    names
+        .iter()
+        .filter(|(v, _)| *v == value)
+        .any(|(_, names)| words.iter().any(|w| matches_any(w, names)))
+}
+
+/// Like [mentioned], but custom animals are known by their own name.
+fn animal_mentioned(words: &[String], animal: Animal) -> bool {
+    match animal {
+        Animal::Custom(_) => {
+            let name = animal.to_string().to_lowercase();
+            words.iter().any(|w| matches_any(w, &[name.as_str()]))
+        }
+        _ => mentioned(words, ANIMALS, animal),
+    }
+}
+
+/// Returns true if the word is one of the names, allowing for typos in longer words.
+fn matches_any(word: &str, names: &[&str]) -> bool {
+    let allowed = match word.chars().count() {
+        0..=4 => 0,
+        5..=7 => 1,
+        _ => 2,
+    };
+    // Also allow plurals like "mountains".
+    let singular = word.strip_suffix('s').unwrap_or(word);
+    names
+        .iter()
+        .any(|name| edit_distance(word, name).min(edit_distance(singular, name)) <= allowed)
+}
+
+/// Number of characters that have to be inserted, removed, replaced or swapped with their
+/// neighbor to turn one word into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect_vec();
+    let b = b.chars().collect_vec();
+    // d[i][j] is the distance between the first i characters of a and the first j of b.
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
@@ -0,0 +1,112 @@
+//! Clues that are not in the game, for expansions and fan variants. They are loaded from a
+//! clue file and deduced like every other clue.
+//!
+//! A clue file is JSON with a list of clues. Every clue has a text, a distance and a condition
+//! for the tiles it is about:
+//!
+//! ```json
+//! [{"text": "within two spaces of water or a white structure", "distance": 2,
+//!   "condition": {"Any": [{"Terrain": "Water"}, {"StructureColor": "White"}]}}]
+//! ```
+//!
+//! The loaded clues are part of the house rules of a map. A clue carries its definition with
+//! it, so it means the same in every game and in every file it is saved to.
+
+use notan::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    houserules::{HouseRules, MAX_CLUE_DISTANCE},
+    i18n::{tr, trf},
+    model::{Animal, StructureColor, StructureKind, Terrain, Tile},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomClue {
+    /// How the clue is shown.
+    pub text: String,
+    /// The creature is within this many spaces of a tile that meets the condition.
+    #[serde(default)]
+    pub distance: u32,
+    pub condition: Condition,
+}
+
+/// What a tile must look like.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Condition {
+    Terrain(Terrain),
+    Animal(Animal),
+    /// Territory of any animal.
+    AnyAnimal,
+    StructureKind(StructureKind),
+    StructureColor(StructureColor),
+    /// Any structure.
+    AnyStructure,
+    /// At least one of the conditions.
+    Any(Vec<Condition>),
+    /// All of the conditions.
+    All(Vec<Condition>),
+    Not(Box<Condition>),
+}
+
+impl Condition {
+    pub fn is_met(&self, tile: &Tile) -> bool {
+        match self {
+            Condition::Terrain(terrain) => tile.terrain == *terrain,
+            Condition::Animal(animal) => tile.animal == Some(*animal),
+            Condition::AnyAnimal => tile.animal.is_some(),
+            Condition::StructureKind(kind) => tile.structure.is_some_and(|s| s.kind == *kind),
+            Condition::StructureColor(color) => tile.structure.is_some_and(|s| s.color == *color),
+            Condition::AnyStructure => tile.structure.is_some(),
+            Condition::Any(conditions) => conditions.iter().any(|c| c.is_met(tile)),
+            Condition::All(conditions) => conditions.iter().all(|c| c.is_met(tile)),
+            Condition::Not(condition) => !condition.is_met(tile),
+        }
+    }
+}
+
+/// Keep a clue for the rest of the program, so that clues referring to it can be copied like
+/// every other clue. Clues are only loaded by hand, so the memory stays small.
+pub fn keep(clue: CustomClue) -> &'static CustomClue {
+    Box::leak(Box::new(clue))
+}
+
+/// Read a clue file.
+pub fn load(json: &str) -> Result<Vec<&'static CustomClue>, String> {
+    let clues: Vec<CustomClue> = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    if let Some(clue) = clues.iter().find(|c| c.distance > MAX_CLUE_DISTANCE) {
+        return Err(trf(
+            "The clue \"{}\" is farther than {} spaces.",
+            &[&clue.text, &MAX_CLUE_DISTANCE],
+        ));
+    }
+    Ok(clues.into_iter().map(keep).collect())
+}
+
+/// Loading clue files into the house rules and a list of the loaded clues.
+pub fn gui(ui: &mut egui::Ui, rules: &mut HouseRules) {
+    let id = egui::Id::new("custom-clues-file");
+    let (mut text, mut message): (String, Option<String>) =
+        ui.data().get_temp(id).unwrap_or_default();
+
+    ui.add(egui::Label::new(tr("Paste the contents of a clue file.")).wrap(true));
+    ui.text_edit_multiline(&mut text);
+    if ui.button(tr("Load")).clicked() {
+        match load(&text) {
+            Ok(clues) => {
+                text.clear();
+                message = Some(trf("Added {} clues.", &[&clues.len()]));
+                rules.custom_clues.extend(clues);
+            }
+            Err(e) => message = Some(trf("Can't load clues: {}", &[&e])),
+        }
+    }
+    if let Some(message) = &message {
+        ui.add(egui::Label::new(message).wrap(true));
+    }
+    ui.data().insert_temp(id, (text, message));
+
+    for clue in &rules.custom_clues {
+        ui.add(egui::Label::new(format!("• {}", clue.text)).wrap(true));
+    }
+}
@@ -0,0 +1,183 @@
+//! How tiles are drawn once the cryptid can't be on them. Tiles change their look in a short
+//! animation and flash, so that it can be seen which tiles changed after a clue was toggled.
+
+use std::collections::HashMap;
+
+use hexx::Hex;
+use notan::{egui, prelude::Color};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::{
+    i18n::{tr, tr_name},
+    model::Tile,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumIter, Display)]
+pub enum EliminationStyle {
+    /// Smaller and faded.
+    #[default]
+    Shrunk,
+    /// Gray instead of the terrain color.
+    Desaturated,
+    /// Lines across the tile.
+    #[strum(to_string = "Cross-hatched")]
+    CrossHatched,
+    /// Not drawn at all.
+    Hidden,
+}
+
+#[derive(Debug, Clone)]
+pub struct Elimination {
+    pub style: EliminationStyle,
+    /// Seconds a tile takes to change its look. Zero changes it immediately.
+    pub duration: f32,
+    /// How far every tile is in its animation, from 0 for possible to 1 for eliminated.
+    progress: HashMap<Hex, f32>,
+    /// Time of the last update.
+    last_update: f32,
+    /// True if tiles flash when they were ruled out or became possible again.
+    pub flash: bool,
+    /// Whether every tile was ruled out at the last update.
+    eliminated: HashMap<Hex, bool>,
+    /// Tiles that changed and when they did.
+    changed: HashMap<Hex, f32>,
+}
+
+/// Seconds a changed tile flashes.
+const FLASH_SECONDS: f32 = 1.0;
+
+impl Default for Elimination {
+    fn default() -> Self {
+        Self {
+            style: EliminationStyle::default(),
+            duration: 0.25,
+            progress: HashMap::new(),
+            last_update: 0.0,
+            flash: true,
+            eliminated: HashMap::new(),
+            changed: HashMap::new(),
+        }
+    }
+}
+
+/// How to draw a tile at some point in its animation.
+#[derive(Debug, Clone, Copy)]
+pub struct TileLook {
+    pub scale: f32,
+    /// Opacity of the terrain and animal territory.
+    pub alpha: f32,
+    /// How much of the terrain color is replaced with gray.
+    pub gray: f32,
+    /// Opacity of the terrain icon.
+    pub icon: f32,
+    /// Opacity of the hatching lines.
+    pub hatching: f32,
+}
+
+impl Elimination {
+    pub fn gui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(tr("Ruled out tiles"));
+            egui::ComboBox::new("elimination-style", "")
+                .selected_text(tr_name(self.style))
+                .show_ui(ui, |ui| {
+                    for style in EliminationStyle::iter() {
+                        ui.selectable_value(&mut self.style, style, tr_name(style));
+                    }
+                });
+        });
+        ui.add(egui::Slider::new(&mut self.duration, 0.0..=1.0).text(tr("Animation seconds")));
+        ui.checkbox(&mut self.flash, tr("Flash changed tiles"));
+    }
+
+    /// Move the animation of every tile towards its current state. Call once per frame.
+    pub fn update(&mut self, tiles: &[Tile], time: f32) {
+        let step = if self.duration > 0.0 {
+            (time - self.last_update) / self.duration
+        } else {
+            1.0
+        };
+        self.last_update = time;
+
+        let mut progress = HashMap::with_capacity(tiles.len());
+        for tile in tiles {
+            let target = if tile.small { 1.0 } else { 0.0 };
+            // New tiles start in their state, there is nothing to see change.
+            let current = self.progress.get(&tile.position).copied().unwrap_or(target);
+            if self
+                .eliminated
+                .get(&tile.position)
+                .is_some_and(|&e| e != tile.small)
+            {
+                self.changed.insert(tile.position, time);
+            }
+            let next = if current < target {
+                (current + step).min(target)
+            } else {
+                (current - step).max(target)
+            };
+            progress.insert(tile.position, next);
+        }
+        self.progress = progress;
+        self.eliminated = tiles.iter().map(|t| (t.position, t.small)).collect();
+        self.changed
+            .retain(|hex, since| time - *since < FLASH_SECONDS && self.progress.contains_key(hex));
+    }
+
+    /// How strong the tile flashes right now, from 0 for not at all to 1.
+    pub fn flash(&self, hex: Hex, time: f32) -> f32 {
+        match self.changed.get(&hex) {
+            Some(since) if self.flash => 1.0 - (time - since) / FLASH_SECONDS,
+            _ => 0.0,
+        }
+    }
+
+    /// How to draw the tile right now.
+    pub fn look(&self, tile: &Tile) -> TileLook {
+        let fallback = if tile.small { 1.0 } else { 0.0 };
+        let t = self
+            .progress
+            .get(&tile.position)
+            .copied()
+            .unwrap_or(fallback);
+        // Ease in and out, so that the change does not start or end abruptly.
+        let t = t * t * (3.0 - 2.0 * t);
+
+        let possible = TileLook {
+            scale: 1.0,
+            alpha: 1.0,
+            gray: 0.0,
+            icon: 1.0,
+            hatching: 0.0,
+        };
+        match self.style {
+            EliminationStyle::Shrunk => TileLook {
+                scale: 1.0 - 0.3 * t,
+                alpha: 1.0 - 0.4 * t,
+                icon: 1.0 - t,
+                ..possible
+            },
+            EliminationStyle::Desaturated => TileLook {
+                gray: 0.8 * t,
+                icon: 1.0 - 0.7 * t,
+                ..possible
+            },
+            EliminationStyle::CrossHatched => TileLook {
+                hatching: t,
+                ..possible
+            },
+            EliminationStyle::Hidden => TileLook {
+                alpha: 1.0 - t,
+                icon: 1.0 - t,
+                ..possible
+            },
+        }
+    }
+}
+
+/// Mix the color with gray of the same brightness.
+pub fn desaturate(color: Color, amount: f32) -> Color {
+    let gray = 0.3 * color.r + 0.59 * color.g + 0.11 * color.b;
+    let mix = |c: f32| c + (gray - c) * amount;
+    Color::new(mix(color.r), mix(color.g), mix(color.b), color.a)
+}
@@ -0,0 +1,51 @@
+//! Getting files out of the app. Native builds write into the working directory,
+//! the web build lets the browser download them.
+
+/// Save the bytes as a file with the given name. Returns a description of where the file went.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(name: &str, bytes: &[u8]) -> Result<String, String> {
+    let path = std::env::current_dir()
+        .map_err(|e| e.to_string())?
+        .join(name);
+    std::fs::write(&path, bytes).map_err(|e| e.to_string())?;
+    Ok(crate::i18n::trf("Saved {}", &[&path.display()]))
+}
+
+/// Save the bytes as a file with the given name. Returns a description of where the file went.
+#[cfg(target_arch = "wasm32")]
+pub fn save(name: &str, bytes: &[u8]) -> Result<String, String> {
+    web::download(name, bytes).map_err(|e| format!("{e:?}"))?;
+    Ok(crate::i18n::trf("Downloaded {}", &[&name]))
+}
+
+#[cfg(target_arch = "wasm32")]
+mod web {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(inline_js = r#"
+        export function download(name, bytes) {
+            const url = URL.createObjectURL(new Blob([bytes]));
+            const a = document.createElement("a");
+            a.href = url;
+            a.download = name;
+            a.click();
+            URL.revokeObjectURL(url);
+        }
+    "#)]
+    extern "C" {
+        #[wasm_bindgen(catch)]
+        pub fn download(name: &str, bytes: &[u8]) -> Result<(), JsValue>;
+    }
+}
+
+/// Encode RGBA pixels as a PNG.
+pub fn png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>, String> {
+    let mut data = Vec::new();
+    let mut encoder = png::Encoder::new(&mut data, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(rgba).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(data)
+}
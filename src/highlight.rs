@@ -0,0 +1,55 @@
+//! Spinning rings around tiles. Rings of different channels can be shown at the same time,
+//! each in its own color and size, so that a selected tile and the tiles of a hint can be told
+//! apart.
+
+use notan::egui;
+use strum::{Display, EnumIter};
+
+use crate::i18n::tr;
+
+/// What a highlight means.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, Display)]
+pub enum HighlightChannel {
+    /// Tiles the user clicked.
+    Selection,
+    /// Tiles a hint or the analysis is about.
+    Hint,
+    /// Tiles a clue would be confirmed by.
+    #[strum(to_string = "Clue preview")]
+    Preview,
+}
+
+impl HighlightChannel {
+    /// Size of the ring relative to the tile. Rings of different channels are nested.
+    pub fn radius(self) -> f32 {
+        match self {
+            HighlightChannel::Selection => 0.8,
+            HighlightChannel::Hint => 0.68,
+            HighlightChannel::Preview => 0.56,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Highlighting {
+    /// Rotations of the rings in radians per second.
+    pub speed: f32,
+    /// Width of the rings, relative to the other lines on the map.
+    pub thickness: f32,
+}
+
+impl Default for Highlighting {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            thickness: 1.0,
+        }
+    }
+}
+
+impl Highlighting {
+    pub fn gui(&mut self, ui: &mut egui::Ui) {
+        ui.add(egui::Slider::new(&mut self.speed, 0.0..=5.0).text(tr("Highlight speed")));
+        ui.add(egui::Slider::new(&mut self.thickness, 0.5..=4.0).text(tr("Highlight thickness")));
+    }
+}
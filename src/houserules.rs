@@ -0,0 +1,109 @@
+//! Distances of the clues and clues from clue files, for fan variants of the game that use other
+//! ranges or clues than the official ones. The rules are part of the map, so they are chosen before the game starts
+//! and stay the same until it ends.
+
+use notan::egui;
+use serde::Serialize;
+
+use crate::{
+    customclues::CustomClue,
+    i18n::tr,
+    model::{Animal, ClueTarget, StructureColor, StructureKind, Terrain},
+};
+
+/// Largest distance a clue can have.
+pub const MAX_CLUE_DISTANCE: u32 = 4;
+
+/// Distance of the clues for each kind of target, and the clues that are not in the game.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HouseRules {
+    pub terrain: u32,
+    pub either_animal: u32,
+    pub animal: u32,
+    pub structure_kind: u32,
+    pub structure_color: u32,
+    /// Clues from clue files, see [crate::customclues].
+    pub custom_clues: Vec<&'static CustomClue>,
+}
+
+impl HouseRules {
+    /// Distances of the official game.
+    pub const OFFICIAL: Self = Self {
+        terrain: 1,
+        either_animal: 1,
+        animal: 2,
+        structure_kind: 2,
+        structure_color: 3,
+        custom_clues: Vec::new(),
+    };
+
+    pub fn distance(&self, target: ClueTarget) -> u32 {
+        match target {
+            ClueTarget::Terrain(_) => self.terrain,
+            ClueTarget::EitherAnimal => self.either_animal,
+            ClueTarget::Animal(_) => self.animal,
+            ClueTarget::StructureKind(_) => self.structure_kind,
+            ClueTarget::StructureColor(_) => self.structure_color,
+        }
+    }
+
+    fn distance_mut(&mut self, target: ClueTarget) -> &mut u32 {
+        match target {
+            ClueTarget::Terrain(_) => &mut self.terrain,
+            ClueTarget::EitherAnimal => &mut self.either_animal,
+            ClueTarget::Animal(_) => &mut self.animal,
+            ClueTarget::StructureKind(_) => &mut self.structure_kind,
+            ClueTarget::StructureColor(_) => &mut self.structure_color,
+        }
+    }
+}
+
+impl Default for HouseRules {
+    fn default() -> Self {
+        Self::OFFICIAL
+    }
+}
+
+/// Settings for the distances of the clues.
+pub fn gui(ui: &mut egui::Ui, rules: &mut HouseRules) {
+    ui.add(
+        egui::Label::new(tr("Distances of the clues, for fan variants of the game.")).wrap(true),
+    );
+    let targets = [
+        ("Terrain", ClueTarget::Terrain(Terrain::Desert)),
+        ("Either animal", ClueTarget::EitherAnimal),
+        ("Animal", ClueTarget::Animal(Animal::Bear)),
+        (
+            "Structure type",
+            ClueTarget::StructureKind(StructureKind::Shack),
+        ),
+        (
+            "Structure color",
+            ClueTarget::StructureColor(StructureColor::White),
+        ),
+    ];
+    egui::Grid::new("house-rules-grid").show(ui, |ui| {
+        for (name, target) in targets {
+            ui.label(tr(name));
+            ui.add(
+                egui::DragValue::new(rules.distance_mut(target)).clamp_range(0..=MAX_CLUE_DISTANCE),
+            );
+            ui.end_row();
+        }
+    });
+    // Only the distances, the custom clues have their own list.
+    let official = |rules: &HouseRules, target| {
+        rules.distance(target) == HouseRules::OFFICIAL.distance(target)
+    };
+    if ui
+        .add_enabled(
+            !targets.iter().all(|&(_, target)| official(rules, target)),
+            egui::Button::new(tr("Official rules")),
+        )
+        .clicked()
+    {
+        for (_, target) in targets {
+            *rules.distance_mut(target) = HouseRules::OFFICIAL.distance(target);
+        }
+    }
+}
@@ -0,0 +1,570 @@
+//! Translations of the texts in the app.
+//!
+//! Texts are written in English in the code and looked up in the table of the current language.
+//! Texts without a translation are shown in English.
+
+use std::{fmt, sync::Mutex};
+
+use notan::egui;
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+/// Languages the app can be shown in. Every language is named in itself.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumIter, Display)]
+pub enum Language {
+    #[default]
+    English,
+    #[strum(to_string = "Deutsch")]
+    German,
+}
+
+static LANGUAGE: Mutex<Language> = Mutex::new(Language::English);
+
+pub fn language() -> Language {
+    LANGUAGE.lock().map(|l| *l).unwrap_or_default()
+}
+
+pub fn set_language(language: Language) {
+    if let Ok(mut current) = LANGUAGE.lock() {
+        *current = language;
+    }
+}
+
+/// Translate the text into the current language.
+pub fn tr(text: &str) -> &str {
+    let table = match language() {
+        Language::English => return text,
+        Language::German => GERMAN,
+    };
+    table
+        .iter()
+        .find(|(english, _)| *english == text)
+        .map(|(_, translated)| *translated)
+        .unwrap_or(text)
+}
+
+/// Translated name of a value, like the [fmt::Display] of an enum.
+pub fn tr_name(value: impl fmt::Display) -> String {
+    tr(&value.to_string()).to_owned()
+}
+
+/// Translate the text and fill in the placeholders. `{}` is replaced with the next argument
+/// and `{0}` with the first one, so that translations can put them in a different order.
+pub fn trf(text: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut out = String::new();
+    let mut next = 0;
+    let mut rest = tr(text);
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let index = match &rest[start + 1..end] {
+            "" => {
+                next += 1;
+                Some(next - 1)
+            }
+            index => index.parse().ok(),
+        };
+        match index.and_then(|i| args.get(i)) {
+            Some(arg) => out.push_str(&arg.to_string()),
+            None => out.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Selection of the language.
+pub fn gui(ui: &mut egui::Ui) {
+    ui.horizontal(|ui| {
+        ui.label(tr("Language"));
+        let mut selected = language();
+        egui::ComboBox::new("language-select", "")
+            .selected_text(selected.to_string())
+            .show_ui(ui, |ui| {
+                for language in Language::iter() {
+                    ui.selectable_value(&mut selected, language, language.to_string());
+                }
+            });
+        if selected != language() {
+            set_language(selected);
+        }
+    });
+}
+
+/// English texts and their German translations.
+const GERMAN: &[(&str, &str)] = &[
+    ("Language", "Sprache"),
+    ("Settings", "Einstellungen"),
+    ("Group answers by kind", "Antworten nach Art gruppieren"),
+    ("Table display", "Tischanzeige"),
+    ("Show only the map, for a TV or projector. Press {} to leave.", "Nur die Karte zeigen, für einen Fernseher oder Beamer. Zum Verlassen {} drücken."),
+    ("Legend", "Legende"),
+    ("Export image", "Bild exportieren"),
+    ("Something went wrong", "Etwas ist schiefgelaufen"),
+    ("Export a report and attach it to an issue on GitHub to help fix this.", "Exportiere einen Bericht und hänge ihn an ein Issue auf GitHub, damit der Fehler behoben werden kann."),
+    ("Export report", "Bericht exportieren"),
+    ("Export failed: {}", "Export fehlgeschlagen: {}"),
+    ("Saved {}", "Gespeichert unter {}"),
+    ("Downloaded {}", "{} heruntergeladen"),
+    ("Dismiss", "Schließen"),
+    ("Are you sure?", "Bist du sicher?"),
+    ("Continue", "Weiter"),
+    ("Cancel", "Abbrechen"),
+    ("Disc: the cryptid may be there (yes)", "Scheibe: Das Kryptid kann dort sein (ja)"),
+    ("Cube: the cryptid is not there (no)", "Würfel: Das Kryptid ist nicht dort (nein)"),
+    ("Letters are the initials of the players. A dot marks answers the app filled in.", "Buchstaben sind die Initialen der Spieler. Ein Punkt markiert Antworten, die die App eingetragen hat."),
+    ("Desert", "Wüste"),
+    ("Forest", "Wald"),
+    ("Water", "Wasser"),
+    ("Swamp", "Sumpf"),
+    ("Mountain", "Gebirge"),
+    ("Bear", "Bär"),
+    ("Cougar", "Puma"),
+    ("White", "Weiß"),
+    ("Green", "Grün"),
+    ("Blue", "Blau"),
+    ("Black", "Schwarz"),
+    ("Red", "Rot"),
+    ("Purple", "Lila"),
+    ("Orange", "Orange"),
+    ("Abandoned Shack", "Verlassene Hütte"),
+    ("Standing Stone", "Menhir"),
+    ("Unknown", "Unbekannt"),
+    ("Yes", "Ja"),
+    ("No", "Nein"),
+    ("Dark", "Dunkel"),
+    ("Light", "Hell"),
+    ("Custom", "Eigene"),
+    ("{} (rotated)", "{} (gedreht)"),
+    ("Piece {}", "Teil {}"),
+    ("not {}", "nicht {}"),
+    ("on {} or {}", "auf {} oder {}"),
+    ("Some Player", "Jemand"),
+    ("Find the cryptid with at most 1 question.", "Finde das Kryptid mit höchstens einer Frage."),
+    ("Find the cryptid with at most {} questions.", "Finde das Kryptid mit höchstens {} Fragen."),
+    ("Show piece seams", "Kanten der Teile zeigen"),
+    ("Seam opacity", "Deckkraft der Kanten"),
+    ("Width of a tile", "Breite eines Felds"),
+    ("Measure a tile of your board, so that the pieces get their real size", "Miss ein Feld deines Spielbretts, damit die Teile ihre echte Größe bekommen"),
+    ("Theme", "Design"),
+    ("Background", "Hintergrund"),
+    ("Show timer", "Uhr zeigen"),
+    ("Seconds per turn", "Sekunden pro Zug"),
+    ("0 for no countdown", "0 für keinen Countdown"),
+    ("Next turn", "Nächster Zug"),
+    ("Or press {}", "Oder {} drücken"),
+    ("Restart", "Neu starten"),
+    ("Game {}", "Spiel {}"),
+    (", turn {}", ", Zug {}"),
+    (", turn over by {}", ", Zug um {} überzogen"),
+    ("Helper/cheat tool for the board game Cryptid. Check GitHub for more information.", "Hilfs- und Schummelwerkzeug für das Brettspiel Cryptid. Mehr Informationen gibt es auf GitHub."),
+    ("Map", "Karte"),
+    ("Size", "Größe"),
+    ("Reset", "Zurücksetzen"),
+    ("Drag pieces on the map to arrange them. Click a piece to rotate it. Shift-click a piece to swap in a piece that is not on the map.", "Ziehe Teile auf der Karte, um sie anzuordnen. Klicke ein Teil an, um es zu drehen. Klicke mit Umschalt, um es gegen ein Teil zu tauschen, das nicht auf der Karte liegt."),
+    ("Select a different piece for every slot to continue", "Wähle für jeden Platz ein anderes Teil, um fortzufahren"),
+    ("Players", "Spieler"),
+    ("Add", "Hinzufügen"),
+    ("Ready", "Fertig"),
+    ("Puzzles", "Rätsel"),
+    ("Solve puzzles without the physical game", "Rätsel ohne das echte Spiel lösen"),
+    ("Edit pieces", "Teile bearbeiten"),
+    ("Create your own pieces for the map", "Eigene Teile für die Karte erstellen"),
+    ("Add 3 to 5 players to continue", "Füge 3 bis 5 Spieler hinzu, um fortzufahren"),
+    ("Use colors only once to continue", "Verwende jede Farbe nur einmal, um fortzufahren"),
+    ("Enter a name for every player to continue", "Gib jedem Spieler einen Namen, um fortzufahren"),
+    ("Enter a different name for every player to continue", "Gib jedem Spieler einen anderen Namen, um fortzufahren"),
+    ("The map has {} pieces and there are {} players. Both can't be changed after this.", "Die Karte hat {} Teile und es gibt {} Spieler. Beides kann danach nicht mehr geändert werden."),
+    ("Difficulty: {}", "Schwierigkeit: {}"),
+    ("Play", "Spielen"),
+    ("Back", "Zurück"),
+    ("Load scenario file", "Szenariodatei laden"),
+    ("Paste the contents of a scenario file.", "Füge den Inhalt einer Szenariodatei ein."),
+    ("Load", "Laden"),
+    ("Can't load scenario: {}", "Szenario kann nicht geladen werden: {}"),
+    ("Piece editor", "Teile-Editor"),
+    ("Click a tile to change its terrain. Shift-click it to add an animal territory.", "Klicke ein Feld an, um sein Gelände zu ändern. Klicke mit Umschalt, um ein Tierrevier hinzuzufügen."),
+    ("Name", "Name"),
+    ("Save", "Speichern"),
+    ("Export", "Exportieren"),
+    ("Clear", "Leeren"),
+    ("The piece is not saved and will be lost.", "Das Teil ist nicht gespeichert und geht verloren."),
+    ("Enter a name to save the piece.", "Gib einen Namen ein, um das Teil zu speichern."),
+    ("Saved. Piece {} can now be selected for the map.", "Gespeichert. Teil {} kann jetzt für die Karte ausgewählt werden."),
+    ("Saving failed: {}", "Speichern fehlgeschlagen: {}"),
+    ("Structures", "Gebäude"),
+    ("{} structures", "Gebäude in {}"),
+    ("Drag structures into position on the map. Or select a structure here or on the map, and click where it should go.", "Ziehe die Gebäude auf der Karte an ihren Platz. Oder wähle ein Gebäude hier oder auf der Karte aus und klicke, wo es hin soll."),
+    ("{} {}", "{1} ({0})"),
+    ("Randomize structures", "Gebäude zufällig verteilen"),
+    ("Place the structures like a setup card would", "Die Gebäude wie eine Aufbaukarte platzieren"),
+    ("Seed", "Startwert"),
+    ("New seed", "Neuer Startwert"),
+    ("Practice", "Üben"),
+    ("Play against 2 to 4 simulated players", "Gegen 2 bis 4 simulierte Spieler spielen"),
+    ("Practice needs 3 to 5 players", "Zum Üben braucht es 3 bis 5 Spieler"),
+    ("{} structures are placed. They can't be moved after this.", "{} Gebäude sind platziert. Sie können danach nicht mehr verschoben werden."),
+    ("Place one shack and one standing stone for {} to continue", "Platziere eine Hütte und einen Menhir in {}, um fortzufahren"),
+    ("The structures on {} and {} are next to each other. That is unusual for official setups, check your setup card.", "Die Gebäude auf {} und {} liegen nebeneinander. Das ist für offizielle Aufbauten ungewöhnlich, prüfe deine Aufbaukarte."),
+    ("Both {} structures stand on {}. That is unusual for official setups, check your setup card.", "Beide Gebäude in {} stehen auf {}. Das ist für offizielle Aufbauten ungewöhnlich, prüfe deine Aufbaukarte."),
+    ("Easy", "Leicht"),
+    ("Medium", "Mittel"),
+    ("Hard", "Schwer"),
+    ("{}, {} questions on average ({} to {})", "{}, {} Fragen im Durchschnitt ({} bis {})"),
+    ("Random", "Zufällig"),
+    ("Greedy", "Gierig"),
+    ("Entropy", "Entropie"),
+    ("The game started. It's your turn.", "Das Spiel hat begonnen. Du bist dran."),
+    ("You are {}. Your clue: the cryptid is {}.", "Du bist {}. Dein Hinweis: Das Kryptid ist {}."),
+    ("Difficulty from here: {}", "Schwierigkeit ab hier: {}"),
+    ("It's {}'s turn.", "{} ist dran."),
+    ("{} asks you about {}. Could the cryptid be there?", "{} fragt dich nach {}. Könnte das Kryptid dort sein?"),
+    ("Select a tile where your clue rules out the cryptid.", "Wähle ein Feld, auf dem dein Hinweis das Kryptid ausschließt."),
+    ("Place 'no' on {}", "„Nein“ auf {} setzen"),
+    ("{} found the cryptid on {}.", "{} hat das Kryptid auf {} gefunden."),
+    ("You needed {} questions. Well done!", "Du hast {} Fragen gebraucht. Gut gemacht!"),
+    ("You needed {} questions. Try again with at most {}.", "Du hast {} Fragen gebraucht. Versuche es noch einmal mit höchstens {}."),
+    ("Reveal solution", "Lösung zeigen"),
+    ("The cryptid is on {}.", "Das Kryptid ist auf {}."),
+    ("Opponents", "Gegner"),
+    ("Your turn. Select a tile to ask about or search.", "Du bist dran. Wähle ein Feld, nach dem du fragst oder das du durchsuchst."),
+    ("Your turn. What about {}?", "Du bist dran. Was ist mit {}?"),
+    ("Ask {}", "{} fragen"),
+    ("You can't ask about a tile with a 'no'.", "Du kannst nicht nach einem Feld mit einem „Nein“ fragen."),
+    ("Search", "Suchen"),
+    ("You can't search a tile with a 'no'.", "Du kannst kein Feld mit einem „Nein“ durchsuchen."),
+    ("Your own clue rules out this tile.", "Dein eigener Hinweis schließt dieses Feld aus."),
+    ("{} asks {} about {}.", "{} fragt {} nach {}."),
+    ("{} answers {}.", "{} antwortet {}."),
+    ("{} searches {}.", "{} durchsucht {}."),
+    ("The search failed.", "Die Suche ist gescheitert."),
+    ("{} found the cryptid!", "{} hat das Kryptid gefunden!"),
+    ("That's not what your clue says.", "Das sagt dein Hinweis nicht."),
+    ("You answer {}.", "Du antwortest {}."),
+    ("Your clue allows the cryptid there.", "Dein Hinweis erlaubt das Kryptid dort."),
+    ("You already answered on that tile.", "Du hast auf diesem Feld schon geantwortet."),
+    ("You place a 'no' on {}.", "Du setzt ein „Nein“ auf {}."),
+    ("{} places a 'no' on {}.", "{} setzt ein „Nein“ auf {}."),
+    ("{} passes.", "{} passt."),
+    ("It's your turn.", "Du bist dran."),
+    ("Enable inverted clues", "Umgekehrte Hinweise aktivieren"),
+    ("Cryptid found", "Kryptid gefunden"),
+    ("{} answers are entered. They can't be changed after this.", "{} Antworten sind eingetragen. Sie können danach nicht mehr geändert werden."),
+    ("Cheat", "Schummeln"),
+    ("You are", "Du bist"),
+    ("Hide my deductions", "Meine Schlüsse verbergen"),
+    ("Don't show anything about your clue, for playing on a shared screen", "Nichts über deinen Hinweis zeigen, zum Spielen an einem gemeinsamen Bildschirm"),
+    ("Select region for hints", "Bereich für Tipps auswählen"),
+    ("Click or drag over tiles to add them to the region. Click again to remove them.", "Klicke Felder an oder ziehe darüber, um sie zum Bereich hinzuzufügen. Klicke erneut, um sie zu entfernen."),
+    ("Hints only use the {} tiles of the region.", "Tipps verwenden nur die {} Felder des Bereichs."),
+    ("Refresh", "Aktualisieren"),
+    ("No hints available or map changed.", "Keine Tipps verfügbar oder die Karte hat sich geändert."),
+    ("Show", "Zeigen"),
+    ("Projection", "Vorschau"),
+    ("Opponent", "Gegner"),
+    ("None", "Keiner"),
+    ("Highlighted clues would reveal the cryptid if they were confirmed.", "Hervorgehobene Hinweise würden das Kryptid verraten, wenn sie bestätigt würden."),
+    ("{}: {} tiles", "{}: {} Felder"),
+    ("Summary", "Übersicht"),
+    ("Divide by", "Aufteilen nach"),
+    ("Board pieces", "Spielplanteile"),
+    ("Selected region", "Ausgewählter Bereich"),
+    ("The cryptid can't be anywhere. Check the answers.", "Das Kryptid kann nirgends sein. Prüfe die Antworten."),
+    ("{}: {} tiles, {}%", "{}: {} Felder, {} %"),
+    ("Inside the region", "Innerhalb des Bereichs"),
+    ("Outside the region", "Außerhalb des Bereichs"),
+    ("Fix map", "Karte korrigieren"),
+    ("Correct a tile that does not match the physical map. Answers are kept.", "Korrigiere ein Feld, das nicht zur echten Karte passt. Antworten bleiben erhalten."),
+    ("Select a tile to fix it.", "Wähle ein Feld, um es zu korrigieren."),
+    ("Terrain", "Gelände"),
+    ("Territory", "Revier"),
+    ("Answers", "Antworten"),
+    ("Guided turns", "Geführte Züge"),
+    ("Enter turns step by step instead of editing answers", "Züge Schritt für Schritt eingeben, statt Antworten zu bearbeiten"),
+    ("Fill in my answers", "Meine Antworten eintragen"),
+    ("Fill in my answer on the selected tile when someone asks me", "Meine Antwort auf dem gewählten Feld eintragen, wenn mich jemand fragt"),
+    ("Pre-fill all", "Alle vorab eintragen"),
+    ("Fill in my answers on every tile right away", "Meine Antworten sofort auf jedem Feld eintragen"),
+    ("Asked by", "Gefragt von"),
+    ("Nobody", "Niemand"),
+    ("{} was answered with a \"no\" and has to place a cube where their clue does not apply.", "{} hat ein „Nein“ bekommen und muss einen Würfel setzen, wo der eigene Hinweis nicht zutrifft."),
+    ("Set answers for the selected tile.", "Antworten für das ausgewählte Feld festlegen."),
+    ("Filled in", "Eingetragen"),
+    ("Select a tile to place anwers.", "Wähle ein Feld, um Antworten zu setzen."),
+    ("Whose turn is it?", "Wer ist dran?"),
+    ("Does {} ask a question or search?", "Stellt {} eine Frage oder sucht?"),
+    ("Question", "Frage"),
+    ("Select the tile on the map.", "Wähle das Feld auf der Karte."),
+    ("Continue with {}", "Weiter mit {}"),
+    ("Whom did {} ask about {}, and what was the answer?", "Wen hat {} nach {} gefragt, und was war die Antwort?"),
+    ("Select the tile where {} placed their cube.", "Wähle das Feld, auf das {} den Würfel gesetzt hat."),
+    ("Cube on {}", "Würfel auf {}"),
+    ("What does {} answer?", "Was antwortet {}?"),
+    ("The cryptid is on {}!", "Das Kryptid ist auf {}!"),
+    ("Start the turn over", "Zug neu beginnen"),
+    ("{} already placed a cube on {}. Nobody may ask about it or place a cube there.", "{} hat schon einen Würfel auf {} gesetzt. Niemand darf danach fragen oder dort einen Würfel setzen."),
+    ("{} has to place a cube before the next question.", "{} muss vor der nächsten Frage einen Würfel setzen."),
+    ("Clues", "Hinweise"),
+    ("{} tiles remain.", "{} Felder bleiben übrig."),
+    ("{}: hidden", "{}: verborgen"),
+    ("Known Clue", "Hinweis bekannt"),
+    ("Inverted clues", "Umgekehrte Hinweise"),
+    ("{} possible clues", "{} mögliche Hinweise"),
+    ("Show where this clue applies", "Zeigen, wo dieser Hinweis zutrifft"),
+    ("Candidates", "Kandidaten"),
+    ("Label tiles with", "Felder beschriften mit"),
+    ("Nothing", "Nichts"),
+    ("Combinations", "Kombinationen"),
+    ("Percentage", "Prozent"),
+    ("Counting combinations of clues…", "Kombinationen von Hinweisen werden gezählt…"),
+    ("{} combinations of one clue per player point to exactly one tile.", "{} Kombinationen aus einem Hinweis pro Spieler zeigen auf genau ein Feld."),
+    ("There are too many combinations to count them all yet.", "Es gibt noch zu viele Kombinationen, um alle zu zählen."),
+    ("{}: {} combinations, {}%", "{}: {} Kombinationen, {} %"),
+    ("Deduction report", "Bericht der Schlüsse"),
+    ("Possible clues", "Mögliche Hinweise"),
+    ("Tiles", "Felder"),
+    ("Tile", "Feld"),
+    ("Eliminated by", "Ausgeschlossen durch"),
+    ("Questions", "Fragen"),
+    ("{}. {} asked {} on {}: {}", "{}. {} fragte {} nach {}: {}"),
+    ("Ask {} here to rule out {} clues.", "Frage {} hier, um {} Hinweise auszuschließen."),
+    ("Ask {} here to rule out {} to {} clues.", "Frage {} hier, um {} bis {} Hinweise auszuschließen."),
+    ("Place a 'no' here to reveal no new information.", "Setze hier ein „Nein“, um nichts Neues zu verraten."),
+    ("Place a 'no' here to rule out {} of your clues.", "Setze hier ein „Nein“, um {} deiner Hinweise auszuschließen."),
+    ("The cryptid must be here. Search!", "Das Kryptid muss hier sein. Suche!"),
+    ("Search here, the cryptid is here in {}% of the combinations. If the search fails, the others rule out {} of your clues.", "Suche hier, das Kryptid ist hier in {} % der Kombinationen. Scheitert die Suche, schließen die anderen {} deiner Hinweise aus."),
+    ("Better ask a question. A search here only finds the cryptid in {}% of the combinations, and failing rules out {} of your clues for the others.", "Stelle besser eine Frage. Eine Suche hier findet das Kryptid nur in {} % der Kombinationen, und scheitert sie, schließen die anderen {} deiner Hinweise aus."),
+    ("Verification", "Überprüfung"),
+    ("Enter the actual clue of every player.", "Gib den tatsächlichen Hinweis jedes Spielers ein."),
+    ("New game", "Neues Spiel"),
+    ("New game with same map", "Neues Spiel mit derselben Karte"),
+    ("New game with same players", "Neues Spiel mit denselben Spielern"),
+    ("The answers and results of this game will be gone.", "Die Antworten und Ergebnisse dieses Spiels gehen verloren."),
+    ("Result", "Ergebnis"),
+    ("The cryptid was on {}.", "Das Kryptid war auf {}."),
+    ("These clues allow {} tiles.", "Diese Hinweise erlauben {} Felder."),
+    ("Every answer matches the clues.", "Jede Antwort passt zu den Hinweisen."),
+    ("{} answered {} on {}, which contradicts their clue.", "{0} hat auf {2} mit {1} geantwortet, was dem eigenen Hinweis widerspricht."),
+    ("Replay", "Wiederholung"),
+    ("No answers were given.", "Es wurden keine Antworten gegeben."),
+    ("{}. {} answered {} on {}. {} tiles remained.", "{0}. {1} antwortete {2} auf {3}. {4} Felder blieben übrig."),
+    (" The cryptid could be found.", " Das Kryptid konnte gefunden werden."),
+    (" Known clues: {}.", " Bekannte Hinweise: {}."),
+    ("Edit type", "Art ändern"),
+    ("One of two terrains", "Eines von zwei Geländen"),
+    ("Inverted (the cryptid is not there)", "Umgekehrt (das Kryptid ist nicht dort)"),
+    ("On", "Auf"),
+    ("or", "oder"),
+    ("structure", "Gebäude"),
+    ("The map does not match the pieces anymore", "Die Karte passt nicht mehr zu den Teilen"),
+    ("{} is not a number of columns", "{} ist keine Anzahl von Spalten"),
+    ("A board has at most three parts separated by ;", "Ein Spielplan hat höchstens drei durch ; getrennte Teile"),
+    ("{} pieces can't be arranged in rows of {}", "{} Teile lassen sich nicht in Reihen zu je {} anordnen"),
+    ("Every piece must be used once", "Jedes Teil darf nur einmal vorkommen"),
+    ("Unknown piece {}", "Unbekanntes Teil {}"),
+    ("{} is not like \"white shack C4\"", "{} sieht nicht aus wie \"white shack C4\""),
+    ("Board code", "Spielplan-Code"),
+    ("Share the board as text, or paste a code to set it up.", "Teile den Spielplan als Text oder füge einen Code ein, um ihn aufzubauen."),
+    ("Copy", "Kopieren"),
+    ("The map can have at most {} pieces in each direction.", "Die Karte kann in jeder Richtung höchstens {} Teile haben."),
+    ("{} is not on the map.", "{} liegt nicht auf der Karte."),
+    ("{} structures from the board code will be placed.", "{} Gebäude aus dem Spielplan-Code werden aufgestellt."),
+    ("The code is for a different map.", "Der Code gehört zu einer anderen Karte."),
+    ("Check the board", "Spielplan prüfen"),
+    ("Compare every tile with the physical board. Point at a tile to find it on the map.", "Vergleiche jedes Feld mit dem echten Spielplan. Zeige auf ein Feld, um es auf der Karte zu finden."),
+    ("{} of {} pieces are verified.", "{} von {} Teilen sind geprüft."),
+    ("Row {}, column {}", "Reihe {}, Spalte {}"),
+    ("Piece {} (row {}, column {})", "Teil {} (Reihe {}, Spalte {})"),
+    ("within {} of {}", "höchstens {} von {} entfernt"),
+    ("one space", "ein Feld"),
+    ("two spaces", "zwei Felder"),
+    ("three spaces", "drei Felder"),
+    ("{} spaces", "{} Felder"),
+    ("{} structure", "einem Gebäude in {}"),
+    ("Near terrain", "Nahe Gelände"),
+    ("Near either animal", "Nahe einem der Tiere"),
+    ("Near animal", "Nahe Tier"),
+    ("Near structure type", "Nahe Gebäudeart"),
+    ("Near structure color", "Nahe Gebäudefarbe"),
+    ("Within", "Höchstens"),
+    ("spaces of", "Felder von"),
+    ("either animal", "einem der Tiere"),
+    ("House rules", "Hausregeln"),
+    ("Distances of the clues, for fan variants of the game.", "Entfernungen der Hinweise, für Fan-Varianten des Spiels."),
+    ("Either animal", "Eines der Tiere"),
+    ("Animal", "Tier"),
+    ("Structure type", "Gebäudeart"),
+    ("Structure color", "Gebäudefarbe"),
+    (
+        "The house rules can only be changed before the game starts.",
+        "Die Hausregeln können nur vor dem Spielbeginn geändert werden.",
+    ),
+    ("Official rules", "Offizielle Regeln"),
+    ("Custom clues", "Eigene Hinweise"),
+    ("Paste the contents of a clue file.", "Füge den Inhalt einer Hinweisdatei ein."),
+    ("Added {} clues.", "{} Hinweise hinzugefügt."),
+    ("Can't load clues: {}", "Hinweise können nicht geladen werden: {}"),
+    ("The clue \"{}\" is farther than {} spaces.", "Der Hinweis „{}“ reicht weiter als {} Felder."),
+    ("any animal territory", "einem beliebigen Tierrevier"),
+    ("There already is an animal called {}", "Es gibt schon ein Tier namens {}"),
+    ("{} can't be the letter of an animal, use a lowercase letter that is not used yet", "{} kann nicht der Buchstabe eines Tiers sein, nimm einen noch nicht benutzten Kleinbuchstaben"),
+    ("Animals", "Tiere"),
+    ("Letter", "Buchstabe"),
+    ("Enter a name to add the animal.", "Gib einen Namen ein, um das Tier hinzuzufügen."),
+    ("Enter one letter for the animal in piece files.", "Gib einen Buchstaben für das Tier in Teildateien ein."),
+    ("Added {}.", "{} hinzugefügt."),
+    ("Icon opacity", "Deckkraft der Symbole"),
+    ("Icon size", "Größe der Symbole"),
+    ("Icon pack", "Symbolpaket"),
+    ("Folder with desert.png, forest.png and so on", "Ordner mit desert.png, forest.png und so weiter"),
+    ("Icons of the app", "Symbole der App"),
+    ("Can't load icons: {}", "Symbole können nicht geladen werden: {}"),
+    ("{} is not a folder", "{} ist kein Ordner"),
+    ("Ruled out tiles", "Ausgeschlossene Felder"),
+    ("Animation seconds", "Sekunden für Animation"),
+    ("Flash changed tiles", "Geänderte Felder aufblitzen lassen"),
+    ("Show clue distances around the selected tile", "Entfernungen der Hinweise um das gewählte Feld zeigen"),
+    ("Selection", "Auswahl"),
+    ("Hint", "Tipp"),
+    ("Clue preview", "Hinweisvorschau"),
+    ("Highlight speed", "Tempo der Markierung"),
+    ("Highlight thickness", "Dicke der Markierung"),
+    ("Highlights", "Markierungen"),
+    ("Hide", "Ausblenden"),
+    ("Edge quality", "Kantenqualität"),
+    ("Normal", "Normal"),
+    ("High", "Hoch"),
+    ("High quality is slower on large screens", "Hohe Qualität ist auf großen Bildschirmen langsamer"),
+    ("Building the map", "Karte bauen"),
+    ("Placing structures", "Gebäude platzieren"),
+    ("Playing", "Spielen"),
+    ("{} answers", "{} Antworten"),
+    ("Verifying", "Überprüfen"),
+    ("Choosing a scenario", "Szenario wählen"),
+    ("Editing a piece", "Teil bearbeiten"),
+    ("Fullscreen", "Vollbild"),
+    ("Center map", "Karte zentrieren"),
+    ("Smooth panning and zooming", "Weiches Verschieben und Zoomen"),
+    ("Selected tile", "Gewähltes Feld"),
+    ("{}: {} clues allow the cryptid here", "{}: {} Hinweise erlauben das Kryptid hier"),
+    ("Terrains", "Gelände"),
+    ("Animal territories", "Tierreviere"),
+    ("Outline: {}", "Umrandung: {}"),
+    ("Markers", "Marker"),
+    ("Panel", "Leiste"),
+    ("Left", "Links"),
+    ("Right", "Rechts"),
+    ("Floating", "Schwebend"),
+    ("Order of the sections", "Reihenfolge der Abschnitte"),
+    ("Simple mode", "Einfacher Modus"),
+    ("Show only the basics, for players new to Cryptid", "Nur das Nötigste zeigen, für Neulinge bei Cryptid"),
+    ("Start the tour", "Rundgang starten"),
+    ("Tour", "Rundgang"),
+    ("Step {} of {}", "Schritt {} von {}"),
+    ("Finish", "Abschließen"),
+    ("Next", "Weiter"),
+    ("End the tour", "Rundgang beenden"),
+    ("Clue reference", "Hinweisübersicht"),
+    ("The cryptid is on the terrain or within {} of it.", "Das Kryptid ist auf dem Gelände oder höchstens {} davon entfernt."),
+    ("The cryptid is on one of the two terrains.", "Das Kryptid ist auf einem der beiden Gelände."),
+    ("The cryptid is in the territory of any animal or within {} of it.", "Das Kryptid ist im Revier eines Tiers oder höchstens {} davon entfernt."),
+    ("The cryptid is in the territory of the animal or within {} of it.", "Das Kryptid ist im Revier des Tiers oder höchstens {} davon entfernt."),
+    ("The cryptid is on a structure of the type, of any color, or within {} of it.", "Das Kryptid ist auf einem Gebäude der Art, egal welcher Farbe, oder höchstens {} davon entfernt."),
+    ("The cryptid is on a structure of the color, of any type, or within {} of it.", "Das Kryptid ist auf einem Gebäude der Farbe, egal welcher Art, oder höchstens {} davon entfernt."),
+    ("Custom clue", "Eigener Hinweis"),
+    ("Loaded from a clue file, within {}.", "Aus einer Hinweisdatei geladen, höchstens {} entfernt."),
+    ("For example: {}", "Zum Beispiel: {}"),
+    ("An inverted clue says the opposite: the cryptid is not where the clue allows it.", "Ein umgekehrter Hinweis sagt das Gegenteil: Das Kryptid ist nicht dort, wo der Hinweis es erlaubt."),
+    ("Controls", "Steuerung"),
+    ("Select", "Auswählen"),
+    ("Pan", "Verschieben"),
+    ("Zoom in", "Hineinzoomen"),
+    ("Zoom out", "Herauszoomen"),
+    ("Answer yes", "Mit Ja antworten"),
+    ("Answer no", "Mit Nein antworten"),
+    ("Next hint", "Nächster Tipp"),
+    ("Left mouse button", "Linke Maustaste"),
+    ("Right mouse button", "Rechte Maustaste"),
+    ("Middle mouse button", "Mittlere Maustaste"),
+    ("Mouse button", "Maustaste"),
+    ("Press a key or mouse button", "Drücke eine Taste oder Maustaste"),
+    ("Undo last move", "Letzte Verschiebung rückgängig machen"),
+    ("Reset to default layout", "Auf die Standardaufstellung zurücksetzen"),
+    ("Put all structures back on the first tiles of the map", "Alle Gebäude zurück auf die ersten Felder der Karte stellen"),
+    ("These structures are still where they were added: {}. Did you forget to drag them?", "Diese Gebäude stehen noch dort, wo sie hinzugefügt wurden: {}. Hast du vergessen, sie zu verschieben?"),
+    ("The cryptid can't be here:", "Das Kryptid kann nicht hier sein:"),
+    ("The clue of {} excludes it: {}", "Der Hinweis von {} schließt es aus: {}"),
+    ("{} has no possible clue left", "{} hat keinen möglichen Hinweis mehr"),
+    ("All {} possible clues of {} exclude it", "Alle {} möglichen Hinweise von {} schließen es aus"),
+    ("Explain ruled out tiles when pointing at them", "Beim Zeigen auf ausgeschlossene Felder erklären, warum"),
+    ("Show eliminations of", "Ausschlüsse zeigen von"),
+    ("Everyone", "Allen"),
+    ("See what the answers of one player rule out, as far as they can tell", "Sieh, was die Antworten eines Spielers ausschließen, soweit er es selbst wissen kann"),
+    ("Explain", "Erklären"),
+    ("Hide the explanation", "Erklärung ausblenden"),
+    ("Show step by step how the answers lead to these tiles", "Schritt für Schritt zeigen, wie die Antworten zu diesen Feldern führen"),
+    ("{} and {} more", "{} und {} weitere"),
+    ("The clue of {} is known: {}.", "Der Hinweis von {} ist bekannt: {}."),
+    ("{} answered {} on {}, which rules out no further clues.", "{0} hat auf {2} mit {1} geantwortet, das schließt keine weiteren Hinweise aus."),
+    ("{} answered {} on {}, which rules out {} clues: {}.", "{0} hat auf {2} mit {1} geantwortet, das schließt {3} Hinweise aus: {4}."),
+    ("So {} has one of {} clues: {}.", "Also hat {} einen von {} Hinweisen: {}."),
+    ("Therefore the cryptid is on one of {} tiles: {}.", "Deshalb ist das Kryptid auf einem von {} Feldern: {}."),
+    ("Predict the questions of opponents", "Fragen der Gegner vorhersagen"),
+    ("Assumes that opponents ask where an answer rules out the most clues", "Nimmt an, dass Gegner dort fragen, wo eine Antwort die meisten Hinweise ausschließt"),
+    ("{} will probably ask {} on one of these tiles next.", "{} fragt als Nächstes wahrscheinlich {} auf einem dieser Felder."),
+    ("No clue allows all answers of {} anymore. The answer on {} contradicts the earlier ones.", "Kein Hinweis erlaubt mehr alle Antworten von {}. Die Antwort auf {} widerspricht den früheren."),
+    ("Your earlier answers only allow a 'yes' here. A 'no' would contradict them.", "Deine früheren Antworten erlauben hier nur ein „Ja“. Ein „Nein“ würde ihnen widersprechen."),
+    ("Your earlier answers only allow a 'no' here. A 'yes' would contradict them.", "Deine früheren Antworten erlauben hier nur ein „Nein“. Ein „Ja“ würde ihnen widersprechen."),
+    ("Games", "Spiele"),
+    ("Estimate chances to win", "Siegchancen schätzen"),
+    ("Play the rest of the game many times with clues that fit the answers", "Den Rest des Spiels viele Male mit Hinweisen durchspielen, die zu den Antworten passen"),
+    ("No clues were found that fit all answers.", "Es wurden keine Hinweise gefunden, die zu allen Antworten passen."),
+    ("Out of {} games", "Von {} Spielen"),
+    ("Bug report", "Fehlerbericht"),
+    ("If a deduction looks wrong, attach this to a bug report. It contains the map and every answer of the game.", "Wenn eine Schlussfolgerung falsch aussieht, hänge dies an einen Fehlerbericht an. Es enthält die Karte und jede Antwort des Spiels."),
+    ("Replace player names", "Spielernamen ersetzen"),
+    ("Copy debug info", "Debug-Infos kopieren"),
+    ("Save debug info", "Debug-Infos speichern"),
+    ("Copied to the clipboard", "In die Zwischenablage kopiert"),
+    ("Version {}", "Version {}"),
+    ("What's new", "Neuigkeiten"),
+    ("Close", "Schließen"),
+    ("Follow a game on the map and rule out tiles with the answers of every player.", "Verfolge ein Spiel auf der Karte und schließe Felder mit den Antworten aller Spieler aus."),
+    ("Hints suggest questions, and the clues each player might have are listed.", "Tipps schlagen Fragen vor, und die Hinweise, die jeder Spieler haben könnte, werden aufgelistet."),
+    ("House rules and custom clues change what clues are possible.", "Hausregeln und eigene Hinweise ändern, welche Hinweise möglich sind."),
+    ("Export the map as an image, or a bug report when deductions look wrong.", "Exportiere die Karte als Bild oder einen Fehlerbericht, wenn Schlussfolgerungen falsch aussehen."),
+    ("Turn the map around", "Karte umdrehen"),
+    ("Mirror the map", "Karte spiegeln"),
+    ("Show the letter of the color on structures", "Buchstaben der Farbe auf Gebäuden zeigen"),
+    ("Answer of {} changed to {}", "Antwort von {} ist jetzt {}"),
+    ("Active player", "Aktiver Spieler"),
+    ("Clicking a tile answers", "Klick auf ein Feld antwortet"),
+    ("Clear all answers", "Alle Antworten entfernen"),
+    ("Fill in No in region", "Nein im Bereich eintragen"),
+    ("Answer no on every unanswered tile of the region", "Auf jedem unbeantworteten Feld des Bereichs mit Nein antworten"),
+    ("Select a region first", "Wähle zuerst einen Bereich"),
+    ("Copy answers from", "Antworten kopieren von"),
+    ("Undo", "Rückgängig"),
+    ("Take back the last change of many answers at once", "Die letzte Änderung vieler Antworten auf einmal zurücknehmen"),
+    ("No game is possible on this map. Place more structures.", "Auf dieser Karte ist kein Spiel möglich. Stelle mehr Gebäude auf."),
+    ("Choose a scenario first.", "Wähle zuerst ein Szenario."),
+    ("{} answered {} on {}, which contradicts their clue. The answer was changed later.", "{0} hat auf {2} mit {1} geantwortet, was dem eigenen Hinweis widerspricht. Die Antwort wurde später geändert."),
+    ("This tour shows how to follow a game of Cryptid with the app. Press Next to go on, or do what a step asks.", "Dieser Rundgang zeigt, wie man mit der App eine Partie Cryptid verfolgt. Drücke Weiter, oder tu, worum ein Schritt bittet."),
+    ("Build the map like the one on the table. Choose the pieces in the panel, drag pieces on the map to arrange them and click a piece to rotate it. Add every player, then press Ready.", "Baue die Karte wie die auf dem Tisch. Wähle die Teile in der Leiste, ziehe Teile auf der Karte, um sie anzuordnen, und klicke ein Teil an, um es zu drehen. Füge alle Spieler hinzu und drücke dann Fertig."),
+    ("Drag the structures on the map to where they stand on the table. Press Ready in the panel when they match.", "Ziehe die Gebäude auf der Karte dorthin, wo sie auf dem Tisch stehen. Drücke Fertig in der Leiste, wenn sie übereinstimmen."),
+    ("Click a tile on the map, then set the answers of the players for it in the panel. Enter every disc and cube that is placed during the game.", "Klicke ein Feld auf der Karte an und setze dann die Antworten der Spieler dafür in der Leiste. Trage jede Scheibe und jeden Würfel ein, die während des Spiels gelegt werden."),
+    ("The app rules out tiles that the answers don't allow. Tiles that shrink can't hold the cryptid. The panel lists the clues each player might have.", "Die App schließt Felder aus, die die Antworten nicht erlauben. Felder, die schrumpfen, können das Kryptid nicht enthalten. Die Leiste zeigt die Hinweise, die jeder Spieler haben könnte."),
+    ("Shrunk", "Verkleinert"),
+    ("Desaturated", "Entsättigt"),
+    ("Cross-hatched", "Schraffiert"),
+    ("Hidden", "Ausgeblendet"),
+    ("Type a clue, like \"within two spaces of a standing stone\"", "Schreibe einen Hinweis, etwa „höchstens zwei Felder von einem Menhir entfernt“"),
+    ("Apply", "Übernehmen"),
+    ("This game has no inverted clues.", "In diesem Spiel gibt es keine umgekehrten Hinweise."),
+    ("A clue is only about terrain, animals, a type of structure or a structure color.", "Ein Hinweis handelt nur von Gelände, Tieren, einer Gebäudeart oder einer Gebäudefarbe."),
+    ("Found no terrain, animal or structure in \"{}\".", "Kein Gelände, Tier oder Gebäude in „{}“ gefunden."),
+    ("A clue names at most two terrains and one structure.", "Ein Hinweis nennt höchstens zwei Gelände und ein Gebäude."),
+    ("This clue only exists with a distance of {}.", "Diesen Hinweis gibt es nur mit einem Abstand von {}."),
+];
@@ -0,0 +1,138 @@
+//! Icons of the terrains. The icons that come with the app can be swapped for an icon pack,
+//! which is a folder with `desert.png`, `forest.png`, `water.png`, `swamp.png` and
+//! `mountain.png`. Terrains without a file in the folder keep the icon of the app.
+
+use std::collections::HashMap;
+
+use notan::{egui, prelude::*};
+use strum::IntoEnumIterator;
+
+use crate::{
+    i18n::{tr, trf},
+    model::Terrain,
+};
+
+pub struct Icons {
+    textures: HashMap<Terrain, Texture>,
+    /// Opacity of the icons.
+    pub alpha: f32,
+    /// Size of the icons, relative to their usual size.
+    pub scale: f32,
+    /// Folder of an icon pack, as entered by the user.
+    folder: String,
+    /// Icon pack to load, or None for the icons of the app. Textures can't be created
+    /// while the gui is drawn, so loading waits until after that.
+    requested: Option<Option<String>>,
+    /// Result of loading the last icon pack.
+    notice: Option<String>,
+}
+
+impl Icons {
+    pub fn new(gfx: &mut Graphics) -> Self {
+        Self {
+            textures: load(gfx, None).expect("icons of the app are valid"),
+            alpha: 0.3,
+            scale: 1.0,
+            folder: String::new(),
+            requested: None,
+            notice: None,
+        }
+    }
+
+    pub fn get(&self, terrain: Terrain) -> &Texture {
+        &self.textures[&terrain]
+    }
+
+    pub fn gui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(tr("Icon opacity"));
+            ui.add(egui::Slider::new(&mut self.alpha, 0.0..=1.0));
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("Icon size"));
+            ui.add(egui::Slider::new(&mut self.scale, 0.25..=2.0));
+        });
+
+        // Folders can only be read by the desktop app.
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.horizontal(|ui| {
+            ui.label(tr("Icon pack"));
+            ui.text_edit_singleline(&mut self.folder)
+                .on_hover_text(tr("Folder with desert.png, forest.png and so on"));
+            if ui.button(tr("Load")).clicked() {
+                self.requested = Some(Some(self.folder.trim().to_owned()));
+            }
+        });
+        if ui.button(tr("Icons of the app")).clicked() {
+            self.requested = Some(None);
+        }
+        if let Some(notice) = &self.notice {
+            ui.add(egui::Label::new(notice).wrap(true));
+        }
+    }
+
+    /// Load the icon pack the user asked for, if any.
+    pub fn update(&mut self, gfx: &mut Graphics) {
+        let Some(folder) = self.requested.take() else {
+            return;
+        };
+        match load(gfx, folder.as_deref()) {
+            Ok(textures) => {
+                self.textures = textures;
+                self.notice = None;
+            }
+            Err(e) => self.notice = Some(trf("Can't load icons: {}", &[&e])),
+        }
+    }
+}
+
+/// Create the textures for the icons in the folder, or for the icons of the app.
+fn load(gfx: &mut Graphics, folder: Option<&str>) -> Result<HashMap<Terrain, Texture>, String> {
+    Terrain::iter()
+        .map(|t| {
+            let bytes = match folder.map(|f| read_icon(f, t)).transpose()?.flatten() {
+                Some(bytes) => bytes,
+                None => built_in(t).to_vec(),
+            };
+            let texture = gfx
+                .create_texture()
+                .from_image(&bytes)
+                .build()
+                .map_err(|e| format!("{}: {e}", file_name(t)))?;
+            Ok((t, texture))
+        })
+        .collect()
+}
+
+/// Read the icon of the terrain from an icon pack. Returns None if the pack has no icon for it.
+#[cfg(not(target_arch = "wasm32"))]
+fn read_icon(folder: &str, terrain: Terrain) -> Result<Option<Vec<u8>>, String> {
+    let folder = std::path::Path::new(folder);
+    if !folder.is_dir() {
+        return Err(trf("{} is not a folder", &[&folder.display()]));
+    }
+    let path = folder.join(file_name(terrain));
+    if !path.exists() {
+        return Ok(None);
+    }
+    std::fs::read(&path).map(Some).map_err(|e| e.to_string())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_icon(_folder: &str, _terrain: Terrain) -> Result<Option<Vec<u8>>, String> {
+    Ok(None)
+}
+
+fn file_name(terrain: Terrain) -> String {
+    format!("{}.png", terrain.to_string().to_lowercase())
+}
+
+fn built_in(terrain: Terrain) -> &'static [u8] {
+    match terrain {
+        Terrain::Desert => include_bytes!("../assets/weather-sun.png"),
+        Terrain::Forest => include_bytes!("../assets/wild-harvested.png"),
+        Terrain::Water => include_bytes!("../assets/wave.png"),
+        Terrain::Swamp => include_bytes!("../assets/skull.png"),
+        Terrain::Mountain => include_bytes!("../assets/rocky-mountain.png"),
+    }
+}
@@ -0,0 +1,521 @@
+//! Input on the map as messages, and what they change. The model holds everything that input
+//! on the map can change and nothing that needs a window, so the interaction can be tested
+//! without one. Each frame applies the messages before anything is drawn, and drawing only
+//! reads the model.
+
+use std::f32::consts::PI;
+
+use hexx::{Hex, HexLayout};
+use notan::math::Vec2;
+
+use crate::{
+    camera::Camera,
+    elimination::Elimination,
+    model::{piece_slot, Answer, PlayerID, Structure, Tile},
+    substate::{Common, SubState},
+};
+
+/// How much bigger answers are drawn on the table display.
+pub const TABLE_MARKER_SCALE: f32 = 1.4;
+
+/// Everything that input on the map changes.
+#[derive(Debug, Default)]
+pub struct Model {
+    pub sub: SubState,
+    pub camera: Camera,
+    pub dragging: Dragging,
+    /// True if egui used the mouse in the last frame, so the map should not react to it.
+    pub is_egui_hovered: bool,
+    pub elimination: Elimination,
+    /// True if only the map is shown, for a TV or projector at the table.
+    pub table_display: bool,
+    /// True if yes and no answers should be drawn next to each other.
+    pub cluster_answers: bool,
+    /// What clicking an answer marker changed, and when.
+    pub answer_notice: Option<(String, f32)>,
+}
+
+impl Model {
+    /// True if the structures are supposed to be draggable in this substate.
+    fn are_structures_draggable(&self) -> bool {
+        matches!(self.sub, SubState::PlacingStructures(_))
+    }
+}
+
+/// Possible dragging modes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Dragging {
+    /// No dragging active.
+    #[default]
+    None,
+    /// The offset i.e. the screen if being dragged. Start is the offset before the drag.
+    Offset { mouse_last_frame: Vec2, start: Vec2 },
+    /// The structure on the tile at from is being dragged. It moves once it is dropped. Cursor
+    /// is where the mouse is, relative to the origin of the map and in tile radii, so that
+    /// it can be drawn with any layout.
+    Structure { from: Hex, cursor: Vec2 },
+    /// Tiles are painted by the substate. Last is the tile the mouse was over in the last frame.
+    Paint { last: Hex, moved: bool },
+    /// The piece of the map with the tile at from is being dragged to the piece with the tile
+    /// at over. The two swap once the piece is dropped.
+    Piece { from: Hex, over: Hex, moved: bool },
+    /// The drag was canceled, but the mouse button is still held.
+    Canceled,
+}
+
+/// Input on the map, independent of which keys and buttons it came from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Message {
+    /// Put back what is being dragged, or cancel what the substate is doing.
+    Cancel,
+    /// The select button was let go with the mouse at the position.
+    Click { mouse: Vec2, modified: bool },
+    /// The select or pan button is held with the mouse at the position.
+    Hold { mouse: Vec2, selecting: bool },
+    /// Neither button is held.
+    Release,
+}
+
+/// What the interaction adds to the map when it is drawn.
+#[derive(Debug, Default, PartialEq)]
+pub struct View {
+    /// Tile of the structure that is being dragged away. It stays faded until it is dropped.
+    pub dragged_from: Option<Hex>,
+    /// The structure that is being dragged, drawn under the mouse.
+    pub ghost: Option<Ghost>,
+}
+
+/// A dragged structure and where it would be dropped.
+#[derive(Debug, PartialEq)]
+pub struct Ghost {
+    pub structure: Structure,
+    /// Where the mouse is on the screen.
+    pub position: Vec2,
+    /// The tile it would be dropped on, and if it may go there.
+    pub target: Hex,
+    pub valid: bool,
+}
+
+/// Apply a message to the model. This does not look at the input, only at the message.
+pub fn update(model: &mut Model, message: Message, layout: &HexLayout, time: f32) {
+    match message {
+        Message::Cancel => {
+            // Put back what is being dragged, or else let the substate cancel what it is doing.
+            match model.dragging {
+                Dragging::Structure { .. } | Dragging::Piece { .. } => {
+                    model.dragging = Dragging::Canceled
+                }
+                Dragging::Offset { start, .. } => {
+                    model.camera.offset = start;
+                    model.camera.release();
+                    model.camera.stop();
+                    model.dragging = Dragging::Canceled;
+                }
+                _ if !model.is_egui_hovered => model.sub.escape(),
+                _ => {}
+            }
+        }
+        Message::Click { mouse, modified } => {
+            // Painting over tiles or moving pieces already handled the tiles.
+            let dragged = matches!(
+                model.dragging,
+                Dragging::Paint { moved: true, .. }
+                    | Dragging::Piece { moved: true, .. }
+                    | Dragging::Canceled
+            );
+            if model.is_egui_hovered || dragged {
+                return;
+            }
+            if !modified {
+                if let Some((hex, player)) = marker_at(model, layout, mouse) {
+                    if let Some(notice) = model.sub.cycle_answer(hex, player) {
+                        model.answer_notice = Some((notice, time));
+                        return;
+                    }
+                }
+            }
+            let hex = layout.world_pos_to_hex(mouse);
+            if modified {
+                model.sub.modified_click(hex);
+            } else {
+                model.sub.click(hex);
+            }
+        }
+        Message::Hold { mouse, selecting } => drag(model, mouse, selecting, layout, time),
+        Message::Release => {
+            match model.dragging {
+                Dragging::Structure { from, cursor } => drop_structure(model, from, cursor, layout),
+                Dragging::Piece {
+                    from,
+                    over,
+                    moved: true,
+                } => model.sub.drag_piece(from, over),
+                _ => {}
+            }
+            model.dragging = Dragging::None;
+            model.camera.release();
+        }
+    }
+}
+
+/// What the drawn map shows of the interaction.
+pub fn view(model: &Model, layout: &HexLayout) -> View {
+    let Dragging::Structure { from, cursor } = model.dragging else {
+        return View::default();
+    };
+    let structure = model
+        .sub
+        .tiles()
+        .iter()
+        .find(|t| t.position == from)
+        .and_then(|t| t.structure);
+    let ghost = structure.map(|structure| {
+        let (target, valid) = drop_target(model, from, cursor, layout);
+        Ghost {
+            structure,
+            position: layout.origin + cursor * layout.hex_size,
+            target,
+            valid,
+        }
+    });
+    View {
+        dragged_from: Some(from),
+        ghost,
+    }
+}
+
+/// Start or go on dragging while a button is held.
+fn drag(model: &mut Model, mouse: Vec2, selecting: bool, layout: &HexLayout, time: f32) {
+    let mouse_hex = layout.world_pos_to_hex(mouse);
+    match model.dragging {
+        Dragging::None => {
+            // Don't start dragging anything when the mouse is over egui
+            if model.is_egui_hovered {
+                return;
+            }
+
+            if !selecting {
+                model.dragging = Dragging::Offset {
+                    mouse_last_frame: mouse,
+                    start: model.camera.offset,
+                };
+                return;
+            }
+
+            // Start dragging a structure (if that is allowed) or the screen.
+            let over_tile = model.sub.tiles().iter().find(|t| t.position == mouse_hex);
+            let has_structure = over_tile.map(|t| t.structure.is_some()).unwrap_or(false);
+
+            if has_structure && model.are_structures_draggable() {
+                model.dragging = Dragging::Structure {
+                    from: mouse_hex,
+                    cursor: map_cursor(mouse, layout),
+                };
+            } else if over_tile.is_some() && model.sub.drags_pieces() {
+                model.dragging = Dragging::Piece {
+                    from: mouse_hex,
+                    over: mouse_hex,
+                    moved: false,
+                };
+            } else if model.sub.paints() {
+                model.dragging = Dragging::Paint {
+                    last: mouse_hex,
+                    moved: false,
+                };
+            } else {
+                model.dragging = Dragging::Offset {
+                    mouse_last_frame: mouse,
+                    start: model.camera.offset,
+                };
+            }
+        }
+        Dragging::Offset {
+            mouse_last_frame,
+            start,
+        } => {
+            let delta = mouse - mouse_last_frame;
+            model.camera.drag(delta, time);
+            model.dragging = Dragging::Offset {
+                mouse_last_frame: mouse,
+                start,
+            };
+        }
+        Dragging::Canceled => {}
+        Dragging::Structure { from, .. } => {
+            model.dragging = Dragging::Structure {
+                from,
+                cursor: map_cursor(mouse, layout),
+            };
+        }
+        Dragging::Paint { last, moved } => {
+            if mouse_hex != last {
+                if !moved {
+                    // Include the tile where painting started.
+                    model.sub.paint(last);
+                }
+                model.sub.paint(mouse_hex);
+                model.dragging = Dragging::Paint {
+                    last: mouse_hex,
+                    moved: true,
+                };
+            }
+        }
+        Dragging::Piece { from, over, moved } => {
+            let over_tile = model.sub.tiles().iter().any(|t| t.position == mouse_hex);
+            if !over_tile || piece_slot(mouse_hex) == piece_slot(over) {
+                // Mouse is still over the same piece or not over the map.
+                return;
+            }
+            // The pieces only swap when the piece is dropped, so the pieces in between
+            // stay where they are.
+            model.dragging = Dragging::Piece {
+                from,
+                over: mouse_hex,
+                moved: moved || piece_slot(mouse_hex) != piece_slot(from),
+            };
+        }
+    }
+}
+
+/// Position of the mouse relative to the origin of the map, in tile radii.
+fn map_cursor(mouse: Vec2, layout: &HexLayout) -> Vec2 {
+    (mouse - layout.origin) / layout.hex_size
+}
+
+/// The tile a structure dragged from a tile would be dropped on, and if it may go there.
+fn drop_target(model: &Model, from: Hex, cursor: Vec2, layout: &HexLayout) -> (Hex, bool) {
+    let hex = layout.world_pos_to_hex(layout.origin + cursor * layout.hex_size);
+    let tile = model.sub.tiles().iter().find(|t| t.position == hex);
+    let valid = tile.is_some_and(|t| t.structure.is_none() || hex == from);
+    (hex, valid)
+}
+
+/// Move the structure that was dragged from a tile to where it was dropped, if it fits there.
+fn drop_structure(model: &mut Model, from: Hex, cursor: Vec2, layout: &HexLayout) {
+    let (to, valid) = drop_target(model, from, cursor, layout);
+    if !valid || to == from {
+        return;
+    }
+    model.sub.move_structure(from, to);
+}
+
+/// Radius of the discs and width of the cubes that show answers.
+pub fn marker_sizes(table_display: bool, tile_radius: f32) -> (f32, f32) {
+    let scale = if table_display {
+        TABLE_MARKER_SCALE
+    } else {
+        1.0
+    };
+    (tile_radius * 0.2 * scale, tile_radius * 0.4 * scale)
+}
+
+/// The answers on the tile with their offset from its center, in the order they are drawn.
+/// Cubes go on top of discs, and markers in front on top of markers behind them.
+pub fn markers(model: &Model, tile: &Tile, tile_radius: f32) -> Vec<(f32, f32, PlayerID, Answer)> {
+    let (circle_radius, box_width) = marker_sizes(model.table_display, tile_radius);
+    let marker_size = (circle_radius * 2.0).max(box_width * 1.2);
+    let offsets = marker_offsets(tile.answers.len(), marker_size, tile_radius);
+    let mut answered = tile
+        .answers
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, &answer))| answer != Answer::Unknown)
+        .collect::<Vec<_>>();
+    if model.cluster_answers {
+        // Put answers of the same kind into neighboring slots.
+        answered.sort_by_key(|(_, (_, &answer))| answer == Answer::No);
+        for (slot, (i, _)) in answered.iter_mut().enumerate() {
+            *i = slot;
+        }
+    }
+    let mut markers = answered
+        .into_iter()
+        .map(|(i, (&player_id, &answer))| (offsets[i].x, offsets[i].y, player_id, answer))
+        .collect::<Vec<_>>();
+    markers.sort_by(|a, b| {
+        let cube = |m: &(f32, f32, PlayerID, Answer)| m.3 == Answer::No;
+        cube(a).cmp(&cube(b)).then(a.1.total_cmp(&b.1))
+    });
+    markers
+}
+
+/// The player whose answer marker is under the mouse, and the tile it is on.
+fn marker_at(model: &Model, layout: &HexLayout, mouse: Vec2) -> Option<(Hex, PlayerID)> {
+    let tile_radius = layout.hex_size.x.abs();
+    let (circle_radius, box_width) = marker_sizes(model.table_display, tile_radius);
+    let reach = circle_radius.max(box_width * 0.6);
+    let hex = layout.world_pos_to_hex(mouse);
+    let tile = model.sub.tiles().iter().find(|t| t.position == hex)?;
+    let look = model.elimination.look(tile);
+    let relative = (mouse - layout.hex_to_world_pos(hex)) / look.scale;
+    markers(model, tile, tile_radius)
+        .into_iter()
+        .rev()
+        .find(|&(x, y, _, _)| relative.distance(Vec2::new(x, y)) <= reach)
+        .map(|(_, _, player, _)| (hex, player))
+}
+
+/// Offsets of the answer markers from the center of a tile. They are evenly spread around the
+/// center, starting at the top, and far enough apart that markers of the given size don't overlap.
+fn marker_offsets(count: usize, marker_size: f32, tile_radius: f32) -> Vec<Vec2> {
+    let step = 2.0 * PI / count.max(1) as f32;
+    let needed = if count > 1 {
+        marker_size / (2.0 * (step / 2.0).sin())
+    } else {
+        0.0
+    };
+    let radius = needed.clamp(tile_radius * 0.5, tile_radius * 0.7);
+    (0..count)
+        .map(|i| {
+            let angle = -PI / 2.0 + step * i as f32;
+            Vec2::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::substate::BuildingMap;
+
+    const WINDOW: Vec2 = Vec2::new(800.0, 600.0);
+
+    /// Apply the messages like a frame does, with the layout of the camera before each one.
+    fn run(model: &mut Model, messages: &[Message]) {
+        for &message in messages {
+            let layout = model.camera.layout(WINDOW);
+            update(model, message, &layout, 0.0);
+        }
+    }
+
+    fn hold(mouse: Vec2, selecting: bool) -> Message {
+        Message::Hold { mouse, selecting }
+    }
+
+    /// Where the tile is in the window.
+    fn screen(model: &Model, hex: Hex) -> Vec2 {
+        model.camera.layout(WINDOW).hex_to_world_pos(hex)
+    }
+
+    /// A model placing structures on the default map.
+    fn placing() -> Model {
+        Model {
+            sub: BuildingMap::default()
+                .next()
+                .expect("the default map can be played"),
+            ..Model::default()
+        }
+    }
+
+    #[test]
+    fn dragging_the_map_moves_it_and_cancel_puts_it_back() {
+        let mut model = Model::default();
+        let start = Vec2::new(10.0, 10.0);
+        run(
+            &mut model,
+            &[
+                hold(start, false),
+                hold(start + Vec2::new(20.0, 5.0), false),
+            ],
+        );
+        assert_eq!(model.camera.offset, Vec2::new(20.0, 5.0));
+
+        run(&mut model, &[Message::Cancel]);
+        assert_eq!(model.camera.offset, Vec2::ZERO);
+        assert_eq!(model.dragging, Dragging::Canceled);
+
+        run(&mut model, &[Message::Release]);
+        assert_eq!(model.dragging, Dragging::None);
+    }
+
+    #[test]
+    fn nothing_is_dragged_while_egui_has_the_mouse() {
+        let mut model = Model {
+            is_egui_hovered: true,
+            ..Model::default()
+        };
+        run(&mut model, &[hold(Vec2::ZERO, true)]);
+        assert_eq!(model.dragging, Dragging::None);
+    }
+
+    #[test]
+    fn dropping_a_piece_swaps_it_with_the_piece_it_is_dropped_on() {
+        let mut model = Model::default();
+        let before = model.sub.tiles().to_vec();
+        let from = before[0].position;
+        let to = before
+            .iter()
+            .map(|t| t.position)
+            .find(|&hex| piece_slot(hex) != piece_slot(from))
+            .expect("the map has more than one piece");
+
+        let path = [
+            hold(screen(&model, from), true),
+            hold(screen(&model, to), true),
+        ];
+        run(&mut model, &path);
+        // The pieces only swap when the piece is dropped.
+        assert_eq!(model.sub.tiles(), &before[..]);
+
+        let click = Message::Click {
+            mouse: screen(&model, to),
+            modified: false,
+        };
+        run(&mut model, &[click, Message::Release]);
+        assert_ne!(model.sub.tiles(), &before[..]);
+        assert_eq!(model.dragging, Dragging::None);
+    }
+
+    #[test]
+    fn a_structure_moves_where_it_is_dropped() {
+        let mut model = placing();
+        let tiles = model.sub.tiles();
+        let from = tiles
+            .iter()
+            .find(|t| t.structure.is_some())
+            .expect("the default map has structures");
+        let (from, structure) = (from.position, from.structure);
+        let to = tiles
+            .iter()
+            .find(|t| t.structure.is_none())
+            .expect("the map has empty tiles")
+            .position;
+
+        let path = [
+            hold(screen(&model, from), true),
+            hold(screen(&model, to), true),
+        ];
+        run(&mut model, &path);
+        let view = view(&model, &model.camera.layout(WINDOW));
+        assert_eq!(view.dragged_from, Some(from));
+        let ghost = view.ghost.expect("the structure is shown under the mouse");
+        assert_eq!((ghost.target, ghost.valid), (to, true));
+
+        run(&mut model, &[Message::Release]);
+        let structure_on = |hex| {
+            let tile = model.sub.tiles().iter().find(|t| t.position == hex);
+            tile.and_then(|t| t.structure)
+        };
+        assert_eq!(structure_on(to), structure);
+        assert_eq!(structure_on(from), None);
+    }
+
+    #[test]
+    fn canceling_a_structure_drag_leaves_it_in_place() {
+        let mut model = placing();
+        let before = model.sub.tiles().to_vec();
+        let from = before.iter().find(|t| t.structure.is_some()).unwrap();
+        let to = before.iter().find(|t| t.structure.is_none()).unwrap();
+
+        let messages = [
+            hold(screen(&model, from.position), true),
+            hold(screen(&model, to.position), true),
+            Message::Cancel,
+            Message::Release,
+        ];
+        run(&mut model, &messages);
+        assert_eq!(model.sub.tiles(), &before[..]);
+        assert_eq!(view(&model, &model.camera.layout(WINDOW)), View::default());
+    }
+}
@@ -1,93 +1,192 @@
+mod analysis;
+mod bindings;
+mod camera;
+mod changelog;
+mod clueparser;
+mod customclues;
+mod elimination;
+mod export;
+mod highlight;
+mod houserules;
+mod i18n;
+mod icons;
+mod interaction;
 mod model;
+mod notation;
+mod panel;
+mod prefs;
+mod report;
+mod rulesref;
+mod scenario;
+mod seams;
+mod simplemode;
+mod smoothing;
 mod substate;
+mod theme;
+mod timer;
+mod tutorial;
+mod winchance;
 
 use crate::model::*;
-use std::{collections::HashMap, f32::consts::PI};
+use std::f32::consts::PI;
 
-use hexx::{Hex, HexLayout, HexOrientation};
+use bindings::{Action, Bindings};
+use changelog::Changelog;
+use hexx::{HexLayout, HexOrientation};
+use highlight::{HighlightChannel, Highlighting};
+use houserules::HouseRules;
+use i18n::{tr, tr_name, trf};
+use icons::Icons;
+use interaction::{Dragging, Message, Model};
 use notan::{
-    draw::{CreateDraw, DrawConfig, DrawImages, DrawShapes, DrawTransform},
-    egui::{self, EguiConfig, EguiPluginSugar, Frame, RichText, ScrollArea, Style},
+    draw::{
+        CreateDraw, CreateFont, Draw, DrawConfig, DrawImages, DrawShapes, DrawTextSection,
+        DrawTransform, Font,
+    },
+    egui::{self, Align2, EguiConfig, EguiPluginSugar, Frame, RichText, ScrollArea},
     math::{Mat3, Vec2},
     prelude::*,
 };
+use panel::Panel;
+use report::ErrorReport;
+use seams::Seams;
+use smoothing::{Smoothing, SUPERSAMPLING};
 use strum::IntoEnumIterator;
-use substate::{Common, SubState};
+use substate::{Common, SubState, Transition};
+use theme::Theme;
+use timer::Timer;
+use tutorial::Tutorial;
 
 pub const LAYOUT_SPACE: f32 = 16.0;
 pub const START_MAXIMIZED: bool = cfg!(target_family = "wasm");
+/// Seconds without input after which the user is considered idle.
+pub const IDLE_AFTER: f32 = 3.0;
+/// Radius of the tiles when exporting the map as an image.
+pub const EXPORT_TILE_RADIUS: f32 = 64.0;
+/// Zoom steps per second while a zoom key is held.
+pub const KEY_ZOOM_SPEED: f32 = 500.0;
+/// Seconds the notice about a changed answer stays on screen.
+pub const ANSWER_NOTICE_TIME: f32 = 2.0;
 
 #[derive(AppState)]
 struct State {
-    /// Radius of the tiles to draw
-    tile_radius: f32,
-    /// Offset to draw the tiles at. Used for dragging with mouse.
-    offset: Vec2,
-    icons: HashMap<Terrain, Texture>,
-    is_egui_hovered: bool,
-    dragging: Dragging,
-    sub: SubState,
+    /// Everything that input on the map changes.
+    model: Model,
+    bindings: Bindings,
+    icons: Icons,
+    /// Font for the initials on answers.
+    font: Font,
+    theme: Theme,
+    seams: Seams,
+    highlighting: Highlighting,
+    smoothing: Smoothing,
+    panel: Panel,
+    tutorial: Tutorial,
+    changelog: Changelog,
+    timer: Timer,
+    /// Result of the last export to show to the user.
+    export_notice: Option<String>,
+    idle: Idle,
+    /// An error that was caught and can be reported.
+    error: Option<ErrorReport>,
+    /// Result of exporting the error report.
+    error_notice: Option<String>,
+    /// True if the distances of the clues are shown around the selected tile.
+    show_ranges: bool,
+    /// True if structures show the letter of their color, for when colors are hard to tell apart.
+    structure_letters: bool,
+    /// True if pointing at a ruled out tile tells why it was ruled out.
+    explain_on_hover: bool,
+    /// Question to the user before switching states, if the substate wants confirmation, and
+    /// the switch to make if the user agrees.
+    confirming: Option<(String, Transition)>,
 }
 
 impl State {
     fn new(gfx: &mut Graphics) -> Self {
-        let icons = load_icons(gfx);
+        let icons = Icons::new(gfx);
+        let font = gfx
+            .create_font(include_bytes!("../assets/Ubuntu-B.ttf"))
+            .expect("font is valid");
 
         Self {
-            tile_radius: 64.0,
+            model: Model::default(),
+            bindings: Bindings::load(),
             icons,
-            is_egui_hovered: false,
-            offset: Vec2::ZERO,
-            dragging: Dragging::None,
-            sub: Default::default(),
+            font,
+            theme: Theme::default(),
+            seams: Seams::default(),
+            highlighting: Highlighting::default(),
+            smoothing: Smoothing::default(),
+            panel: Panel::default(),
+            tutorial: Tutorial::default(),
+            changelog: Changelog::default(),
+            timer: Timer::default(),
+            export_notice: None,
+            idle: Idle::default(),
+            error: ErrorReport::from_last_crash(),
+            error_notice: None,
+            show_ranges: false,
+            structure_letters: false,
+            explain_on_hover: false,
+            confirming: None,
         }
     }
 
-    /// True if the structures are supposed to be draggable in this substate.
-    fn are_structures_draggable(&self) -> bool {
-        matches!(self.sub, SubState::PlacingStructures(_))
+    /// Remember the error of the result for reporting, if there is one.
+    fn handle_error<R>(&mut self, result: Result<R, String>) -> Option<R> {
+        match result {
+            Ok(r) => Some(r),
+            Err(e) => {
+                self.error = Some(ErrorReport::new(e, &self.model.sub));
+                self.error_notice = None;
+                None
+            }
+        }
     }
 }
 
-/// Possible dragging modes.
-#[derive(Debug, Clone, Copy)]
-enum Dragging {
-    /// No dragging active.
-    None,
-    /// The offset i.e. the screen if being dragged.
-    Offset { mouse_last_frame: Vec2 },
-    /// A structure (currently on the tile at the Hex) is being dragged to another tile.
-    Structure(Hex),
+/// Tracks user activity to detect when the user becomes idle.
+#[derive(Debug, Default)]
+struct Idle {
+    /// An event happened since the last frame.
+    input: bool,
+    /// Time of the last input.
+    last_input: f32,
+    /// True if the current idle phase was already used for work.
+    handled: bool,
 }
 
-fn load_icons(gfx: &mut Graphics) -> HashMap<Terrain, Texture> {
-    Terrain::iter()
-        .map(|t| {
-            (
-                t,
-                match t {
-                    Terrain::Desert => include_bytes!("../assets/weather-sun.png").as_slice(),
-                    Terrain::Forest => include_bytes!("../assets/wild-harvested.png").as_slice(),
-                    Terrain::Water => include_bytes!("../assets/wave.png").as_slice(),
-                    Terrain::Swamp => include_bytes!("../assets/skull.png").as_slice(),
-                    Terrain::Mountain => include_bytes!("../assets/rocky-mountain.png").as_slice(),
-                },
-            )
-        })
-        .map(|(t, bytes)| {
-            (
-                t,
-                gfx.create_texture()
-                    .from_image(bytes)
-                    .build()
-                    .expect("load icon"),
-            )
-        })
-        .collect()
+impl Idle {
+    /// Returns true exactly once after the user stopped giving input for a while.
+    fn became_idle(&mut self, now: f32) -> bool {
+        if self.input {
+            self.input = false;
+            self.last_input = now;
+            self.handled = false;
+        }
+
+        if !self.handled && now - self.last_input > IDLE_AFTER {
+            self.handled = true;
+            true
+        } else {
+            false
+        }
+    }
 }
 
 #[notan_main]
 fn main() -> Result<(), String> {
+    report::install_panic_hook();
+
+    // Rate a scenario file instead of starting the app: `cryptid-finder --rate puzzle.json`
+    #[cfg(not(target_arch = "wasm32"))]
+    if let [_, flag, path] = std::env::args().collect::<Vec<_>>().as_slice() {
+        if flag == "--rate" {
+            return rate_scenario(path);
+        }
+    }
+
     notan::init_with(State::new)
         .draw(draw)
         .event(event)
@@ -97,44 +196,662 @@ fn main() -> Result<(), String> {
             WindowConfig::new()
                 .resizable(true)
                 .maximized(START_MAXIMIZED)
+                .multisampling(smoothing::MULTISAMPLING)
                 .title("Cryptid Finder"),
         )
         .build()
 }
 
+/// Print the estimated difficulty of a scenario file.
+#[cfg(not(target_arch = "wasm32"))]
+fn rate_scenario(path: &str) -> Result<(), String> {
+    let json = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let setup = scenario::Scenario::parse(&json)?.build()?;
+    println!("{}", substate::Difficulty::of(&setup));
+    Ok(())
+}
+
 fn event(state: &mut State, event: Event) {
-    if !state.is_egui_hovered {
+    state.idle.input = true;
+
+    if !state.model.is_egui_hovered {
         if let Event::MouseWheel { delta_y, .. } = event {
-            state.tile_radius = (state.tile_radius + delta_y * 0.1).clamp(8.0, 1024.0);
+            state.model.camera.zoom(delta_y);
         }
     }
 }
 
 fn draw(app: &mut App, gfx: &mut Graphics, plugins: &mut Plugins, state: &mut State) {
     let mut draw = gfx.create_draw();
-    draw.clear(Color::BLACK);
-
-    let stroke_width = state.tile_radius * 0.05;
+    draw.clear(Color::from(state.theme.background.to_array()));
 
     let (window_width, window_height) = app.window().size();
     let window_size: Vec2 = (window_width as f32, window_height as f32).into();
-    let origin = window_size * 0.5 + state.offset;
+    state.bindings.capture(app);
+    let zoom_keys = state.bindings.is_down(app, Action::ZoomIn) as i8
+        - state.bindings.is_down(app, Action::ZoomOut) as i8;
+    if zoom_keys != 0 {
+        state
+            .model
+            .camera
+            .zoom(zoom_keys as f32 * KEY_ZOOM_SPEED * app.timer.delta_f32());
+    }
+    state.model.camera.update(app.timer.time_since_init());
 
-    let layout = HexLayout {
-        orientation: HexOrientation::flat(),
-        origin,
-        hex_size: Vec2::splat(state.tile_radius),
+    // Apply the input on the map before anything is drawn. egui runs later in the frame, so
+    // whether it has the mouse is known from the last frame.
+    let time = app.timer.time_since_init();
+    for message in messages(app, &state.bindings) {
+        let layout = state.model.camera.layout(window_size);
+        let result =
+            report::catch(|| interaction::update(&mut state.model, message, &layout, time));
+        state.handle_error(result);
+    }
+
+    // Dragging or zooming could move the map out of the window.
+    state
+        .model
+        .camera
+        .keep_visible(state.model.sub.tiles(), window_size);
+    let layout = state.model.camera.layout(window_size);
+    let origin = layout.origin;
+
+    match state.model.sub.player_layer() {
+        Some((_, eliminated)) => {
+            // Only the tiles that the player rules out are drawn as ruled out.
+            let tiles = state
+                .model
+                .sub
+                .tiles()
+                .iter()
+                .map(|t| Tile {
+                    small: eliminated.contains(&t.position),
+                    ..t.clone()
+                })
+                .collect::<Vec<_>>();
+            state
+                .model
+                .elimination
+                .update(&tiles, app.timer.time_since_init());
+        }
+        None => state
+            .model
+            .elimination
+            .update(state.model.sub.tiles(), app.timer.time_since_init()),
+    }
+    state.smoothing.prepare(gfx, window_size);
+    if let Some(texture) = state.smoothing.texture() {
+        let supersampled = HexLayout {
+            orientation: HexOrientation::flat(),
+            origin: origin * SUPERSAMPLING,
+            hex_size: layout.hex_size * SUPERSAMPLING,
+        };
+        let mut map = texture.create_draw();
+        map.clear(Color::from(state.theme.background.to_array()));
+        draw_map(
+            &mut map,
+            state,
+            &supersampled,
+            window_size * SUPERSAMPLING,
+            app.timer.time_since_init(),
+        );
+        gfx.render_to(texture, &map);
+        draw.image(texture).size(window_size.x, window_size.y);
+    } else {
+        draw_map(
+            &mut draw,
+            state,
+            &layout,
+            window_size,
+            app.timer.time_since_init(),
+        );
+    }
+    gfx.render(&draw);
+
+    let mut transition = None;
+    let mut export_image = false;
+    let mut toggle_table_display = state.bindings.was_pressed(app, Action::TableDisplay);
+    let mut toggle_fullscreen = state.bindings.was_pressed(app, Action::Fullscreen);
+    if state.bindings.was_pressed(app, Action::NextTurn) {
+        state.timer.next_turn(app.timer.time_since_init());
+    }
+    if state.bindings.was_pressed(app, Action::AnswerYes) {
+        state.model.sub.answer_shortcut(Answer::Yes);
+    }
+    if state.bindings.was_pressed(app, Action::AnswerNo) {
+        state.model.sub.answer_shortcut(Answer::No);
+    }
+    if state.bindings.was_pressed(app, Action::NextHint) {
+        state.model.sub.next_hint();
+    }
+
+    let output = plugins.egui(|ctx| {
+        ctx.set_visuals(state.theme.visuals());
+        let panel = state.panel;
+        let panel_rect = panel.show(ctx, !state.model.table_display, |ui| {
+            ScrollArea::vertical().show(ui, |ui| {
+                ui.heading("Cryptid Finder");
+                ui.label(RichText::new("by haselkern").weak());
+                state.changelog.gui(ui);
+                state.tutorial.gui(ui);
+                ui.add_space(LAYOUT_SPACE);
+
+                egui::CollapsingHeader::new(tr("Settings")).show(ui, |ui| {
+                    i18n::gui(ui);
+                    simplemode::gui(ui);
+                    state.theme.gui(ui);
+                    state.seams.gui(ui);
+                    state.icons.gui(ui);
+                    state.model.elimination.gui(ui);
+                    state.highlighting.gui(ui);
+                    state.smoothing.gui(ui);
+                    state.model.camera.gui(ui);
+                    state.panel.gui(ui);
+                    state.timer.gui(
+                        ui,
+                        app.timer.time_since_init(),
+                        state.bindings.input(Action::NextTurn),
+                    );
+                    ui.checkbox(
+                        &mut state.model.cluster_answers,
+                        tr("Group answers by kind"),
+                    );
+                    ui.checkbox(
+                        &mut state.show_ranges,
+                        tr("Show clue distances around the selected tile"),
+                    );
+                    ui.checkbox(
+                        &mut state.structure_letters,
+                        tr("Show the letter of the color on structures"),
+                    );
+                    ui.checkbox(
+                        &mut state.explain_on_hover,
+                        tr("Explain ruled out tiles when pointing at them"),
+                    );
+                    if ui
+                        .button(tr("Table display"))
+                        .on_hover_text(trf(
+                            "Show only the map, for a TV or projector. Press {} to leave.",
+                            &[&state.bindings.input(Action::TableDisplay)],
+                        ))
+                        .clicked()
+                    {
+                        toggle_table_display = true;
+                    }
+                    if ui.button(tr("Center map")).clicked() {
+                        state.model.camera.center_on(state.model.sub.tiles());
+                    }
+                    if ui
+                        .button(tr("Fullscreen"))
+                        .on_hover_text(trf(
+                            "Or press {}",
+                            &[&state.bindings.input(Action::Fullscreen)],
+                        ))
+                        .clicked()
+                    {
+                        toggle_fullscreen = true;
+                    }
+                });
+                egui::CollapsingHeader::new(tr("Controls")).show(ui, |ui| state.bindings.gui(ui));
+                if !simplemode::enabled() {
+                    egui::CollapsingHeader::new(tr("House rules")).show(ui, |ui| {
+                        house_rules(ui, &mut state.model.sub, houserules::gui)
+                    });
+                    egui::CollapsingHeader::new(tr("Custom clues")).show(ui, |ui| {
+                        house_rules(ui, &mut state.model.sub, customclues::gui)
+                    });
+                }
+                egui::CollapsingHeader::new(tr("Clue reference"))
+                    .show(ui, |ui| rulesref::gui(ui, state.model.sub.house_rules()));
+                egui::CollapsingHeader::new(tr("Legend")).show(ui, |ui| {
+                    legend(ui, state.model.sub.players(), state.model.sub.tiles())
+                });
+                egui::CollapsingHeader::new(tr("Bug report"))
+                    .show(ui, |ui| report::debug_gui(ui, &state.model.sub));
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Export image")).clicked() {
+                        export_image = true;
+                    }
+                    if let Some(notice) = &state.export_notice {
+                        ui.label(notice);
+                    }
+                });
+                ui.add_space(LAYOUT_SPACE);
+
+                let result = report::catch(|| state.model.sub.gui(ui));
+                transition = state.handle_error(result).flatten();
+                if let Some(t) = transition {
+                    if let Some(question) = state.model.sub.confirm_switch() {
+                        state.confirming = Some((question, t));
+                        transition = None;
+                    }
+                }
+            });
+        });
+        if let Some(rect) = panel_rect {
+            state.panel.width = rect.width();
+        }
+
+        state
+            .seams
+            .labels(ctx, state.model.sub.tiles(), &layout, state.theme.overlay());
+        state
+            .timer
+            .overlay(ctx, app.timer.time_since_init(), state.theme.overlay());
+        let map_rect = camera::tile_bounds(state.model.sub.tiles(), &layout).map(|(min, max)| {
+            let radius = Vec2::splat(state.model.camera.tile_radius);
+            let (min, max) = (min - radius, max + radius);
+            egui::Rect::from_min_max(egui::pos2(min.x, min.y), egui::pos2(max.x, max.y))
+        });
+        // Explain why the tile under the mouse was ruled out.
+        if state.explain_on_hover
+            && !state.model.is_egui_hovered
+            && state.model.dragging == Dragging::None
+        {
+            let mouse = Vec2::from(app.mouse.position());
+            let reasons = state
+                .model
+                .sub
+                .elimination_reasons(layout.world_pos_to_hex(mouse));
+            if !reasons.is_empty() {
+                egui::show_tooltip_at_pointer(ctx, egui::Id::new("elimination-reasons"), |ui| {
+                    ui.label(tr("The cryptid can't be here:"));
+                    for reason in reasons {
+                        ui.label(format!("• {reason}"));
+                    }
+                });
+            }
+        }
+        state.tutorial.overlay(
+            ctx,
+            &state.model.sub,
+            panel_rect,
+            map_rect,
+            state.theme.highlights[HighlightChannel::Selection as usize],
+        );
+        state.changelog.overlay(ctx);
+
+        let mut dismiss_error = false;
+        if let Some(error) = &state.error {
+            egui::Window::new(tr("Something went wrong"))
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    ui.label(&error.error);
+                    ui.label(tr(
+                        "Export a report and attach it to an issue on GitHub to help fix this.",
+                    ));
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("Export report")).clicked() {
+                            let result = error.export();
+                            state.error_notice =
+                                Some(result.unwrap_or_else(|e| trf("Export failed: {}", &[&e])));
+                        }
+                        if ui.button(tr("Dismiss")).clicked() {
+                            dismiss_error = true;
+                        }
+                    });
+                    if let Some(notice) = &state.error_notice {
+                        ui.label(notice);
+                    }
+                });
+        }
+        if dismiss_error {
+            state.error = None;
+        }
+
+        let now = app.timer.time_since_init();
+        if let Some((_, since)) = state.model.answer_notice {
+            if now - since > ANSWER_NOTICE_TIME {
+                state.model.answer_notice = None;
+            }
+        }
+        let toasts = report::toasts();
+        if !toasts.is_empty() || state.model.answer_notice.is_some() {
+            egui::Area::new("toasts")
+                .anchor(Align2::RIGHT_BOTTOM, [-LAYOUT_SPACE, -LAYOUT_SPACE])
+                .show(ctx, |ui| {
+                    if let Some((notice, _)) = &state.model.answer_notice {
+                        Frame::popup(ui.style()).show(ui, |ui| ui.label(notice));
+                    }
+                    for toast in &toasts {
+                        Frame::popup(ui.style()).show(ui, |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label(tr(toast));
+                                if ui.button(tr("Dismiss")).clicked() {
+                                    report::dismiss_toast(toast);
+                                }
+                            });
+                        });
+                    }
+                });
+        }
+
+        if let Some((question, t)) = &state.confirming {
+            let mut answer = None;
+            egui::Window::new(tr("Are you sure?"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(question);
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("Continue")).clicked() {
+                            answer = Some(true);
+                        }
+                        if ui.button(tr("Cancel")).clicked() {
+                            answer = Some(false);
+                        }
+                    });
+                });
+            match answer {
+                Some(true) => transition = Some(*t),
+                Some(false) => state.model.sub.cancel_switch(),
+                None => (),
+            }
+            if answer.is_some() {
+                state.confirming = None;
+            }
+        }
+
+        if transition.is_some() {
+            ctx.memory().reset_areas();
+        }
+
+        state.model.is_egui_hovered = ctx.is_pointer_over_area() || ctx.is_using_pointer();
+        state.bindings.typing = ctx.wants_keyboard_input();
+    });
+
+    gfx.render(&output);
+    state.icons.update(gfx);
+
+    if toggle_table_display {
+        state.model.table_display = !state.model.table_display;
+        app.window().set_fullscreen(state.model.table_display);
+    }
+    if toggle_fullscreen {
+        let fullscreen = app.window().is_fullscreen();
+        app.window().set_fullscreen(!fullscreen);
+    }
+
+    let title = format!("Cryptid Finder — {}", state.model.sub.title());
+    if app.window().title() != title {
+        app.window().set_title(&title);
+    }
+
+    if export_image {
+        let result = export_image_of_map(gfx, state, app.timer.time_since_init());
+        state.export_notice = Some(result.unwrap_or_else(|e| trf("Export failed: {}", &[&e])));
+    }
+
+    if let Some(transition) = transition {
+        let result = report::catch(|| switch_state(state, transition));
+        if let Some(Err(e)) = state.handle_error(result) {
+            report::toast(e);
+        }
+    }
+
+    if state.idle.became_idle(app.timer.time_since_init()) {
+        let result = report::catch(|| state.model.sub.idle());
+        state.handle_error(result);
+    }
+
+    // Panics abort in the browser, so the hook can only report the state it was given before.
+    #[cfg(target_arch = "wasm32")]
+    report::remember_state(app.timer.time_since_init(), &state.model.sub);
+}
+
+/// Leave the current substate the way it asked for.
+fn switch_state(state: &mut State, transition: Transition) -> Result<(), String> {
+    state.model.sub = state.model.sub.after(transition)?;
+    report::log(format!("Switched to {}", state.model.sub.name()));
+    Ok(())
+}
+
+/// Draw a disc like the ones players use for "yes", seen from slightly above.
+fn draw_disc(
+    draw: &mut Draw,
+    (x, y): (f32, f32),
+    radius: f32,
+    color: Color,
+    stroke: Color,
+    stroke_width: f32,
+) {
+    let squash = 0.8;
+    let thickness = radius * 0.4;
+    let side = shade(color);
+    draw.ellipse((x, y + thickness * 0.5), (radius, radius * squash))
+        .color(side);
+    draw.rect((x - radius, y - thickness * 0.5), (radius * 2.0, thickness))
+        .color(side);
+    draw.ellipse((x, y + thickness * 0.5), (radius, radius * squash))
+        .stroke_color(stroke)
+        .stroke(stroke_width);
+    draw.ellipse((x, y - thickness * 0.5), (radius, radius * squash))
+        .color(color);
+    draw.ellipse((x, y - thickness * 0.5), (radius, radius * squash))
+        .stroke_color(stroke)
+        .stroke(stroke_width);
+}
+
+/// Draw a cube like the ones players use for "no", seen from a corner.
+fn draw_cube(
+    draw: &mut Draw,
+    (x, y): (f32, f32),
+    width: f32,
+    color: Color,
+    stroke: Color,
+    stroke_width: f32,
+) {
+    let radius = width * 0.6;
+    let corner = |degrees: f32| {
+        let angle = degrees.to_radians();
+        (x + angle.cos() * radius, y + angle.sin() * radius)
+    };
+    let faces = [
+        ([-150.0, -90.0, -30.0], color),
+        ([-150.0, 150.0, 90.0], shade(color)),
+        ([90.0, 30.0, -30.0], shade(shade(color))),
+    ];
+    for (corners, face_color) in faces {
+        let [a, b, c] = corners.map(corner);
+        for fill in [true, false] {
+            let mut path = draw.path();
+            path.move_to(x, y)
+                .line_to(a.0, a.1)
+                .line_to(b.0, b.1)
+                .line_to(c.0, c.1)
+                .close();
+            if fill {
+                path.fill().color(face_color);
+            } else {
+                path.stroke(stroke_width).color(stroke);
+            }
+        }
+    }
+}
+
+/// A darker variant of the color, for sides that face away from the light.
+fn shade(color: Color) -> Color {
+    Color::new(color.r * 0.75, color.g * 0.75, color.b * 0.75, color.a)
+}
+
+/// Settings for the house rules of the map. Once the game started they are only shown, because
+/// changing the rules in the middle of a game would change what the answers so far mean.
+fn house_rules(
+    ui: &mut egui::Ui,
+    sub: &mut SubState,
+    gui: impl FnOnce(&mut egui::Ui, &mut HouseRules),
+) {
+    match sub.house_rules_mut() {
+        Some(rules) => gui(ui, rules),
+        None => {
+            let mut rules = sub.house_rules().clone();
+            ui.add_enabled_ui(false, |ui| gui(ui, &mut rules));
+            ui.add(
+                egui::Label::new(tr(
+                    "The house rules can only be changed before the game starts.",
+                ))
+                .wrap(true),
+            );
+        }
+    }
+}
+
+/// Explain what the markers on the map mean, with the animals on the tiles.
+fn legend(ui: &mut egui::Ui, players: &PlayerList, tiles: &[Tile]) {
+    let size = egui::vec2(16.0, 16.0);
+    let stroke = ui.visuals().text_color();
+    let swatch = |ui: &mut egui::Ui, sides: u8, rotation: f32, fill: Color, outline| {
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        let points = (0..sides)
+            .map(|i| {
+                let angle = rotation + i as f32 * 2.0 * PI / sides as f32;
+                rect.center() + 7.0 * egui::vec2(angle.cos(), angle.sin())
+            })
+            .collect();
+        let [r, g, b, a] = fill.rgba_u8();
+        ui.painter().add(egui::Shape::convex_polygon(
+            points,
+            egui::Color32::from_rgba_unmultiplied(r, g, b, a),
+            (1.5, outline),
+        ));
     };
 
-    for tile in state.sub.tiles() {
-        let pos = layout.hex_to_world_pos(tile.position);
+    ui.label(RichText::new(tr("Terrains")).strong());
+    for terrain in Terrain::iter() {
+        ui.horizontal(|ui| {
+            swatch(ui, 6, 0.0, terrain.into(), stroke);
+            ui.label(tr_name(terrain));
+        });
+    }
+
+    ui.label(RichText::new(tr("Animal territories")).strong());
+    for animal in Animal::all(tiles) {
+        let [r, g, b, a] = Color::from(animal).rgba_u8();
+        ui.horizontal(|ui| {
+            swatch(
+                ui,
+                6,
+                0.0,
+                Color::TRANSPARENT,
+                egui::Color32::from_rgba_unmultiplied(r, g, b, a),
+            );
+            ui.label(trf("Outline: {}", &[&tr_name(animal)]));
+        });
+    }
+
+    ui.label(RichText::new(tr("Structures")).strong());
+    for kind in StructureKind::iter() {
+        ui.horizontal(|ui| {
+            for color in StructureColor::iter() {
+                // Rotated like on the map, where the shack points up.
+                swatch(ui, structure_sides(kind), -PI / 2.0, color.into(), stroke);
+            }
+            ui.label(tr_name(kind));
+        });
+    }
+    for color in StructureColor::iter() {
+        ui.horizontal(|ui| {
+            swatch(ui, 4, PI / 4.0, color.into(), stroke);
+            ui.label(tr_name(color));
+        });
+    }
+
+    ui.label(RichText::new(tr("Markers")).strong());
+    ui.horizontal(|ui| {
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        ui.painter()
+            .circle(rect.center(), 7.0, egui::Color32::GRAY, (1.0, stroke));
+        ui.label(tr("Disc: the cryptid may be there (yes)"));
+    });
+    ui.horizontal(|ui| {
+        let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+        ui.painter()
+            .rect(rect.shrink(2.0), 0.0, egui::Color32::GRAY, (1.0, stroke));
+        ui.label(tr("Cube: the cryptid is not there (no)"));
+    });
+    for player in players.iter() {
+        ui.horizontal(|ui| {
+            let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+            ui.painter().circle(
+                rect.center(),
+                7.0,
+                egui::Color32::from(player.color),
+                (1.0, stroke),
+            );
+            ui.label(&player.name);
+        });
+    }
+    ui.label(tr(
+        "Letters are the initials of the players. A dot marks answers the app filled in.",
+    ));
+}
+
+/// Corners of the polygon for structures of the kind.
+fn structure_sides(kind: StructureKind) -> u8 {
+    match kind {
+        StructureKind::Shack => 3,
+        StructureKind::Stone => 8,
+    }
+}
 
-        let scale = if tile.small {
-            Mat3::from_scale(Vec2::splat(0.7))
+/// Draw a structure centered on the current transform, with the letter of its color if a font
+/// is given.
+fn draw_structure(
+    draw: &mut Draw,
+    building: Structure,
+    tile_radius: f32,
+    stroke: Color,
+    alpha: f32,
+    letter: Option<&Font>,
+) {
+    let sides = structure_sides(building.kind);
+    draw.polygon(sides, tile_radius * 0.5)
+        .color(building.color.into())
+        .alpha(alpha)
+        .rotate(PI);
+    draw.polygon(sides, tile_radius * 0.5)
+        .stroke(tile_radius * 0.05)
+        .stroke_color(stroke)
+        .alpha(alpha)
+        .rotate(PI);
+    if let Some(font) = letter {
+        let color = Color::from(building.color);
+        let brightness = 0.299 * color.r + 0.587 * color.g + 0.114 * color.b;
+        let text = if brightness > 0.5 {
+            Color::BLACK
         } else {
-            Mat3::IDENTITY
+            Color::WHITE
         };
-        let alpha = if tile.small { 0.6 } else { 1.0 };
+        draw.text(font, &building.color.letter().to_string())
+            .size(tile_radius * 0.3)
+            .color(text)
+            .alpha(alpha)
+            .h_align_center()
+            .v_align_middle();
+    }
+}
+
+/// Draw the tiles and everything on them. Tiles outside of `area`, the size of the drawn area,
+/// are skipped. This only reads the state, input was applied to it before.
+fn draw_map(draw: &mut Draw, state: &State, layout: &HexLayout, area: Vec2, time: f32) {
+    let tile_radius = layout.hex_size.x.abs();
+    let stroke_width = tile_radius * 0.05;
+    let view = interaction::view(&state.model, layout);
+
+    // Markers and highlights stay close to their tile, so this margin contains them.
+    let margin = Vec2::splat(tile_radius * 1.5);
+    let visible = |pos: Vec2| pos.cmpge(-margin).all() && pos.cmple(area + margin).all();
+
+    for tile in state.model.sub.tiles() {
+        let pos = layout.hex_to_world_pos(tile.position);
+        if !visible(pos) {
+            continue;
+        }
+
+        let look = state.model.elimination.look(tile);
+        let scale = Mat3::from_scale(Vec2::splat(look.scale));
+        let alpha = look.alpha;
 
         draw.transform().push(Mat3::from_translation(pos) * scale);
 
@@ -142,17 +859,14 @@ fn draw(app: &mut App, gfx: &mut Graphics, plugins: &mut Plugins, state: &mut St
         {
             draw.transform().push(Mat3::from_rotation_z(PI / 6.0));
 
-            draw.polygon(6, state.tile_radius)
-                .color(tile.terrain.into())
+            draw.polygon(6, tile_radius)
+                .color(elimination::desaturate(tile.terrain.into(), look.gray))
                 .alpha(alpha);
 
             if let Some(animal) = tile.animal {
-                let color = match animal {
-                    Animal::Bear => Color::BLACK,
-                    Animal::Cougar => Color::from_bytes(220, 25, 11, 255),
-                };
+                let color = animal.into();
 
-                draw.polygon(6, state.tile_radius * 0.9)
+                draw.polygon(6, tile_radius * 0.9)
                     .stroke(stroke_width)
                     .stroke_color(color)
                     .alpha(alpha);
@@ -162,188 +876,299 @@ fn draw(app: &mut App, gfx: &mut Graphics, plugins: &mut Plugins, state: &mut St
         }
 
         // Draw icon for terrain
-        if !tile.small {
-            let tex = state.icons.get(&tile.terrain).unwrap();
-            let scale = state.tile_radius * 0.015;
+        if look.icon > 0.0 {
+            let tex = state.icons.get(tile.terrain);
             let size = Vec2::from(tex.size());
+            // Icons cover the same part of the tile, whatever the resolution of the pack.
+            let scale = tile_radius * 0.72 * state.icons.scale / size.max_element();
             draw.transform()
                 .push(Mat3::from_scale(Vec2::splat(scale)) * Mat3::from_translation(size * -0.5));
-            draw.image(tex).alpha(0.3);
+            draw.image(tex).alpha(state.icons.alpha * look.icon);
             draw.transform().pop();
         }
 
-        // Draw structure
-        if let Some(building) = tile.structure {
-            let color = building.color.into();
-            let sides = match building.kind {
-                StructureKind::Shack => 3,
-                StructureKind::Stone => 8,
-            };
+        // Cross out ruled out tiles with diagonal lines that stay inside of the hex.
+        if look.hatching > 0.0 {
+            let radius = tile_radius * 0.8;
+            let along = Vec2::new(1.0, -1.0).normalize();
+            for i in -3..=3 {
+                let distance = i as f32 * radius / 4.0;
+                let half = (radius * radius - distance * distance).sqrt();
+                let a = along.perp() * distance - along * half;
+                let b = along.perp() * distance + along * half;
+                draw.line((a.x, a.y), (b.x, b.y))
+                    .width(stroke_width)
+                    .color(state.theme.stroke())
+                    .alpha(look.hatching);
+            }
+        }
 
-            draw.polygon(sides, state.tile_radius * 0.5)
-                .color(color)
-                .rotate(PI);
-            draw.polygon(sides, state.tile_radius * 0.5)
-                .stroke(stroke_width)
-                .stroke_color(Color::BLACK)
-                .rotate(PI);
+        // Draw structure. A structure that is being dragged away stays faded until it is dropped.
+        if let Some(building) = tile.structure {
+            let dragged = view.dragged_from == Some(tile.position);
+            let alpha = if dragged { 0.4 } else { 1.0 };
+            let letter = state.structure_letters.then_some(&state.font);
+            draw_structure(
+                draw,
+                building,
+                tile_radius,
+                state.theme.stroke(),
+                alpha,
+                letter,
+            );
         }
 
-        // Draw answers in a little circle.
-        for (i, (&player_id, &answer)) in tile.answers.iter().enumerate() {
-            let player = state.sub.players().get(player_id);
-            let angle = i as f32;
-            let radius = state.tile_radius * 0.6;
-            let x = angle.cos() * radius;
-            let y = angle.sin() * radius;
-            let circle_radius = state.tile_radius * 0.2;
-            let box_width = state.tile_radius * 0.4;
-            let outline_stroke = (stroke_width * 0.5).max(1.0);
-            match answer {
-                Answer::Unknown => (),
-                Answer::Yes => {
-                    draw.circle(circle_radius)
-                        .color(player.color.into())
-                        .position(x, y);
-                    draw.circle(circle_radius)
-                        .stroke_color(Color::BLACK)
-                        .stroke(outline_stroke)
-                        .position(x, y);
-                }
+        // Draw answers like the discs and cubes of the game, evenly spread in a little circle.
+        let (circle_radius, box_width) =
+            interaction::marker_sizes(state.model.table_display, tile_radius);
+        let outline_stroke = (stroke_width * 0.5).max(1.0);
+        let markers = interaction::markers(&state.model, tile, tile_radius);
+        for (x, y, player_id, answer) in markers {
+            let player = state.model.sub.players().get(player_id);
+            let color = player.color.into();
+            let stroke = state.theme.stroke();
+            // Where the top of the marker is, to put the initial on.
+            let top = match answer {
                 Answer::No => {
-                    draw.rect(
-                        (x - box_width * 0.5, y - box_width * 0.5),
-                        (box_width, box_width),
-                    )
-                    .color(player.color.into());
-                    draw.rect(
-                        (x - box_width * 0.5, y - box_width * 0.5),
-                        (box_width, box_width),
-                    )
-                    .stroke_color(Color::BLACK)
-                    .stroke(outline_stroke);
+                    draw_cube(draw, (x, y), box_width, color, stroke, outline_stroke);
+                    y - box_width * 0.25
+                }
+                _ => {
+                    draw_disc(draw, (x, y), circle_radius, color, stroke, outline_stroke);
+                    y - circle_radius * 0.2
                 }
+            };
+
+            // Show whose answer it is, for users that can't tell the colors apart.
+            draw.text(&state.font, &player.initial())
+                .position(x, top)
+                .size(circle_radius * 1.2)
+                .color(theme::readable_on(player.color.into()))
+                .h_align_center()
+                .v_align_middle();
+
+            // Mark answers the app filled in, so they can be told apart from entered ones.
+            if state.model.sub.is_auto_answer(tile.position, player_id) {
+                // Off center, to keep the initial readable.
+                let offset = circle_radius * 0.8;
+                draw.circle(circle_radius * 0.3)
+                    .color(stroke)
+                    .position(x + offset, top - offset);
             }
         }
 
         draw.transform().pop();
     }
 
-    // This tile might be highlighted
-    for highlight in state.sub.highlights() {
-        let position = layout.hex_to_world_pos(highlight);
+    for (hex, label) in state.model.sub.tile_labels() {
+        let position = layout.hex_to_world_pos(hex) + Vec2::new(0.0, tile_radius * 0.3);
+        if !visible(position) {
+            continue;
+        }
+        draw.text(&state.font, &label)
+            .position(position.x, position.y)
+            .size(tile_radius * 0.3)
+            .color(state.theme.stroke())
+            .h_align_center()
+            .v_align_middle();
+    }
+
+    for hex in state.model.sub.shaded() {
+        let position = layout.hex_to_world_pos(hex);
+        if !visible(position) {
+            continue;
+        }
         draw.transform().push(Mat3::from_translation(position));
-        draw.polygon(6, state.tile_radius * 0.8)
-            .stroke(stroke_width)
-            .stroke_color(Color::YELLOW)
-            .rotate(app.timer.time_since_init());
+        draw.polygon(6, tile_radius)
+            .color(Color::from(state.theme.background.to_array()))
+            .alpha(0.7)
+            .rotate(PI / 6.0);
         draw.transform().pop();
     }
 
-    gfx.render(&draw);
+    // Tint the tiles of the player layer in the color of the player.
+    if let Some((color, tiles)) = state.model.sub.player_layer() {
+        for hex in tiles {
+            let position = layout.hex_to_world_pos(hex);
+            if !visible(position) {
+                continue;
+            }
+            draw.transform().push(Mat3::from_translation(position));
+            draw.polygon(6, tile_radius)
+                .color(color.into())
+                .alpha(0.4)
+                .rotate(PI / 6.0);
+            draw.transform().pop();
+        }
+    }
 
-    let mut switch_state = false;
+    for hex in state.model.sub.region() {
+        let position = layout.hex_to_world_pos(hex);
+        if !visible(position) {
+            continue;
+        }
+        draw.transform().push(Mat3::from_translation(position));
+        draw.polygon(6, tile_radius)
+            .color(state.theme.overlay())
+            .alpha(0.3)
+            .rotate(PI / 6.0);
+        draw.transform().pop();
+    }
 
-    let output = plugins.egui(|ctx| {
-        let frame = Frame::side_top_panel(&Style::default()).inner_margin(LAYOUT_SPACE);
-        egui::SidePanel::left("sidepanel")
-            .resizable(true)
-            .frame(frame)
-            .show(ctx, |ui| {
-                ScrollArea::vertical().show(ui, |ui| {
-                    ui.heading("Cryptid Finder");
-                    ui.label(RichText::new("by haselkern").weak());
-                    ui.add_space(LAYOUT_SPACE);
-
-                    switch_state = state.sub.gui(ui);
-                });
-            });
+    // Bands around the selected tile for the distances of the clues, to see which clues
+    // could include the tile.
+    if let (true, [selected]) = (state.show_ranges, state.model.sub.highlights().as_slice()) {
+        let rules = state.model.sub.house_rules();
+        let distances = [
+            rules.terrain,
+            rules.either_animal,
+            rules.animal,
+            rules.structure_kind,
+            rules.structure_color,
+        ];
+        // Every band has the tiles that are farther away than the band inside of it.
+        let mut inner = 0;
+        for distance in (1..=houserules::MAX_CLUE_DISTANCE).filter(|d| distances.contains(d)) {
+            let band = selected.rings(inner + 1..distance + 1).flatten();
+            inner = distance;
+            for hex in band {
+                let position = layout.hex_to_world_pos(hex);
+                let on_map = state.model.sub.tiles().iter().any(|t| t.position == hex);
+                if !on_map || !visible(position) {
+                    continue;
+                }
+                draw.transform().push(Mat3::from_translation(position));
+                draw.polygon(6, tile_radius)
+                    .color(state.theme.range_band(distance))
+                    .alpha(0.3)
+                    .rotate(PI / 6.0);
+                draw.transform().pop();
+            }
+        }
+    }
 
-        if switch_state {
-            ctx.memory().reset_areas();
+    state
+        .seams
+        .draw(draw, state.model.sub.tiles(), layout, state.theme.overlay());
+
+    // Tiles that were just ruled out or became possible again
+    for tile in state.model.sub.tiles() {
+        let flash = state.model.elimination.flash(tile.position, time);
+        let position = layout.hex_to_world_pos(tile.position);
+        if flash <= 0.0 || !visible(position) {
+            continue;
         }
+        draw.transform().push(Mat3::from_translation(position));
+        draw.polygon(6, tile_radius)
+            .color(state.theme.highlight(HighlightChannel::Selection))
+            .alpha(0.5 * flash)
+            .rotate(PI / 6.0);
+        draw.transform().pop();
+    }
 
-        state.is_egui_hovered = ctx.is_pointer_over_area() || ctx.is_using_pointer();
-    });
+    // Ghost of the dragged structure under the mouse, over the tile it would be dropped on.
+    if let Some(ghost) = &view.ghost {
+        draw.transform().push(Mat3::from_translation(
+            layout.hex_to_world_pos(ghost.target),
+        ));
+        draw.polygon(6, tile_radius)
+            .color(state.theme.drop_target(ghost.valid))
+            .alpha(0.4)
+            .rotate(PI / 6.0);
+        draw.transform().pop();
 
-    gfx.render(&output);
+        draw.transform()
+            .push(Mat3::from_translation(ghost.position));
+        let letter = state.structure_letters.then_some(&state.font);
+        draw_structure(
+            draw,
+            ghost.structure,
+            tile_radius,
+            state.theme.stroke(),
+            0.7,
+            letter,
+        );
+        draw.transform().pop();
+    }
 
-    if switch_state {
-        match &state.sub {
-            SubState::BuildingMap(sub) => state.sub = SubState::PlacingStructures(sub.into()),
-            SubState::PlacingStructures(sub) => state.sub = SubState::TryingClues(sub.into()),
-            other => {
-                panic!("{other:?} wanted to switch states, but I don't know how :( This is a bug.")
+    // This tile might be highlighted, possibly in several channels at once
+    let highlights = std::iter::once((HighlightChannel::Selection, state.model.sub.highlights()))
+        .chain(state.model.sub.channel_highlights());
+    for (channel, tiles) in highlights {
+        for highlight in tiles {
+            let position = layout.hex_to_world_pos(highlight);
+            if !visible(position) {
+                continue;
             }
-        };
+            draw.transform().push(Mat3::from_translation(position));
+            draw.polygon(6, tile_radius * channel.radius())
+                .stroke(stroke_width * state.highlighting.thickness)
+                .stroke_color(state.theme.highlight(channel))
+                .rotate(time * state.highlighting.speed);
+            draw.transform().pop();
+        }
     }
-
-    // Perform the update now. We now know whether we should process mouse events,
-    // or if egui already handled them.
-    update(app, state, &layout);
 }
 
-fn update(app: &mut App, state: &mut State, layout: &HexLayout) {
-    let mouse = Vec2::from(app.mouse.position());
-    let mouse_hex = layout.world_pos_to_hex(mouse);
+/// Render the map into an offscreen texture and save it as a PNG.
+fn export_image_of_map(gfx: &mut Graphics, state: &State, time: f32) -> Result<String, String> {
+    let mut layout = HexLayout {
+        orientation: HexOrientation::flat(),
+        origin: Vec2::ZERO,
+        hex_size: Vec2::splat(EXPORT_TILE_RADIUS),
+    };
 
-    if app.mouse.left_was_released() && !state.is_egui_hovered {
-        state.sub.click(mouse_hex);
-    }
+    let (min, max) = camera::tile_bounds(state.model.sub.tiles(), &layout)
+        .ok_or_else(|| "there is no map".to_owned())?;
 
-    if app.mouse.left_is_down() {
-        match state.dragging {
-            Dragging::None => {
-                // Don't start dragging anything when the mouse is over egui
-                if state.is_egui_hovered {
-                    return;
-                }
+    // Leave some space around the outermost tile centers.
+    let margin = Vec2::splat(EXPORT_TILE_RADIUS * 1.5);
+    let min = min - margin;
+    let size = (max + margin - min).ceil();
+    let (width, height) = (size.x as u32, size.y as u32);
+    layout.origin = -min;
 
-                // Start dragging a structure (if that is allowed) or the screen.
-                let over_tile = state.sub.tiles().iter().find(|t| t.position == mouse_hex);
-                let has_structure = over_tile.map(|t| t.structure.is_some()).unwrap_or(false);
+    let texture = gfx
+        .create_render_texture(width as i32, height as i32)
+        .build()?;
+    let mut draw = texture.create_draw();
+    draw.clear(Color::from(state.theme.background.to_array()));
+    draw_map(&mut draw, state, &layout, size, time);
+    gfx.render_to(&texture, &draw);
 
-                if has_structure && state.are_structures_draggable() {
-                    state.dragging = Dragging::Structure(mouse_hex);
-                } else {
-                    state.dragging = Dragging::Offset {
-                        mouse_last_frame: app.mouse.position().into(),
-                    };
-                }
-            }
-            Dragging::Offset { mouse_last_frame } => {
-                let delta = mouse - mouse_last_frame;
-                state.offset += delta;
-                state.dragging = Dragging::Offset {
-                    mouse_last_frame: mouse,
-                };
-            }
-            Dragging::Structure(at) => {
-                // Check if the hex under the mouse has space for the structure.
-                // Move the structure (currently "at" another hex) to there.
-                let mouse_hex = layout.world_pos_to_hex(mouse);
-                let tiles = state.sub.tiles_mut();
-
-                let Some(to) = tiles.iter().position(|t| t.position == mouse_hex) else {
-                    // No tile under mouse.
-                    return;
-                };
-
-                if tiles[to].structure.is_some() {
-                    // Tile under mouse already has a structure.
-                    return;
-                }
+    let mut pixels = vec![0; (width * height * 4) as usize];
+    gfx.read_pixels(&texture).read_to(&mut pixels)?;
+    // Render textures are stored upside down.
+    let pixels: Vec<u8> = pixels
+        .chunks(width as usize * 4)
+        .rev()
+        .flatten()
+        .copied()
+        .collect();
 
-                let from = tiles
-                    .iter()
-                    .position(|t| t.position == at)
-                    .expect("The map changed drastically. This should not happen.");
+    let png = export::png(width, height, &pixels)?;
+    export::save("cryptid-finder.png", &png)
+}
 
-                tiles[to].structure = tiles[from].structure.take();
-                state.dragging = Dragging::Structure(mouse_hex);
-            }
-        }
+/// What the user did with the mouse and the keyboard on the map in this frame, in the order
+/// in which it is applied.
+fn messages(app: &App, bindings: &Bindings) -> Vec<Message> {
+    let mouse = Vec2::from(app.mouse.position());
+    let mut messages = Vec::new();
+    if bindings.was_pressed(app, Action::Cancel) {
+        messages.push(Message::Cancel);
+    }
+    if bindings.was_released(app, Action::Select) {
+        messages.push(Message::Click {
+            mouse,
+            modified: app.keyboard.shift(),
+        });
+    }
+    let selecting = bindings.is_down(app, Action::Select);
+    if selecting || bindings.is_down(app, Action::Pan) {
+        messages.push(Message::Hold { mouse, selecting });
     } else {
-        state.dragging = Dragging::None;
+        messages.push(Message::Release);
     }
+    messages
 }
@@ -1,14 +1,23 @@
 use std::{
     collections::{BTreeMap, HashSet},
     fmt, iter,
+    sync::LazyLock,
 };
 
 use hexx::{Hex, HexMap, OffsetHexMode};
 use itertools::Itertools;
 use notan::{egui, prelude::Color};
+use serde::{Deserialize, Serialize};
 use strum::{Display, EnumIter, IntoEnumIterator};
 
-#[derive(Debug, Clone, Copy, EnumIter, PartialEq, Eq, Hash, Display)]
+use crate::{
+    customclues::{self, CustomClue},
+    houserules::HouseRules,
+    i18n::{tr, tr_name, trf},
+    report,
+};
+
+#[derive(Debug, Clone, Copy, EnumIter, PartialEq, Eq, Hash, Display, Serialize, Deserialize)]
 pub enum Terrain {
     Desert,
     Forest,
@@ -17,6 +26,19 @@ pub enum Terrain {
     Mountain,
 }
 
+impl Terrain {
+    /// Letter of the terrain in piece definitions.
+    pub fn letter(self) -> char {
+        match self {
+            Terrain::Desert => 'D',
+            Terrain::Forest => 'F',
+            Terrain::Water => 'W',
+            Terrain::Swamp => 'S',
+            Terrain::Mountain => 'M',
+        }
+    }
+}
+
 impl From<Terrain> for Color {
     fn from(value: Terrain) -> Self {
         match value {
@@ -29,13 +51,127 @@ impl From<Terrain> for Color {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Display)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, Serialize, Deserialize)]
+#[serde(from = "AnimalFile")]
 pub enum Animal {
     Bear,
     Cougar,
+    /// An animal of a fan map. It carries its definition, so that it means the same in every
+    /// map and file.
+    #[strum(disabled)]
+    Custom(&'static CustomAnimal),
+}
+
+/// An animal the user added, like the wolf of some fan maps.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CustomAnimal {
+    pub name: String,
+    /// Letter of the animal in piece definitions.
+    pub letter: char,
+    pub color: [u8; 3],
+}
+
+/// Animals as written in files, where custom animals come with their definition.
+#[derive(Deserialize)]
+enum AnimalFile {
+    Bear,
+    Cougar,
+    Custom(CustomAnimal),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Display, Hash)]
+impl From<AnimalFile> for Animal {
+    fn from(value: AnimalFile) -> Self {
+        match value {
+            AnimalFile::Bear => Animal::Bear,
+            AnimalFile::Cougar => Animal::Cougar,
+            AnimalFile::Custom(animal) => Animal::Custom(Box::leak(Box::new(animal))),
+        }
+    }
+}
+
+impl Animal {
+    /// The animals of the game and the custom animals on the tiles.
+    pub fn all(tiles: &[Tile]) -> Vec<Self> {
+        let custom = tiles
+            .iter()
+            .filter_map(|t| t.animal)
+            .filter(|a| matches!(a, Animal::Custom(_)));
+        Self::iter()
+            .chain(custom)
+            .unique_by(|a| a.letter())
+            .collect()
+    }
+
+    /// A new custom animal, so that it can be used for pieces and clues. It must differ from
+    /// the animals that already exist.
+    pub fn custom(
+        name: &str,
+        letter: char,
+        color: [u8; 3],
+        existing: &[Self],
+    ) -> Result<Self, String> {
+        if existing
+            .iter()
+            .any(|a| a.to_string().eq_ignore_ascii_case(name))
+        {
+            return Err(trf("There already is an animal called {}", &[&name]));
+        }
+        if !letter.is_ascii_lowercase() || existing.iter().any(|a| a.letter() == letter) {
+            return Err(trf(
+                "{} can't be the letter of an animal, use a lowercase letter that is not used yet",
+                &[&letter],
+            ));
+        }
+
+        let animal = CustomAnimal {
+            name: name.to_owned(),
+            letter,
+            color,
+        };
+        // Animals are only added by hand, so keeping them for the rest of the program is cheap
+        // and lets them be copied like the animals of the game.
+        Ok(Self::Custom(Box::leak(Box::new(animal))))
+    }
+
+    /// Letter of the animal in piece definitions.
+    pub fn letter(self) -> char {
+        match self {
+            Animal::Bear => 'b',
+            Animal::Cougar => 'c',
+            Animal::Custom(a) => a.letter,
+        }
+    }
+
+    /// Reverse of [Animal::letter], among the given animals.
+    pub fn from_letter(letter: char, animals: &[Self]) -> Option<Self> {
+        animals.iter().copied().find(|a| a.letter() == letter)
+    }
+}
+
+impl fmt::Display for Animal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Animal::Bear => write!(f, "Bear"),
+            Animal::Cougar => write!(f, "Cougar"),
+            Animal::Custom(a) => write!(f, "{}", a.name),
+        }
+    }
+}
+
+impl From<Animal> for Color {
+    fn from(value: Animal) -> Self {
+        match value {
+            Animal::Bear => Color::BLACK,
+            Animal::Cougar => Color::from_bytes(220, 25, 11, 255),
+            Animal::Custom(a) => {
+                let [r, g, b] = a.color;
+                Color::from_bytes(r, g, b, 255)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Display, Hash, Serialize, Deserialize)]
 pub enum StructureColor {
     White,
     Green,
@@ -43,6 +179,18 @@ pub enum StructureColor {
     Black,
 }
 
+impl StructureColor {
+    /// Letter to tell the colors apart without seeing them. Black is K, like in print.
+    pub fn letter(self) -> char {
+        match self {
+            StructureColor::White => 'W',
+            StructureColor::Green => 'G',
+            StructureColor::Blue => 'B',
+            StructureColor::Black => 'K',
+        }
+    }
+}
+
 impl From<StructureColor> for Color {
     fn from(value: StructureColor) -> Self {
         match value {
@@ -54,7 +202,7 @@ impl From<StructureColor> for Color {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Display, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Display, Hash, Serialize, Deserialize)]
 pub enum StructureKind {
     #[strum(to_string = "Abandoned Shack")]
     Shack,
@@ -62,14 +210,14 @@ pub enum StructureKind {
     Stone,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Structure {
     pub kind: StructureKind,
     pub color: StructureColor,
 }
 
 /// A single hexagon in the game world.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct Tile {
     pub position: Hex,
     pub terrain: Terrain,
@@ -81,8 +229,52 @@ pub struct Tile {
     pub answers: BTreeMap<PlayerID, Answer>,
 }
 
+/// Number of tile columns in a single piece.
+pub const PIECE_WIDTH: i32 = 6;
+/// Number of tile rows in a single piece.
+pub const PIECE_HEIGHT: i32 = 3;
+
+/// Number of piece columns in the map of the base game.
+pub const DEFAULT_MAP_COLUMNS: usize = 2;
+/// Number of piece rows in the map of the base game.
+pub const DEFAULT_MAP_ROWS: usize = 3;
+
+/// Returns the position of the piece in the arrangement of pieces that contains the hex.
+pub fn piece_slot(hex: Hex) -> [i32; 2] {
+    let [col, row] = hex.to_offset_coordinates(OffsetHexMode::OddColumns);
+    [col.div_euclid(PIECE_WIDTH), row.div_euclid(PIECE_HEIGHT)]
+}
+
+/// Human readable name of a position on the map, like "F4".
+/// Columns are letters from left to right, rows are numbers from top to bottom.
+pub fn coordinate(hex: Hex) -> String {
+    let [col, row] = hex.to_offset_coordinates(OffsetHexMode::OddColumns);
+    match u8::try_from(col) {
+        Ok(col) if col < 26 => format!("{}{}", (b'A' + col) as char, row + 1),
+        _ => format!("{col}/{}", row + 1),
+    }
+}
+
+/// Reverse of [coordinate]. Parses names like "F4" into positions.
+pub fn parse_coordinate(name: &str) -> Option<Hex> {
+    let mut chars = name.trim().chars();
+    let col = chars.next()?.to_ascii_uppercase();
+    if !col.is_ascii_uppercase() {
+        return None;
+    }
+    let col = (col as u8 - b'A') as i32;
+    let row: i32 = chars.as_str().parse().ok()?;
+    if row < 1 {
+        return None;
+    }
+    Some(Hex::from_offset_coordinates(
+        [col, row - 1],
+        OffsetHexMode::OddColumns,
+    ))
+}
+
 /// Choice for building the world. User can select a piece and decide to rotate it 180°.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PieceChoice {
     pub piece: Piece,
     pub rotated: bool,
@@ -91,7 +283,7 @@ pub struct PieceChoice {
 impl fmt::Display for PieceChoice {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.rotated {
-            write!(f, "{} (rotated)", self.piece.name())
+            write!(f, "{}", trf("{} (rotated)", &[&self.piece.name()]))
         } else {
             write!(f, "{}", self.piece.name())
         }
@@ -107,7 +299,8 @@ impl From<Piece> for PieceChoice {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Serialize, Deserialize)]
+#[serde(from = "PieceFile")]
 pub enum Piece {
     One,
     Two,
@@ -115,72 +308,248 @@ pub enum Piece {
     Four,
     Five,
     Six,
+    /// A piece from the piece editor. It carries its definition, so that it means the same in
+    /// every map and file.
+    #[strum(disabled)]
+    Custom(&'static CustomPiece),
+}
+
+/// A piece the user created in the piece editor.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CustomPiece {
+    pub name: String,
+    pub definition: String,
+    /// The custom animals the definition uses.
+    pub animals: Vec<Animal>,
+}
+
+/// Pieces as written in files, where custom pieces come with their definition.
+#[derive(Deserialize)]
+enum PieceFile {
+    One,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Custom(CustomPiece),
+}
+
+impl From<PieceFile> for Piece {
+    fn from(value: PieceFile) -> Self {
+        match value {
+            PieceFile::One => Piece::One,
+            PieceFile::Two => Piece::Two,
+            PieceFile::Three => Piece::Three,
+            PieceFile::Four => Piece::Four,
+            PieceFile::Five => Piece::Five,
+            PieceFile::Six => Piece::Six,
+            PieceFile::Custom(piece) => Piece::Custom(Box::leak(Box::new(piece))),
+        }
+    }
 }
 
 impl Piece {
-    pub fn definition(self) -> &'static str {
+    /// The pieces of the game and the custom pieces.
+    pub fn all(custom: &[Self]) -> Vec<Self> {
+        Self::iter().chain(custom.iter().copied()).collect()
+    }
+
+    /// A new custom piece, so that it can be used for building maps. The definition may use
+    /// the given animals, and the name must differ from the existing pieces.
+    pub fn custom(
+        name: &str,
+        definition: &str,
+        animals: &[Animal],
+        existing: &[Self],
+    ) -> Result<Self, String> {
+        if existing.iter().any(|p| p.name() == name) {
+            return Err(format!("There already is a piece called {name}"));
+        }
+        let parsed = parse_piece(name, definition, animals)?;
+
+        let piece = CustomPiece {
+            name: name.to_owned(),
+            definition: definition.to_owned(),
+            animals: parsed
+                .0
+                .iter()
+                .filter_map(|t| t.animal)
+                .filter(|a| matches!(a, Animal::Custom(_)))
+                .unique()
+                .collect(),
+        };
+        // Pieces are only added by hand, so keeping them for the rest of the program is cheap
+        // and lets them be copied like the pieces of the game.
+        Ok(Self::Custom(Box::leak(Box::new(piece))))
+    }
+
+    pub fn definition(self) -> String {
         match self {
-            Piece::One => include_str!("../assets/piece-1.txt"),
-            Piece::Two => include_str!("../assets/piece-2.txt"),
-            Piece::Three => include_str!("../assets/piece-3.txt"),
-            Piece::Four => include_str!("../assets/piece-4.txt"),
-            Piece::Five => include_str!("../assets/piece-5.txt"),
-            Piece::Six => include_str!("../assets/piece-6.txt"),
+            Piece::One => include_str!("../assets/piece-1.txt").to_owned(),
+            Piece::Two => include_str!("../assets/piece-2.txt").to_owned(),
+            Piece::Three => include_str!("../assets/piece-3.txt").to_owned(),
+            Piece::Four => include_str!("../assets/piece-4.txt").to_owned(),
+            Piece::Five => include_str!("../assets/piece-5.txt").to_owned(),
+            Piece::Six => include_str!("../assets/piece-6.txt").to_owned(),
+            Piece::Custom(p) => p.definition.clone(),
         }
     }
 
-    pub fn name(self) -> &'static str {
+    pub fn name(self) -> String {
         match self {
-            Piece::One => "1",
-            Piece::Two => "2",
-            Piece::Three => "3",
-            Piece::Four => "4",
-            Piece::Five => "5",
-            Piece::Six => "6",
+            Piece::One => "1".to_owned(),
+            Piece::Two => "2".to_owned(),
+            Piece::Three => "3".to_owned(),
+            Piece::Four => "4".to_owned(),
+            Piece::Five => "5".to_owned(),
+            Piece::Six => "6".to_owned(),
+            Piece::Custom(p) => p.name.clone(),
         }
     }
 
-    pub fn parse(self) -> ParsedPiece {
-        let mut tiles = Vec::new();
-        for (row_i, row) in self.definition().lines().enumerate() {
-            let chars: Vec<char> = row.chars().collect();
-            for col_i in (0..row.len()).step_by(2) {
-                let terrain = chars[col_i];
-                let animal = chars.get(col_i + 1).copied().unwrap_or(' '); // Be lenient with missing trailing spaces
-
-                let terrain = match terrain {
-                    'W' => Terrain::Water,
-                    'D' => Terrain::Desert,
-                    'M' => Terrain::Mountain,
-                    'F' => Terrain::Forest,
-                    'S' => Terrain::Swamp,
-                    unknown => panic!("Terrain {unknown} invalid, must be one of WDMFS"),
-                };
+    /// The animals of the game and the custom animals of the piece.
+    fn animals(self) -> Vec<Animal> {
+        let custom = match self {
+            Piece::Custom(p) => p.animals.as_slice(),
+            _ => &[],
+        };
+        Animal::iter().chain(custom.iter().copied()).collect()
+    }
 
-                let animal = match animal {
-                    'b' => Some(Animal::Bear),
-                    'c' => Some(Animal::Cougar),
-                    _ => None,
-                };
+    pub fn parse(self) -> Result<ParsedPiece, PieceParseError> {
+        parse_piece(&self.name(), &self.definition(), &self.animals())
+    }
+}
+
+/// What is wrong with the definition of a piece and where. Lines and columns count from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PieceParseError {
+    pub piece: String,
+    pub line: usize,
+    pub column: usize,
+    pub problem: PieceProblem,
+}
 
-                tiles.push(Tile {
-                    position: Hex::from_offset_coordinates(
-                        [col_i as i32 / 2, row_i as i32],
-                        OffsetHexMode::OddColumns,
-                    ),
-                    terrain,
-                    animal,
-                    structure: None, // Structures get added later
-                    small: false,
-                    answers: Default::default(),
-                });
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PieceProblem {
+    /// The character is not the letter of a terrain.
+    UnknownTerrain(char),
+    /// The character is neither a space nor the letter of an animal.
+    UnknownAnimal(char),
+    /// A row with this many tiles instead of a row as wide as a piece.
+    RowLength(usize),
+    /// This many rows instead of as many as a piece is high.
+    RowCount(usize),
+}
+
+impl fmt::Display for PieceParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Piece {}, line {}, column {}: ",
+            self.piece, self.line, self.column
+        )?;
+        match self.problem {
+            PieceProblem::UnknownTerrain(c) => {
+                write!(f, "terrain {c:?} is invalid, must be one of WDMFS")
+            }
+            PieceProblem::UnknownAnimal(c) => {
+                write!(f, "there is no animal with the letter {c:?}")
             }
+            PieceProblem::RowLength(n) => {
+                write!(f, "the row has {n} tiles, but must have {PIECE_WIDTH}")
+            }
+            PieceProblem::RowCount(n) => {
+                write!(f, "the piece has {n} rows, but must have {PIECE_HEIGHT}")
+            }
+        }
+    }
+}
+
+impl From<PieceParseError> for String {
+    fn from(value: PieceParseError) -> Self {
+        value.to_string()
+    }
+}
+
+/// Read the tiles of a piece from its definition. Every tile is a terrain letter followed by
+/// an animal letter of one of the animals or a space.
+fn parse_piece(
+    name: &str,
+    definition: &str,
+    animals: &[Animal],
+) -> Result<ParsedPiece, PieceParseError> {
+    let error = |line: usize, column: usize, problem| PieceParseError {
+        piece: name.to_owned(),
+        line,
+        column,
+        problem,
+    };
+
+    // Lines may end with "\r\n" when the file was written on Windows. Blank lines at the end,
+    // like from a trailing newline, are not rows.
+    let mut rows = definition
+        .lines()
+        .map(|line| line.trim_end_matches('\r'))
+        .collect_vec();
+    while rows.last().is_some_and(|row| row.trim().is_empty()) {
+        rows.pop();
+    }
+    if rows.len() != PIECE_HEIGHT as usize {
+        let line = rows.len().min(PIECE_HEIGHT as usize) + 1;
+        return Err(error(line, 1, PieceProblem::RowCount(rows.len())));
+    }
+
+    let mut tiles = Vec::new();
+    for (row_i, row) in rows.into_iter().enumerate() {
+        // Be lenient with missing trailing spaces.
+        let chars = row.trim_end().chars().collect_vec();
+        let width = chars.len().div_ceil(2);
+        if width != PIECE_WIDTH as usize {
+            let column = width.min(PIECE_WIDTH as usize) * 2 + 1;
+            return Err(error(row_i + 1, column, PieceProblem::RowLength(width)));
+        }
+
+        for (col_i, tile) in chars.chunks(2).enumerate() {
+            let terrain = Terrain::iter()
+                .find(|t| t.letter() == tile[0])
+                .ok_or_else(|| {
+                    error(
+                        row_i + 1,
+                        col_i * 2 + 1,
+                        PieceProblem::UnknownTerrain(tile[0]),
+                    )
+                })?;
+            let animal = match tile.get(1).copied().unwrap_or(' ') {
+                ' ' => None,
+                letter => Some(Animal::from_letter(letter, animals).ok_or_else(|| {
+                    error(
+                        row_i + 1,
+                        col_i * 2 + 2,
+                        PieceProblem::UnknownAnimal(letter),
+                    )
+                })?),
+            };
+
+            tiles.push(Tile {
+                position: Hex::from_offset_coordinates(
+                    [col_i as i32, row_i as i32],
+                    OffsetHexMode::OddColumns,
+                ),
+                terrain,
+                animal,
+                structure: None, // Structures get added later
+                small: false,
+                answers: Default::default(),
+            });
         }
-        ParsedPiece(tiles)
     }
+    Ok(ParsedPiece(tiles))
 }
 
 /// One of the six 6x3 pieces the world is built out of.
+#[derive(Debug, Clone, Serialize)]
 pub struct ParsedPiece(pub Vec<Tile>);
 
 impl ParsedPiece {
@@ -197,32 +566,53 @@ impl ParsedPiece {
             tile.position += t;
         }
     }
+
+    /// Write the piece in the format of the piece files. The origin is expected to be top-left.
+    pub fn definition(&self) -> String {
+        let mut lines = Vec::new();
+        for row in 0..PIECE_HEIGHT {
+            let mut line = String::new();
+            for col in 0..PIECE_WIDTH {
+                let hex = Hex::from_offset_coordinates([col, row], OffsetHexMode::OddColumns);
+                let Some(tile) = self.0.iter().find(|t| t.position == hex) else {
+                    continue;
+                };
+                line.push(tile.terrain.letter());
+                line.push(tile.animal.map_or(' ', Animal::letter));
+            }
+            lines.push(line.trim_end().to_owned());
+        }
+        lines.join("\n") + "\n"
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Clue {
     pub kind: ClueKind,
+    #[serde(default)]
     pub inverted: bool,
 }
 
 impl Clue {
-    /// Returns every possible clue for the available structure colors/kinds.
+    /// Returns every possible clue for the available animals and structure colors/kinds under
+    /// the house rules.
     pub fn all<'a>(
+        animals: &'a [Animal],
         structure_colors: &'a [StructureColor],
         structure_kinds: &'a [StructureKind],
         with_inverted: bool,
+        rules: &'a HouseRules,
     ) -> impl Iterator<Item = Self> + 'a {
-        let clues = ClueKind::all(structure_colors, structure_kinds).map(|kind| Clue {
+        let kinds = move || ClueKind::all(animals, structure_colors, structure_kinds, rules);
+        let clues = kinds().map(|kind| Clue {
             kind,
             inverted: false,
         });
         let inverted: Box<dyn Iterator<Item = Clue>> = if with_inverted {
-            Box::new(
-                ClueKind::all(structure_colors, structure_kinds).map(|kind| Clue {
-                    kind,
-                    inverted: true,
-                }),
-            )
+            Box::new(kinds().map(|kind| Clue {
+                kind,
+                inverted: true,
+            }))
         } else {
             Box::new(iter::empty())
         };
@@ -242,7 +632,7 @@ impl From<ClueKind> for Clue {
 impl fmt::Display for Clue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if self.inverted {
-            write!(f, "not {}", self.kind)
+            write!(f, "{}", trf("not {}", &[&self.kind]))
         } else {
             write!(f, "{}", self.kind)
         }
@@ -250,39 +640,125 @@ impl fmt::Display for Clue {
 }
 
 /// All possible clues.
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(from = "ClueKindFile")]
 pub enum ClueKind {
-    /// The creature is with one space of the terrain.
-    Terrain(Terrain),
+    /// The creature is within n spaces of the target.
+    WithinN { n: u32, target: ClueTarget },
     /// The creature is on one of these types of terrain.
     TwoTerrains(Terrain, Terrain),
-    /// The creature is within one space of either animal.
+    /// A clue from a clue file, see [customclues].
+    Custom(&'static CustomClue),
+}
+
+/// What a clue with a distance is about.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum ClueTarget {
+    Terrain(Terrain),
+    /// Territory of either animal.
     EitherAnimal,
-    /// The creature is within two spaces of the animal territory.
+    /// Territory of the animal.
     Animal(Animal),
-    /// The creature is within two spaces of the type of structure.
     StructureKind(StructureKind),
-    /// The creature is within two spaces of the type of structure.
     StructureColor(StructureColor),
 }
 
+impl ClueTarget {
+    /// Returns true if the tile is the target of the clue.
+    pub fn is_on(self, tile: &Tile) -> bool {
+        match self {
+            ClueTarget::Terrain(terrain) => tile.terrain == terrain,
+            ClueTarget::EitherAnimal => tile.animal.is_some(),
+            ClueTarget::Animal(animal) => tile.animal == Some(animal),
+            ClueTarget::StructureKind(kind) => tile.structure.is_some_and(|s| s.kind == kind),
+            ClueTarget::StructureColor(color) => tile.structure.is_some_and(|s| s.color == color),
+        }
+    }
+}
+
+impl fmt::Display for ClueTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            ClueTarget::Terrain(t) => tr_name(t),
+            ClueTarget::EitherAnimal => tr("any animal territory").to_owned(),
+            ClueTarget::Animal(a) => tr_name(a),
+            ClueTarget::StructureKind(k) => tr_name(k),
+            ClueTarget::StructureColor(c) => trf("{} structure", &[&tr_name(c)]),
+        };
+        write!(f, "{text}")
+    }
+}
+
+/// Clues as written in scenario files. Clues without a distance have the official one.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ClueKindFile {
+    Official(ClueTarget),
+    WithDistance(ClueKindWithDistance),
+}
+
+#[derive(Deserialize)]
+enum ClueKindWithDistance {
+    WithinN { n: u32, target: ClueTarget },
+    TwoTerrains(Terrain, Terrain),
+    Custom(CustomClue),
+}
+
+impl From<ClueKindFile> for ClueKind {
+    fn from(value: ClueKindFile) -> Self {
+        match value {
+            ClueKindFile::Official(target) => ClueKind::WithinN {
+                n: HouseRules::OFFICIAL.distance(target),
+                target,
+            },
+            ClueKindFile::WithDistance(ClueKindWithDistance::WithinN { n, target }) => {
+                ClueKind::WithinN { n, target }
+            }
+            ClueKindFile::WithDistance(ClueKindWithDistance::TwoTerrains(a, b)) => {
+                ClueKind::TwoTerrains(a, b)
+            }
+            ClueKindFile::WithDistance(ClueKindWithDistance::Custom(clue)) => {
+                ClueKind::Custom(customclues::keep(clue))
+            }
+        }
+    }
+}
+
 impl ClueKind {
-    /// Returns every possible clue for the available structure colors/kinds.
+    /// A clue about the target, with the distance of the house rules.
+    pub fn within(target: ClueTarget, rules: &HouseRules) -> Self {
+        ClueKind::WithinN {
+            n: rules.distance(target),
+            target,
+        }
+    }
+
+    /// Returns every possible clue for the available animals and structure colors/kinds under
+    /// the house rules.
     pub fn all<'a>(
+        animals: &'a [Animal],
         structure_colors: &'a [StructureColor],
         structure_kinds: &'a [StructureKind],
+        rules: &'a HouseRules,
     ) -> impl Iterator<Item = Self> + 'a {
-        let terrain = Terrain::iter().map(ClueKind::Terrain);
+        let within = move |target| ClueKind::within(target, rules);
+        let terrain = Terrain::iter().map(ClueTarget::Terrain).map(within);
         let two_terrains = Terrain::iter()
             .combinations(2)
             .map(|ts| ClueKind::TwoTerrains(ts[0], ts[1]));
-        let either_animal = [ClueKind::EitherAnimal];
-        let animal = Animal::iter().map(ClueKind::Animal);
-        let structure_kind = structure_kinds.iter().copied().map(ClueKind::StructureKind);
+        let either_animal = [within(ClueTarget::EitherAnimal)];
+        let animal = animals.iter().copied().map(ClueTarget::Animal).map(within);
+        let structure_kind = structure_kinds
+            .iter()
+            .copied()
+            .map(ClueTarget::StructureKind)
+            .map(within);
         let structure_color = structure_colors
             .iter()
             .copied()
-            .map(ClueKind::StructureColor);
+            .map(ClueTarget::StructureColor)
+            .map(within);
+        let custom = rules.custom_clues.iter().copied().map(ClueKind::Custom);
 
         terrain
             .chain(two_terrains)
@@ -290,51 +766,79 @@ impl ClueKind {
             .chain(animal)
             .chain(structure_kind)
             .chain(structure_color)
+            .chain(custom)
     }
 }
 
 impl fmt::Display for ClueKind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ClueKind::Terrain(t) => write!(f, "within one space of {t}"),
-            ClueKind::TwoTerrains(a, b) => write!(f, "on {a} or {b}"),
-            ClueKind::EitherAnimal => write!(f, "within one space of bear or cougar"),
-            ClueKind::Animal(a) => write!(f, "within two spaces of {a}"),
-            ClueKind::StructureKind(k) => write!(f, "within two spaces of {k}"),
-            ClueKind::StructureColor(c) => write!(f, "within three spaces of {c} structure"),
-        }
+        let text = match self {
+            ClueKind::WithinN { n, target } => trf("within {} of {}", &[&spaces(*n), target]),
+            ClueKind::TwoTerrains(a, b) => trf("on {} or {}", &[&tr_name(a), &tr_name(b)]),
+            ClueKind::Custom(c) => c.text.clone(),
+        };
+        write!(f, "{text}")
+    }
+}
+
+/// A distance in words, like "two spaces".
+pub fn spaces(n: u32) -> String {
+    match n {
+        1 => tr("one space").to_owned(),
+        2 => tr("two spaces").to_owned(),
+        3 => tr("three spaces").to_owned(),
+        n => trf("{} spaces", &[&n]),
     }
 }
 
-/// A map of tiles.
-#[derive(Debug, Default)]
-pub struct Map(pub Vec<Tile>);
+/// A map of tiles, and the house rules of the game that is played on it. The rules decide
+/// which clues are possible, so they stay the same for the whole game.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Map {
+    pub tiles: Vec<Tile>,
+    pub rules: HouseRules,
+}
 
 impl Map {
+    /// Build the world out of pieces, arranged row by row in the given number of columns. The
+    /// map has the official rules.
+    pub fn from_pieces(pieces: &[PieceChoice], columns: usize) -> Result<Self, String> {
+        let mut tiles = Vec::new();
+        for (i, piece) in pieces.iter().enumerate() {
+            let col = (i % columns) as i32 * PIECE_WIDTH;
+            let row = (i / columns) as i32 * PIECE_HEIGHT;
+            let offset = Hex::from_offset_coordinates([col, row], OffsetHexMode::OddColumns);
+
+            let mut parsed = piece.piece.parse()?;
+            if piece.rotated {
+                parsed.rotate();
+            }
+            parsed.translate(offset);
+            tiles.extend(parsed.0);
+        }
+        Ok(Self {
+            tiles,
+            rules: HouseRules::OFFICIAL,
+        })
+    }
+
     pub fn get(&self, at: Hex) -> Option<&Tile> {
-        self.0.iter().find(|tile| tile.position == at)
+        self.tiles.iter().find(|tile| tile.position == at)
     }
 
     pub fn get_mut(&mut self, at: Hex) -> Option<&mut Tile> {
-        self.0.iter_mut().find(|tile| tile.position == at)
+        self.tiles.iter_mut().find(|tile| tile.position == at)
     }
 
     /// Returns true if the cryptid could be at the given position according to the clue.
     pub fn clue_applies(&self, clue: Clue, position: Hex) -> bool {
         let applies = match clue.kind {
-            ClueKind::Terrain(terrain) => self.any(position, 1, |t| t.terrain == terrain),
+            ClueKind::WithinN { n, target } => self.any(position, n, |t| target.is_on(t)),
             ClueKind::TwoTerrains(a, b) => match self.get(position) {
                 Some(tile) => tile.terrain == a || tile.terrain == b,
                 None => false,
             },
-            ClueKind::EitherAnimal => self.any(position, 1, |t| t.animal.is_some()),
-            ClueKind::Animal(animal) => self.any(position, 2, |t| t.animal == Some(animal)),
-            ClueKind::StructureKind(kind) => self.any(position, 2, |t| {
-                t.structure.map(|s| s.kind == kind).unwrap_or(false)
-            }),
-            ClueKind::StructureColor(color) => self.any(position, 3, |t| {
-                t.structure.map(|s| s.color == color).unwrap_or(false)
-            }),
+            ClueKind::Custom(c) => self.any(position, c.distance, |t| c.condition.is_met(t)),
         };
 
         if clue.inverted {
@@ -344,9 +848,90 @@ impl Map {
         }
     }
 
+    /// Returns the tiles where the cryptid could be according to the clue. Same as
+    /// [Map::clue_applies] for every tile, but much faster on the whole map.
+    pub fn mask(&self, clue: Clue) -> TileMask {
+        let within = |distance: u32, condition: &dyn Fn(&Tile) -> bool| {
+            let targets = self
+                .tiles
+                .iter()
+                .filter(|t| condition(t))
+                .map(|t| t.position)
+                .collect_vec();
+            TileMask::from_fn(self.tiles.len(), |i| {
+                let position = self.tiles[i].position;
+                targets
+                    .iter()
+                    .any(|t| t.unsigned_distance_to(position) <= distance)
+            })
+        };
+        let applies = match clue.kind {
+            ClueKind::WithinN { n, target } => within(n, &|t| target.is_on(t)),
+            ClueKind::TwoTerrains(a, b) => TileMask::from_fn(self.tiles.len(), |i| {
+                [a, b].contains(&self.tiles[i].terrain)
+            }),
+            ClueKind::Custom(c) => within(c.distance, &|t| c.condition.is_met(t)),
+        };
+
+        if clue.inverted {
+            applies.complement(self.tiles.len())
+        } else {
+            applies
+        }
+    }
+
+    /// Returns every possible clue together with the tiles it applies to.
+    pub fn masks(&self, with_inverted: bool) -> Vec<(Clue, TileMask)> {
+        Clue::all(
+            &self.animals(),
+            &self.structure_colors(),
+            &self.structure_kinds(),
+            with_inverted,
+            &self.rules,
+        )
+        .map(|clue| (clue, self.mask(clue)))
+        .collect()
+    }
+
+    /// Returns the tiles where the player answered yes and the tiles where they answered no.
+    pub fn answer_masks(&self, player: PlayerID) -> (TileMask, TileMask) {
+        let answered = |answer| {
+            TileMask::from_fn(self.tiles.len(), |i| {
+                self.tiles[i].answers.get(&player) == Some(&answer)
+            })
+        };
+        (answered(Answer::Yes), answered(Answer::No))
+    }
+
+    /// Returns every possible clue together with the positions it applies to.
+    pub fn coverage(&self, with_inverted: bool) -> Vec<(Clue, HashSet<Hex>)> {
+        Clue::all(
+            &self.animals(),
+            &self.structure_colors(),
+            &self.structure_kinds(),
+            with_inverted,
+            &self.rules,
+        )
+        .map(|clue| {
+            let positions = self
+                .tiles
+                .iter()
+                .map(|t| t.position)
+                .filter(|&position| self.clue_applies(clue, position))
+                .collect();
+            (clue, positions)
+        })
+        .collect()
+    }
+
+    /// Returns the animals of the game and the custom animals on the map.
+    pub fn animals(&self) -> Vec<Animal> {
+        Animal::all(&self.tiles)
+    }
+
     /// Returns [StructureColor]s present on the map.
     pub fn structure_colors(&self) -> Vec<StructureColor> {
-        self.0
+        self.tiles
             .iter()
             .filter_map(|t| t.structure)
             .map(|s| s.color)
@@ -356,7 +941,7 @@ impl Map {
 
     /// Returns [StructureKind]s present on the map.
     pub fn structure_kinds(&self) -> Vec<StructureKind> {
-        self.0
+        self.tiles
             .iter()
             .filter_map(|t| t.structure)
             .map(|s| s.kind)
@@ -364,45 +949,18 @@ impl Map {
             .collect()
     }
 
-    /// Return a list of possible clues for the player, respecting the answers they already gave.
-    pub fn clues_for_player(&self, player: PlayerID, with_inverted: bool) -> Vec<Clue> {
-        let mut result = Vec::new();
-
-        for clue in Clue::all(
-            &self.structure_colors(),
-            &self.structure_kinds(),
-            with_inverted,
-        ) {
-            let tiles_with_answer = self
-                .0
-                .iter()
-                .filter_map(|t| t.answers.get(&player).map(|&a| (a, t)));
-
-            // We now need to decide whether the clue is possible for the player by answering:
-            //     Does the clue contradict any answers the player gave?
-
-            let mut contradiction = false;
-            for (answer, tile) in tiles_with_answer {
-                let clue_applies = self.clue_applies(clue, tile.position);
-                let contradicts = match (answer, clue_applies) {
-                    (Answer::Unknown, _) => false,
-                    (Answer::Yes, true) => false,
-                    (Answer::Yes, false) => true,
-                    (Answer::No, true) => true,
-                    (Answer::No, false) => false,
-                };
-                if contradicts {
-                    contradiction = true;
-                    break;
-                }
-            }
-
-            if !contradiction {
-                result.push(clue);
-            }
-        }
-
-        result
+    /// The clues out of the masks that fit the answers of the player. The masks only change
+    /// with the map, so compute them once with [Map::masks] and keep them.
+    pub fn allowed<'a>(
+        &self,
+        masks: &'a [(Clue, TileMask)],
+        player: PlayerID,
+    ) -> impl Iterator<Item = &'a (Clue, TileMask)> {
+        let (yes, no) = self.answer_masks(player);
+        // A clue is possible if it doesn't contradict any answer the player gave.
+        masks
+            .iter()
+            .filter(move |(_, mask)| yes.is_subset(mask) && !no.intersects(mask))
     }
 
     /// Check any fields for the condition. Position is always checked. Add fields with "distance".
@@ -424,23 +982,92 @@ impl Map {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+/// A set of tiles of a [Map], by their index in it. Deductions on many tiles at once become a
+/// few bitwise operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileMask(Vec<u64>);
+
+impl TileMask {
+    /// The tiles out of the first `len` for which the function returns true.
+    pub fn from_fn(len: usize, mut f: impl FnMut(usize) -> bool) -> Self {
+        let mut words = vec![0; len.div_ceil(64)];
+        for i in (0..len).filter(|&i| f(i)) {
+            words[i / 64] |= 1 << (i % 64);
+        }
+        Self(words)
+    }
+
+    /// Every tile out of the first `len` that is not in the mask.
+    pub fn complement(&self, len: usize) -> Self {
+        Self::from_fn(len, |i| !self.contains(i))
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        self.0.get(i / 64).is_some_and(|w| w >> (i % 64) & 1 == 1)
+    }
+
+    /// The tiles that are in both masks.
+    pub fn and(&self, other: &Self) -> Self {
+        Self(self.0.iter().zip(&other.0).map(|(a, b)| a & b).collect())
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.0.iter().zip(&other.0).any(|(a, b)| a & b != 0)
+    }
+
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.0.iter().zip(&other.0).all(|(a, b)| a & !b == 0)
+    }
+
+    pub fn count(&self) -> usize {
+        self.0.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&w| w == 0)
+    }
+
+    /// The only tile in the mask, if there is exactly one.
+    pub fn single(&self) -> Option<usize> {
+        if self.count() != 1 {
+            return None;
+        }
+        let (i, w) = self.0.iter().enumerate().find(|(_, &w)| w != 0)?;
+        Some(i * 64 + w.trailing_zeros() as usize)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord, Serialize)]
 pub struct PlayerID(usize);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Player {
     pub id: PlayerID,
     pub name: String,
     pub color: PlayerColor,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Display)]
+impl Player {
+    /// First letter of the name, to tell players apart without colors.
+    pub fn initial(&self) -> String {
+        self.name
+            .chars()
+            .next()
+            .map(|c| c.to_uppercase().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Hash, Display, Serialize)]
 pub enum PlayerColor {
     Red,
     Purple,
     Orange,
     Green,
     Blue,
+    /// Any color, e.g. to match pawns from an expansion.
+    #[strum(disabled, to_string = "Custom")]
+    Custom([u8; 3]),
 }
 
 impl From<PlayerColor> for egui::Color32 {
@@ -451,6 +1078,7 @@ impl From<PlayerColor> for egui::Color32 {
             PlayerColor::Orange => Self::from_rgb(246, 159, 38),
             PlayerColor::Green => Self::from_rgb(38, 158, 117),
             PlayerColor::Blue => Self::from_rgb(85, 197, 223),
+            PlayerColor::Custom([r, g, b]) => Self::from_rgb(r, g, b),
         }
     }
 }
@@ -463,9 +1091,10 @@ impl From<PlayerColor> for Color {
 }
 
 /// Answer a player gave on a tile.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, EnumIter, Display)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, EnumIter, Display, Serialize, Deserialize)]
 pub enum Answer {
     /// The player gave no information for a tile.
+    #[default]
     Unknown,
     /// The player revealed that the cryptid may be on the tile in question.
     Yes,
@@ -473,21 +1102,31 @@ pub enum Answer {
     No,
 }
 
-impl Default for Answer {
-    fn default() -> Self {
-        Self::Unknown
-    }
+/// An answer a player gave on a tile at some point in the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct AnswerEvent {
+    pub player: PlayerID,
+    pub position: Hex,
+    pub answer: Answer,
 }
 
-#[derive(Debug, Clone, Default)]
+/// Stand-in for players that don't exist, so that a bug does not crash the app.
+static UNKNOWN_PLAYER: LazyLock<Player> = LazyLock::new(|| Player {
+    id: PlayerID(usize::MAX),
+    name: "Unknown player".to_owned(),
+    color: PlayerColor::Red,
+});
+
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct PlayerList(Vec<Player>);
 
 impl PlayerList {
+    /// Returns the player with the ID. An unknown player is returned (and reported) for invalid IDs.
     pub fn get(&self, id: PlayerID) -> &Player {
-        self.0
-            .iter()
-            .find(|p| p.id == id)
-            .unwrap_or_else(|| panic!("Invalid {id:?} provided"))
+        self.0.iter().find(|p| p.id == id).unwrap_or_else(|| {
+            report::toast(format!("Invalid {id:?} provided. This is a bug."));
+            &UNKNOWN_PLAYER
+        })
     }
 
     pub fn remove(&mut self, id: PlayerID) {
@@ -506,6 +1145,14 @@ impl PlayerList {
         self.0.len()
     }
 
+    /// Add a player with the given name and return their ID.
+    pub fn push_named(&mut self, name: &str) -> PlayerID {
+        self.push_new();
+        let player = self.0.last_mut().expect("a player was just added");
+        player.name = name.to_owned();
+        player.id
+    }
+
     pub fn push_new(&mut self) {
         let id = self.0.iter().map(|p| p.id.0).max().unwrap_or(0) + 1;
         let all_colors: HashSet<PlayerColor> = PlayerColor::iter().collect();
@@ -519,15 +1166,92 @@ impl PlayerList {
 
         self.0.push(Player {
             id: PlayerID(id),
-            name: "Some Player".to_owned(),
+            name: tr("Some Player").to_owned(),
             color,
         })
     }
 }
 
 /// Describe some fields with a text for the user.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Hint {
     pub text: String,
     pub tiles: Vec<Hex>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::customclues::{self, Condition, CustomClue};
+
+    /// The map of the game with a structure of every kind and color, and a custom clue.
+    fn map() -> Map {
+        let pieces = Piece::iter().map(PieceChoice::from).collect_vec();
+        let mut map = Map::from_pieces(&pieces, DEFAULT_MAP_COLUMNS).expect("the pieces fit");
+        let structures = StructureKind::iter().cartesian_product(StructureColor::iter());
+        for (i, (kind, color)) in structures.enumerate() {
+            map.tiles[i * 13].structure = Some(Structure { kind, color });
+        }
+        map.rules.custom_clues.push(customclues::keep(CustomClue {
+            text: "within one space of water or a shack".to_owned(),
+            distance: 1,
+            condition: Condition::Any(vec![
+                Condition::Terrain(Terrain::Water),
+                Condition::StructureKind(StructureKind::Shack),
+            ]),
+        }));
+        map
+    }
+
+    #[test]
+    fn rotating_a_piece_twice_changes_nothing() {
+        for piece in Piece::iter() {
+            let parsed = piece.parse().expect("pieces of the game are valid");
+            let mut rotated = parsed.clone();
+            rotated.rotate();
+            assert_ne!(rotated.0, parsed.0, "piece {}", piece.name());
+            rotated.rotate();
+            assert_eq!(rotated.0, parsed.0, "piece {}", piece.name());
+        }
+    }
+
+    #[test]
+    fn translating_a_piece_keeps_the_distances() {
+        let parsed = Piece::One.parse().expect("pieces of the game are valid");
+        let mut moved = parsed.clone();
+        moved.translate(Hex::new(7, -3));
+        let distances = |piece: &ParsedPiece| {
+            piece
+                .0
+                .iter()
+                .cartesian_product(&piece.0)
+                .map(|(a, b)| a.position.unsigned_distance_to(b.position))
+                .collect_vec()
+        };
+        assert_eq!(distances(&moved), distances(&parsed));
+    }
+
+    #[test]
+    fn an_inverted_clue_applies_where_the_clue_does_not() {
+        let map = map();
+        let len = map.tiles.len();
+        for (clue, mask) in map.masks(false) {
+            let inverted = Clue {
+                inverted: true,
+                ..clue
+            };
+            assert_eq!(map.mask(inverted), mask.complement(len), "{clue}");
+            for (i, tile) in map.tiles.iter().enumerate() {
+                let applies = map.clue_applies(clue, tile.position);
+                assert_eq!(mask.contains(i), applies, "{clue} on {:?}", tile.position);
+                assert_eq!(
+                    map.clue_applies(inverted, tile.position),
+                    !applies,
+                    "{clue} on {:?}",
+                    tile.position
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! A short text for a whole board, to share a setup in a chat.
+//!
+//! The text has three parts separated by semicolons: the pieces row by row, with an "r" for
+//! rotated pieces, the number of pieces in a row, and the structures with their positions.
+//! For example `1,2r,3,4,5r,6;2;white shack C4,white stone F7`. Custom pieces are written by
+//! their name, so a code with them only works where the same pieces were created.
+
+use std::fmt;
+
+use hexx::{Hex, OffsetHexMode};
+use itertools::Itertools;
+use strum::IntoEnumIterator;
+
+use crate::{
+    i18n::{tr, trf},
+    model::{
+        coordinate, parse_coordinate, piece_slot, Piece, PieceChoice, Structure, StructureColor,
+        StructureKind, Tile, DEFAULT_MAP_COLUMNS, PIECE_HEIGHT, PIECE_WIDTH,
+    },
+};
+
+/// Everything about a board that can't be seen on the pieces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    /// Pieces row by row.
+    pub pieces: Vec<PieceChoice>,
+    /// Number of pieces next to each other.
+    pub columns: usize,
+    pub structures: Vec<(Hex, Structure)>,
+}
+
+impl Board {
+    /// Describe the structures on the tiles of a map built from the pieces.
+    pub fn new(pieces: &[PieceChoice], columns: usize, tiles: &[Tile]) -> Self {
+        Self {
+            pieces: pieces.to_vec(),
+            columns,
+            structures: tiles
+                .iter()
+                .filter_map(|t| Some((t.position, t.structure?)))
+                .collect(),
+        }
+    }
+
+    /// Recognize the pieces of a map among the given pieces. Fails if tiles were changed, so
+    /// that they match no piece.
+    pub fn from_tiles(tiles: &[Tile], pieces: &[Piece]) -> Result<Self, String> {
+        let candidates = pieces;
+        let slots = tiles.iter().into_group_map_by(|t| piece_slot(t.position));
+        let columns = slots.keys().map(|[col, _]| col + 1).max().unwrap_or(0);
+        let rows = slots.keys().map(|[_, row]| row + 1).max().unwrap_or(0);
+
+        let mut pieces = Vec::new();
+        for (row, col) in (0..rows).cartesian_product(0..columns) {
+            let slot = slots.get(&[col, row]).cloned().unwrap_or_default();
+            let offset = Hex::from_offset_coordinates(
+                [col * PIECE_WIDTH, row * PIECE_HEIGHT],
+                OffsetHexMode::OddColumns,
+            );
+            let choice = candidates
+                .iter()
+                .copied()
+                .cartesian_product([false, true])
+                .map(|(piece, rotated)| PieceChoice { piece, rotated })
+                .find(|choice| {
+                    let Ok(mut parsed) = choice.piece.parse() else {
+                        return false;
+                    };
+                    if choice.rotated {
+                        parsed.rotate();
+                    }
+                    parsed.translate(offset);
+                    parsed.0.len() == slot.len()
+                        && parsed.0.iter().all(|p| {
+                            slot.iter().any(|t| {
+                                t.position == p.position
+                                    && t.terrain == p.terrain
+                                    && t.animal == p.animal
+                            })
+                        })
+                })
+                .ok_or_else(|| tr("The map does not match the pieces anymore").to_owned())?;
+            pieces.push(choice);
+        }
+
+        Ok(Self::new(&pieces, columns as usize, tiles))
+    }
+
+    /// Read a board whose pieces are among the given pieces.
+    pub fn parse(text: &str, pieces: &[Piece]) -> Result<Self, String> {
+        let mut parts = text.trim().split(';').map(str::trim);
+        let candidates = pieces;
+        let pieces = parts
+            .next()
+            .unwrap_or_default()
+            .split(',')
+            .map(|piece| parse_piece(piece, candidates))
+            .collect::<Result<Vec<_>, _>>()?;
+        let columns = match parts.next() {
+            Some(columns) => columns
+                .parse()
+                .map_err(|_| trf("{} is not a number of columns", &[&columns]))?,
+            None => DEFAULT_MAP_COLUMNS,
+        };
+        let structures = match parts.next() {
+            Some(structures) if !structures.is_empty() => structures
+                .split(',')
+                .map(parse_structure)
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => Vec::new(),
+        };
+        if parts.next().is_some() {
+            return Err(tr("A board has at most three parts separated by ;").to_owned());
+        }
+
+        if columns == 0 || pieces.len() % columns != 0 {
+            return Err(trf(
+                "{} pieces can't be arranged in rows of {}",
+                &[&pieces.len(), &columns],
+            ));
+        }
+        if !pieces.iter().map(|choice| choice.piece).all_unique() {
+            return Err(tr("Every piece must be used once").to_owned());
+        }
+        Ok(Self {
+            pieces,
+            columns,
+            structures,
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.pieces.len() / self.columns
+    }
+}
+
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pieces = self
+            .pieces
+            .iter()
+            .map(|choice| {
+                let rotated = if choice.rotated { "r" } else { "" };
+                format!("{}{rotated}", choice.piece.name())
+            })
+            .join(",");
+        let structures = self
+            .structures
+            .iter()
+            .sorted_by_key(|(_, s)| (s.color as usize, s.kind as usize))
+            .map(|(hex, s)| {
+                format!(
+                    "{} {} {}",
+                    color_name(s.color),
+                    kind_name(s.kind),
+                    coordinate(*hex)
+                )
+            })
+            .join(",");
+        write!(f, "{pieces};{};{structures}", self.columns)
+    }
+}
+
+fn parse_piece(text: &str, pieces: &[Piece]) -> Result<PieceChoice, String> {
+    let text = text.trim();
+    let find = |name: &str| pieces.iter().copied().find(|p| p.name() == name);
+    if let Some(piece) = find(text) {
+        return Ok(piece.into());
+    }
+    match text.strip_suffix('r').and_then(find) {
+        Some(piece) => Ok(PieceChoice {
+            piece,
+            rotated: true,
+        }),
+        None => Err(trf("Unknown piece {}", &[&text])),
+    }
+}
+
+fn parse_structure(text: &str) -> Result<(Hex, Structure), String> {
+    let invalid = || trf("{} is not like \"white shack C4\"", &[&text.trim()]);
+    let [color, kind, at] = text
+        .split_whitespace()
+        .collect_vec()
+        .try_into()
+        .map_err(|_| invalid())?;
+    let color = StructureColor::iter()
+        .find(|&c| color_name(c) == color.to_lowercase())
+        .ok_or_else(invalid)?;
+    let kind = StructureKind::iter()
+        .find(|&k| kind_name(k) == kind.to_lowercase())
+        .ok_or_else(invalid)?;
+    let at = parse_coordinate(at).ok_or_else(invalid)?;
+    Ok((at, Structure { kind, color }))
+}
+
+/// Names in the notation don't depend on the language of the app.
+fn color_name(color: StructureColor) -> String {
+    color.to_string().to_lowercase()
+}
+
+fn kind_name(kind: StructureKind) -> &'static str {
+    match kind {
+        StructureKind::Shack => "shack",
+        StructureKind::Stone => "stone",
+    }
+}
@@ -0,0 +1,87 @@
+//! Where the panel with all the controls is shown: on either side of the window, or as a
+//! window that floats over the map.
+
+use notan::egui::{self, Frame};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::{
+    i18n::{tr, tr_name},
+    LAYOUT_SPACE,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumIter, Display)]
+pub enum PanelSide {
+    #[default]
+    Left,
+    Right,
+    Floating,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Panel {
+    pub side: PanelSide,
+    /// Width of the panel, kept when the panel moves to another side.
+    pub width: f32,
+}
+
+impl Default for Panel {
+    fn default() -> Self {
+        Self {
+            side: PanelSide::default(),
+            width: 300.0,
+        }
+    }
+}
+
+impl Panel {
+    pub fn gui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(tr("Panel"));
+            egui::ComboBox::new("panel-side", "")
+                .selected_text(tr_name(self.side))
+                .show_ui(ui, |ui| {
+                    for side in PanelSide::iter() {
+                        ui.selectable_value(&mut self.side, side, tr_name(side));
+                    }
+                });
+        });
+    }
+
+    /// Show the panel with the contents, if it is shown at all. Returns where the panel is,
+    /// which also tells the width the user resized it to.
+    pub fn show(
+        self,
+        ctx: &egui::Context,
+        shown: bool,
+        add_contents: impl FnOnce(&mut egui::Ui),
+    ) -> Option<egui::Rect> {
+        let frame = Frame::side_top_panel(&ctx.style()).inner_margin(LAYOUT_SPACE);
+        let response = match self.side {
+            PanelSide::Left => {
+                egui::SidePanel::left("sidepanel")
+                    .resizable(true)
+                    .default_width(self.width)
+                    .frame(frame)
+                    .show_animated(ctx, shown, add_contents)?
+                    .response
+            }
+            PanelSide::Right => {
+                egui::SidePanel::right("sidepanel-right")
+                    .resizable(true)
+                    .default_width(self.width)
+                    .frame(frame)
+                    .show_animated(ctx, shown, add_contents)?
+                    .response
+            }
+            PanelSide::Floating if shown => {
+                egui::Window::new("Cryptid Finder")
+                    .resizable(true)
+                    .default_width(self.width)
+                    .show(ctx, add_contents)?
+                    .response
+            }
+            PanelSide::Floating => return None,
+        };
+        Some(response.rect)
+    }
+}
@@ -0,0 +1,72 @@
+//! Small files that are kept between sessions, like the bindings and the last seen version.
+//! They are kept in the config folder of the platform, or in the storage of the browser.
+
+pub use platform::{load, store};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod platform {
+    use std::{env, path::PathBuf};
+
+    /// The folder of the app within the config folder of the platform.
+    fn folder() -> Option<PathBuf> {
+        let var = |name| {
+            env::var_os(name)
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+        };
+        let base = if cfg!(target_os = "windows") {
+            var("APPDATA")
+        } else if cfg!(target_os = "macos") {
+            var("HOME").map(|home| home.join("Library").join("Application Support"))
+        } else {
+            var("XDG_CONFIG_HOME").or_else(|| var("HOME").map(|home| home.join(".config")))
+        }?;
+        Some(base.join("cryptid-finder"))
+    }
+
+    /// Contents of the file, if it was stored before.
+    pub fn load(file: &str) -> Option<String> {
+        std::fs::read_to_string(folder()?.join(file)).ok()
+    }
+
+    pub fn store(file: &str, contents: &str) -> Result<(), String> {
+        let folder = folder().ok_or("There is no config folder.")?;
+        std::fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+        std::fs::write(folder.join(file), contents).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod platform {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen(inline_js = r#"
+        export function load(key) {
+            return window.localStorage.getItem(key);
+        }
+        export function store(key, contents) {
+            window.localStorage.setItem(key, contents);
+        }
+    "#)]
+    extern "C" {
+        #[wasm_bindgen(catch, js_name = load)]
+        fn load_js(key: &str) -> Result<Option<String>, JsValue>;
+        #[wasm_bindgen(catch, js_name = store)]
+        fn store_js(key: &str, contents: &str) -> Result<(), JsValue>;
+    }
+
+    /// Files are kept under the name without the extension, like "cryptid-finder-version".
+    fn key(file: &str) -> String {
+        let name = file.split_once('.').map_or(file, |(name, _)| name);
+        format!("cryptid-finder-{name}")
+    }
+
+    /// Contents of the file, if it was stored before.
+    pub fn load(file: &str) -> Option<String> {
+        load_js(&key(file)).ok().flatten()
+    }
+
+    pub fn store(file: &str, contents: &str) -> Result<(), String> {
+        store_js(&key(file), contents).map_err(|e| format!("{e:?}"))
+    }
+}
@@ -0,0 +1,494 @@
+//! Collecting information about errors, so that users can attach it to bug reports. Users can
+//! also copy the same information by hand when the deductions look wrong.
+//!
+//! Panics are caught on native platforms. In the browser they abort the app instead, so the
+//! panic hook writes the report to the storage of the browser, and it is offered on the next
+//! start.
+
+use std::{
+    collections::VecDeque,
+    panic::{self, AssertUnwindSafe},
+    sync::Mutex,
+};
+
+use notan::egui;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    export,
+    i18n::{tr, trf},
+    prefs,
+};
+
+/// Number of log messages to keep for reports.
+const LOG_LENGTH: usize = 100;
+
+static LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+static TOASTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+/// The state from shortly before, with the time it was taken, for reports of panics that
+/// cannot be caught.
+static STATE: Mutex<Option<(f32, Value)>> = Mutex::new(None);
+
+/// Seconds between two states that are kept for crash reports.
+#[cfg(target_arch = "wasm32")]
+const STATE_INTERVAL: f32 = 2.0;
+
+/// File of the report of a panic that aborted the app.
+const CRASH_FILE: &str = "crash-report.json";
+
+/// Remember a message for error reports.
+pub fn log(message: impl Into<String>) {
+    let Ok(mut log) = LOG.lock() else {
+        return;
+    };
+    if log.len() == LOG_LENGTH {
+        log.pop_front();
+    }
+    log.push_back(message.into());
+}
+
+/// Show an error to the user that does not need a full report. The same message is only shown once.
+pub fn toast(message: impl Into<String>) {
+    let message = message.into();
+    let Ok(mut toasts) = TOASTS.lock() else {
+        return;
+    };
+    if !toasts.contains(&message) {
+        log(format!("Error: {message}"));
+        toasts.push(message);
+    }
+}
+
+/// Errors that should currently be shown to the user.
+pub fn toasts() -> Vec<String> {
+    TOASTS.lock().map(|t| t.clone()).unwrap_or_default()
+}
+
+/// Stop showing the error.
+pub fn dismiss_toast(message: &str) {
+    if let Ok(mut toasts) = TOASTS.lock() {
+        toasts.retain(|t| t != message);
+    }
+}
+
+/// Remember panic messages, so that they can be reported when the panic is caught. Where
+/// panics abort, the report is stored for the next start instead.
+pub fn install_panic_hook() {
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if cfg!(target_arch = "wasm32") {
+            store_crash(info.to_string());
+        } else if let Ok(mut last) = LAST_PANIC.lock() {
+            *last = Some(info.to_string());
+        }
+        default_hook(info);
+    }));
+}
+
+/// Keep the state for the report of a panic that cannot be caught. It is only taken every few
+/// seconds, because turning it into JSON takes a while.
+#[cfg(target_arch = "wasm32")]
+pub fn remember_state(now: f32, state: &impl Serialize) {
+    let Ok(mut remembered) = STATE.try_lock() else {
+        return;
+    };
+    if remembered
+        .as_ref()
+        .is_none_or(|(time, _)| now - time > STATE_INTERVAL)
+    {
+        let value = serde_json::to_value(state).unwrap_or_else(|e| Value::String(e.to_string()));
+        *remembered = Some((now, value));
+    }
+}
+
+/// Write the report of the panic with the last remembered state, before the app aborts.
+fn store_crash(error: String) {
+    let state = STATE
+        .try_lock()
+        .ok()
+        .and_then(|remembered| remembered.as_ref().map(|(_, state)| state.clone()))
+        .unwrap_or(Value::Null);
+    let result = ErrorReport::build(error, &state, true)
+        .json()
+        .and_then(|json| prefs::store(CRASH_FILE, &json));
+    if let Err(e) = result {
+        log(format!("Could not store the crash report: {e}"));
+    }
+}
+
+/// Run the function and return the panic message if it panics.
+pub fn catch<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|_| {
+        LAST_PANIC
+            .lock()
+            .ok()
+            .and_then(|mut last| last.take())
+            .unwrap_or_else(|| "Unknown error".to_owned())
+    })
+}
+
+/// Everything needed to understand an error.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorReport {
+    /// The error to show to the user.
+    #[serde(skip)]
+    pub error: String,
+    /// The error in the report, with player names removed like in the state.
+    #[serde(rename = "error")]
+    reported_error: String,
+    version: String,
+    platform: String,
+    log: Vec<String>,
+    /// State of the app, usually with player names removed.
+    state: Value,
+}
+
+impl ErrorReport {
+    pub fn new(error: String, state: &impl Serialize) -> Self {
+        log(format!("Error: {error}"));
+        Self::build(error, state, true)
+    }
+
+    /// A report without an error, for when the user thinks something is wrong. The state
+    /// includes the history of the game, so that the report can be reproduced.
+    pub fn debug_info(state: &impl Serialize, anonymize: bool) -> Self {
+        Self::build(
+            "No error, requested by the user".to_owned(),
+            state,
+            anonymize,
+        )
+    }
+
+    fn build(error: String, state: &impl Serialize, anonymize: bool) -> Self {
+        let mut state =
+            serde_json::to_value(state).unwrap_or_else(|e| Value::String(e.to_string()));
+        hide_own_clue(&mut state);
+        let mut log: Vec<String> = LOG
+            .lock()
+            .map(|log| log.iter().cloned().collect())
+            .unwrap_or_default();
+        let mut reported_error = error.clone();
+        if anonymize {
+            let mut names = Vec::new();
+            player_names(&state, &mut names);
+            names.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+            redact_state(&mut state, &names);
+            redact(&mut reported_error, &names);
+            for line in &mut log {
+                redact(line, &names);
+            }
+        }
+
+        Self {
+            error,
+            reported_error,
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            platform: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            log,
+            state,
+        }
+    }
+
+    /// The report of a panic that aborted the app last time, if there was one. It is only
+    /// offered once.
+    pub fn from_last_crash() -> Option<Self> {
+        let json = prefs::load(CRASH_FILE).filter(|json| !json.is_empty())?;
+        if let Err(e) = prefs::store(CRASH_FILE, "") {
+            log(format!("Could not remove the crash report: {e}"));
+        }
+        let mut report: Self = serde_json::from_str(&json)
+            .map_err(|e| log(format!("Could not read the crash report: {e}")))
+            .ok()?;
+        report.error = report.reported_error.clone();
+        Some(report)
+    }
+
+    pub fn json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+
+    /// Save the report as a JSON file.
+    pub fn export(&self) -> Result<String, String> {
+        export::save("cryptid-finder-report.json", self.json()?.as_bytes())
+    }
+}
+
+/// Buttons to copy or save a report of the state, for bug reports about wrong deductions.
+pub fn debug_gui(ui: &mut egui::Ui, state: &impl Serialize) {
+    let anonymize_id = egui::Id::new("debug-info-anonymize");
+    let notice_id = egui::Id::new("debug-info-notice");
+    let mut anonymize = ui.data().get_temp(anonymize_id).unwrap_or(true);
+    let mut notice: Option<String> = ui.data().get_temp(notice_id).unwrap_or_default();
+
+    ui.add(
+        egui::Label::new(tr(
+            "If a deduction looks wrong, attach this to a bug report. It contains the map and \
+            every answer of the game.",
+        ))
+        .wrap(true),
+    );
+    ui.checkbox(&mut anonymize, tr("Replace player names"));
+    ui.horizontal(|ui| {
+        if ui.button(tr("Copy debug info")).clicked() {
+            let json = ErrorReport::debug_info(state, anonymize).json();
+            notice = Some(match json {
+                Ok(json) => {
+                    ui.output().copied_text = json;
+                    tr("Copied to the clipboard").to_owned()
+                }
+                Err(e) => trf("Export failed: {}", &[&e]),
+            });
+        }
+        if ui.button(tr("Save debug info")).clicked() {
+            let result = ErrorReport::debug_info(state, anonymize).export();
+            notice = Some(result.unwrap_or_else(|e| trf("Export failed: {}", &[&e])));
+        }
+    });
+    if let Some(notice) = &notice {
+        ui.label(notice);
+    }
+
+    ui.data().insert_temp(anonymize_id, anonymize);
+    ui.data().insert_temp(notice_id, notice);
+}
+
+/// Keys of everything in the state that tells the clue of a player, by the id of the player.
+const OWN_CLUE: &[&str] = &[
+    "clues",
+    "known_clues",
+    "deduced_clues",
+    "with_inverted",
+    "auto_answers",
+    "taken_back",
+];
+
+/// Remove the clue of the user from states where they hide it, for playing on a shared screen.
+/// The answers the app filled in from the clue are removed too, from the tiles and the history.
+fn hide_own_clue(value: &mut Value) {
+    match value {
+        Value::Array(values) => values.iter_mut().for_each(hide_own_clue),
+        Value::Object(map) => {
+            if map.get("hide_own") == Some(&Value::Bool(true)) {
+                if let Some(user) = map.get("user").map(Value::to_string) {
+                    let filled_in = map
+                        .get("auto_answers")
+                        .and_then(|a| a.get(&user))
+                        .and_then(Value::as_array)
+                        .cloned()
+                        .unwrap_or_default();
+                    for key in OWN_CLUE {
+                        if let Some(Value::Object(by_player)) = map.get_mut(*key) {
+                            by_player.remove(&user);
+                        }
+                    }
+                    let tiles = map
+                        .get_mut("map")
+                        .and_then(|m| m.get_mut("tiles"))
+                        .and_then(Value::as_array_mut);
+                    for tile in tiles.into_iter().flatten() {
+                        if tile.get("position").is_some_and(|p| filled_in.contains(p)) {
+                            if let Some(Value::Object(answers)) = tile.get_mut("answers") {
+                                answers.remove(&user);
+                            }
+                        }
+                    }
+                    if let Some(Value::Array(history)) = map.get_mut("history") {
+                        history.retain(|event| {
+                            event.get("player").map(Value::to_string) != Some(user.clone())
+                                || !event.get("position").is_some_and(|p| filled_in.contains(p))
+                        });
+                    }
+                }
+            }
+            map.values_mut().for_each(hide_own_clue);
+        }
+        _ => (),
+    }
+}
+
+/// Keys of texts in the state that may mention players, like hints and warnings.
+const FREE_TEXTS: &[&str] = &[
+    "text",
+    "explanation",
+    "rule_warning",
+    "log",
+    "error",
+    "notice",
+    "export_notice",
+];
+
+/// Returns true for the object of a player, which has the name to replace.
+fn is_player(map: &serde_json::Map<String, Value>) -> bool {
+    ["id", "name", "color"]
+        .iter()
+        .all(|key| map.contains_key(*key))
+}
+
+/// Player names in the state, each with a numbered placeholder. The same name always gets
+/// the same placeholder, so that the players can still be told apart.
+fn player_names(value: &Value, names: &mut Vec<(String, String)>) {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                player_names(value, names);
+            }
+        }
+        Value::Object(map) => {
+            if let Some(Value::String(name)) = map.get("name").filter(|_| is_player(map)) {
+                if !name.is_empty() && names.iter().all(|(n, _)| n != name) {
+                    let placeholder = format!("Player {}", names.len() + 1);
+                    names.push((name.clone(), placeholder));
+                }
+            }
+            for value in map.values() {
+                player_names(value, names);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Replace the names where they appear as whole words in the text, so that a player called
+/// "Red" keeps "Redwood" intact. Longer names go first, so that a name that contains another
+/// one is replaced as a whole.
+fn redact(text: &mut String, names: &[(String, String)]) {
+    let is_word = |c: Option<char>| c.is_some_and(char::is_alphanumeric);
+    let mut redacted = String::with_capacity(text.len());
+    let mut i = 0;
+    'text: while let Some(c) = text[i..].chars().next() {
+        if !is_word(text[..i].chars().next_back()) {
+            for (name, placeholder) in names {
+                let end = i + name.len();
+                if text[i..].starts_with(name.as_str()) && !is_word(text[end..].chars().next()) {
+                    redacted.push_str(placeholder);
+                    i = end;
+                    continue 'text;
+                }
+            }
+        }
+        redacted.push(c);
+        i += c.len_utf8();
+    }
+    *text = redacted;
+}
+
+/// Replace the names of the players and the names in texts that may mention them. Other
+/// strings, like the variants of colors and animals, are kept as they are.
+fn redact_state(value: &mut Value, names: &[(String, String)]) {
+    match value {
+        Value::Array(values) => {
+            for value in values {
+                redact_state(value, names);
+            }
+        }
+        Value::Object(map) => {
+            let player = is_player(map);
+            for (key, value) in map.iter_mut() {
+                match value {
+                    Value::String(name) if player && key == "name" => redact(name, names),
+                    _ if FREE_TEXTS.contains(&key.as_str()) => redact_texts(value, names),
+                    _ => redact_state(value, names),
+                }
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Replace the names in every string of a free text, which may also be a list of texts.
+fn redact_texts(value: &mut Value, names: &[(String, String)]) {
+    match value {
+        Value::String(text) => redact(text, names),
+        Value::Array(values) => {
+            for value in values {
+                redact_texts(value, names);
+            }
+        }
+        _ => redact_state(value, names),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn redacted(state: Value) -> Value {
+        let report = ErrorReport::debug_info(&state, true);
+        report.state
+    }
+
+    #[test]
+    fn names_that_are_also_variants_only_change_the_player() {
+        let state = redacted(json!({
+            "players": [
+                {"id": 0, "name": "Red", "color": "Red"},
+                {"id": 1, "name": "Bear", "color": "Blue"},
+                {"id": 2, "name": "a", "color": "Green"},
+            ],
+            "tiles": [{"terrain": "Water", "animal": "Bear", "structure": {"color": "Red"}}],
+            "rule_warning": "Red asked Bear about Bearington.",
+        }));
+        assert_eq!(
+            state,
+            json!({
+                "players": [
+                    {"id": 0, "name": "Player 1", "color": "Red"},
+                    {"id": 1, "name": "Player 2", "color": "Blue"},
+                    {"id": 2, "name": "Player 3", "color": "Green"},
+                ],
+                "tiles": [{"terrain": "Water", "animal": "Bear", "structure": {"color": "Red"}}],
+                "rule_warning": "Player 1 asked Player 2 about Bearington.",
+            })
+        );
+    }
+
+    #[test]
+    fn a_hidden_clue_is_not_reported() {
+        let mut state = json!({
+            "user": 0,
+            "hide_own": true,
+            "clues": {"0": "Forest", "1": "Water"},
+            "auto_answers": {"0": [{"x": 1, "y": 0}]},
+            "map": {"tiles": [
+                {"position": {"x": 0, "y": 0}, "answers": {"0": "No", "1": "Yes"}},
+                {"position": {"x": 1, "y": 0}, "answers": {"0": "Yes"}},
+            ]},
+            "history": [
+                {"player": 0, "position": {"x": 0, "y": 0}, "answer": "No"},
+                {"player": 0, "position": {"x": 1, "y": 0}, "answer": "Yes"},
+            ],
+        });
+        hide_own_clue(&mut state);
+        assert_eq!(
+            state,
+            json!({
+                "user": 0,
+                "hide_own": true,
+                "clues": {"1": "Water"},
+                "auto_answers": {},
+                "map": {"tiles": [
+                    {"position": {"x": 0, "y": 0}, "answers": {"0": "No", "1": "Yes"}},
+                    {"position": {"x": 1, "y": 0}, "answers": {}},
+                ]},
+                "history": [
+                    {"player": 0, "position": {"x": 0, "y": 0}, "answer": "No"},
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn longer_names_are_replaced_as_a_whole() {
+        let names = [
+            ("Anna Lena".to_owned(), "Player 2".to_owned()),
+            ("Anna".to_owned(), "Player 1".to_owned()),
+        ];
+        let mut text = "Anna Lena asked Anna, then Annabel.".to_owned();
+        redact(&mut text, &names);
+        assert_eq!(text, "Player 2 asked Player 1, then Annabel.");
+    }
+}
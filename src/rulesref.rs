@@ -0,0 +1,146 @@
+//! A reference of the clue types, to settle rules questions at the table. Every clue type
+//! comes with its distance under the house rules of the game and a small diagram of the tiles it
+//! allows.
+
+use hexx::{Hex, HexLayout, HexOrientation};
+use notan::{egui, math::Vec2};
+
+use crate::{
+    houserules::HouseRules,
+    i18n::{tr, trf},
+    model::{spaces, Animal, Clue, ClueKind, ClueTarget, StructureColor, StructureKind, Terrain},
+};
+
+/// One clue type in the reference.
+struct Entry {
+    name: String,
+    description: String,
+    example: Clue,
+    /// Tiles within this distance of a matching tile are allowed.
+    distance: u32,
+}
+
+fn entries(rules: &HouseRules) -> Vec<Entry> {
+    let within = |name: &str, description: &str, target: ClueTarget| {
+        let distance = rules.distance(target);
+        Entry {
+            name: tr(name).to_owned(),
+            description: trf(description, &[&spaces(distance)]),
+            example: ClueKind::within(target, rules).into(),
+            distance,
+        }
+    };
+    let mut entries = vec![
+        within(
+            "Near terrain",
+            "The cryptid is on the terrain or within {} of it.",
+            ClueTarget::Terrain(Terrain::Water),
+        ),
+        Entry {
+            name: tr("One of two terrains").to_owned(),
+            description: tr("The cryptid is on one of the two terrains.").to_owned(),
+            example: ClueKind::TwoTerrains(Terrain::Forest, Terrain::Swamp).into(),
+            distance: 0,
+        },
+        within(
+            "Near either animal",
+            "The cryptid is in the territory of any animal or within {} of it.",
+            ClueTarget::EitherAnimal,
+        ),
+        within(
+            "Near animal",
+            "The cryptid is in the territory of the animal or within {} of it.",
+            ClueTarget::Animal(Animal::Cougar),
+        ),
+        within(
+            "Near structure type",
+            "The cryptid is on a structure of the type, of any color, or within {} of it.",
+            ClueTarget::StructureKind(StructureKind::Stone),
+        ),
+        within(
+            "Near structure color",
+            "The cryptid is on a structure of the color, of any type, or within {} of it.",
+            ClueTarget::StructureColor(StructureColor::Blue),
+        ),
+    ];
+    for &clue in &rules.custom_clues {
+        entries.push(Entry {
+            name: tr("Custom clue").to_owned(),
+            description: trf(
+                "Loaded from a clue file, within {}.",
+                &[&spaces(clue.distance)],
+            ),
+            example: ClueKind::Custom(clue).into(),
+            distance: clue.distance,
+        });
+    }
+    entries
+}
+
+pub fn gui(ui: &mut egui::Ui, rules: &HouseRules) {
+    let id = egui::Id::new("rules-reference-search");
+    let mut search: String = ui.data().get_temp(id).unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.label(tr("Search"));
+        ui.text_edit_singleline(&mut search);
+    });
+
+    let query = search.trim().to_lowercase();
+    for entry in entries(rules) {
+        let text = format!("{} {} {}", entry.name, entry.description, entry.example);
+        if !text.to_lowercase().contains(&query) {
+            continue;
+        }
+        ui.separator();
+        ui.label(egui::RichText::new(&entry.name).strong());
+        ui.add(egui::Label::new(&entry.description).wrap(true));
+        ui.horizontal(|ui| {
+            diagram(ui, entry.distance);
+            ui.add(egui::Label::new(trf("For example: {}", &[&entry.example])).wrap(true));
+        });
+    }
+    ui.separator();
+    ui.add(
+        egui::Label::new(tr(
+            "An inverted clue says the opposite: the cryptid is not where the clue allows it.",
+        ))
+        .wrap(true),
+    );
+
+    ui.data().insert_temp(id, search);
+}
+
+/// A tile that matches the clue in the middle and the tiles around it. Tiles where the cryptid
+/// may be are filled.
+fn diagram(ui: &mut egui::Ui, distance: u32) {
+    let rings = distance + 1;
+    let size = 6.0;
+    // Rows of flat hexagons are sqrt(3) tiles apart, a bit more than the columns.
+    let extent = (rings as f32 * 3f32.sqrt() + 1.0) * size * 2.0;
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(extent, extent), egui::Sense::hover());
+    let layout = HexLayout {
+        orientation: HexOrientation::flat(),
+        origin: Vec2::new(rect.center().x, rect.center().y),
+        hex_size: Vec2::splat(size),
+    };
+
+    let visuals = ui.visuals();
+    let stroke = egui::Stroke::new(1.0, visuals.text_color());
+    let allowed = visuals.selection.bg_fill;
+    let target = visuals.warn_fg_color;
+    for hex in Hex::ZERO.range(rings) {
+        let points = layout
+            .hex_corners(hex)
+            .map(|c| egui::pos2(c.x, c.y))
+            .to_vec();
+        let fill = if hex == Hex::ZERO {
+            target
+        } else if hex.unsigned_distance_to(Hex::ZERO) <= distance {
+            allowed
+        } else {
+            egui::Color32::TRANSPARENT
+        };
+        ui.painter()
+            .add(egui::Shape::convex_polygon(points, fill, stroke));
+    }
+}
@@ -0,0 +1,185 @@
+//! Puzzles with a fixed map, clues and answers, that can be played without the physical game.
+
+use std::{collections::HashMap, fmt};
+
+use hexx::Hex;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    i18n::{tr, trf},
+    model::{
+        parse_coordinate, Answer, Clue, Map, PieceChoice, PlayerID, PlayerList, Structure, Tile,
+        DEFAULT_MAP_COLUMNS,
+    },
+};
+
+/// A scenario as written in a scenario file. Positions are written like "F4".
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub pieces: [PieceChoice; 6],
+    #[serde(default)]
+    pub structures: Vec<PlacedStructure>,
+    /// Players in turn order. The first player is the user.
+    pub players: Vec<ScenarioPlayer>,
+    /// Answers that were given before the scenario starts.
+    #[serde(default)]
+    pub answers: Vec<PlacedAnswer>,
+    pub objective: Option<Objective>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlacedStructure {
+    pub at: String,
+    #[serde(flatten)]
+    pub structure: Structure,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScenarioPlayer {
+    pub name: String,
+    pub clue: Clue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlacedAnswer {
+    pub player: String,
+    pub at: String,
+    pub answer: Answer,
+}
+
+/// What the user has to achieve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Objective {
+    /// Maximum number of questions the user may ask before finding the cryptid.
+    /// Failed searches count as questions.
+    pub questions: usize,
+}
+
+impl fmt::Display for Objective {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self.questions {
+            1 => tr("Find the cryptid with at most 1 question.").to_owned(),
+            n => trf("Find the cryptid with at most {} questions.", &[&n]),
+        };
+        write!(f, "{text}")
+    }
+}
+
+/// Everything needed to play a scenario.
+#[derive(Debug, Clone, Serialize)]
+pub struct Setup {
+    pub map: Map,
+    pub players: PlayerList,
+    pub clues: HashMap<PlayerID, Clue>,
+    pub cryptid: Hex,
+}
+
+impl Scenario {
+    /// Read a scenario file and check that it can be played.
+    pub fn parse(json: &str) -> Result<Self, String> {
+        let scenario: Self = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        scenario.build()?;
+        Ok(scenario)
+    }
+
+    /// The scenarios that come with the app.
+    pub fn examples() -> Vec<Self> {
+        [
+            include_str!("../assets/scenarios/first-steps.json"),
+            include_str!("../assets/scenarios/crowded-table.json"),
+            include_str!("../assets/scenarios/the-last-question.json"),
+        ]
+        .into_iter()
+        .map(|json| Self::parse(json).expect("example scenarios are valid"))
+        .collect()
+    }
+
+    /// Build the map and players of the scenario.
+    pub fn build(&self) -> Result<Setup, String> {
+        if !self.pieces.iter().map(|choice| choice.piece).all_unique() {
+            return Err("Every piece must be used once".to_owned());
+        }
+        if !(2..=5).contains(&self.players.len()) {
+            return Err("A scenario needs 2 to 5 players".to_owned());
+        }
+        if !self.players.iter().map(|p| &p.name).all_unique() {
+            return Err("Every player needs a different name".to_owned());
+        }
+
+        let mut map = Map::from_pieces(&self.pieces, DEFAULT_MAP_COLUMNS)?;
+        for placed in &self.structures {
+            let tile = tile_at(&mut map, &placed.at)?;
+            tile.structure = Some(placed.structure);
+        }
+
+        let mut players = PlayerList::default();
+        let mut clues = HashMap::new();
+        let mut ids = HashMap::new();
+        for player in &self.players {
+            let id = players.push_named(&player.name);
+            clues.insert(id, player.clue);
+            ids.insert(player.name.as_str(), id);
+        }
+
+        // Every tile has an answer for every player, like in the other states.
+        for tile in &mut map.tiles {
+            for player in players.iter() {
+                tile.answers.insert(player.id, Answer::Unknown);
+            }
+        }
+        for placed in &self.answers {
+            let &player = ids
+                .get(placed.player.as_str())
+                .ok_or_else(|| format!("Unknown player {}", placed.player))?;
+            let tile = tile_at(&mut map, &placed.at)?;
+            tile.answers.insert(player, placed.answer);
+
+            let position = tile.position;
+            let applies = map.clue_applies(clues[&player], position);
+            let truthful = match placed.answer {
+                Answer::Unknown => true,
+                Answer::Yes => applies,
+                Answer::No => !applies,
+            };
+            if !truthful {
+                return Err(format!(
+                    "The answer of {} on {} contradicts their clue",
+                    placed.player, placed.at
+                ));
+            }
+        }
+
+        let cryptid = map
+            .tiles
+            .iter()
+            .map(|t| t.position)
+            .filter(|&t| clues.values().all(|&clue| map.clue_applies(clue, t)))
+            .collect_vec();
+        let cryptid = match cryptid.as_slice() {
+            [cryptid] => *cryptid,
+            tiles => {
+                return Err(format!(
+                    "The clues must match exactly one tile, but they match {}",
+                    tiles.len()
+                ))
+            }
+        };
+
+        Ok(Setup {
+            map,
+            players,
+            clues,
+            cryptid,
+        })
+    }
+}
+
+fn tile_at<'a>(map: &'a mut Map, at: &str) -> Result<&'a mut Tile, String> {
+    parse_coordinate(at)
+        .and_then(|hex| map.get_mut(hex))
+        .ok_or_else(|| format!("{at} is not a position on the map"))
+}
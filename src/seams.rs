@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use hexx::{Hex, HexLayout};
+use itertools::{Itertools, MinMaxResult};
+use notan::{
+    draw::{Draw, DrawShapes},
+    egui::{self, Align2, Color32, FontId, LayerId},
+    math::Vec2,
+    prelude::Color,
+};
+
+use crate::{
+    i18n::tr,
+    model::{piece_slot, Tile},
+};
+
+/// Overlay that shows where the physical pieces of the board meet, and how large they are.
+/// Helps with aligning a photo of the table against the map on the screen.
+#[derive(Debug, Clone, Copy)]
+pub struct Seams {
+    pub enabled: bool,
+    pub opacity: f32,
+    /// Width of one physical tile in centimeters, from one flat side to the other. The size
+    /// of the pieces is measured in the same unit.
+    pub tile_width: f32,
+}
+
+impl Default for Seams {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            opacity: 0.8,
+            tile_width: 4.5,
+        }
+    }
+}
+
+impl Seams {
+    pub fn gui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, tr("Show piece seams"));
+        if self.enabled {
+            ui.add(egui::Slider::new(&mut self.opacity, 0.0..=1.0).text(tr("Seam opacity")));
+            ui.horizontal(|ui| {
+                ui.label(tr("Width of a tile"));
+                ui.add(
+                    egui::DragValue::new(&mut self.tile_width)
+                        .clamp_range(0.5..=20.0)
+                        .speed(0.05)
+                        .suffix(" cm"),
+                );
+            })
+            .response
+            .on_hover_text(tr(
+                "Measure a tile of your board, so that the pieces get their real size",
+            ));
+        }
+    }
+
+    /// Draw lines along every edge between two pieces and around the board.
+    pub fn draw(&self, draw: &mut Draw, tiles: &[Tile], layout: &HexLayout, color: Color) {
+        if !self.enabled {
+            return;
+        }
+
+        let slots: HashMap<Hex, [i32; 2]> = tiles
+            .iter()
+            .map(|t| (t.position, piece_slot(t.position)))
+            .collect();
+        let width = layout.hex_size.x.abs() * 0.08;
+
+        for (&hex, &own_slot) in &slots {
+            for neighbor in hex.all_neighbors() {
+                let is_seam = match slots.get(&neighbor) {
+                    // Both tiles would draw the same line, only draw it once.
+                    Some(&other_slot) => {
+                        other_slot != own_slot && hex.to_array() < neighbor.to_array()
+                    }
+                    None => true,
+                };
+                if !is_seam {
+                    continue;
+                }
+
+                // The shared edge is perpendicular to the line between both centers.
+                let center = layout.hex_to_world_pos(hex);
+                let towards = layout.hex_to_world_pos(neighbor) - center;
+                let middle = center + towards * 0.5;
+                let along = towards.perp().normalize() * layout.hex_size.x.abs() * 0.5;
+                let a = middle - along;
+                let b = middle + along;
+
+                draw.line((a.x, a.y), (b.x, b.y))
+                    .width(width)
+                    .color(color)
+                    .alpha(self.opacity);
+            }
+        }
+    }
+
+    /// Label every piece with its width and height in centimeters, scaled from the width of
+    /// a tile.
+    pub fn labels(&self, ctx: &egui::Context, tiles: &[Tile], layout: &HexLayout, color: Color) {
+        if !self.enabled {
+            return;
+        }
+
+        let painter = ctx.layer_painter(LayerId::background());
+        let [r, g, b, _] = color.rgba_u8();
+        let color = Color32::from_rgba_unmultiplied(r, g, b, (self.opacity * 255.0) as u8);
+        let font = FontId::proportional(layout.hex_size.x.abs() * 0.4);
+
+        // The flat sides of the hexagons point up and down in the layout.
+        let tile_width = extent(&layout.hex_corners(Hex::ZERO), |c| c.y);
+        let scale = self.tile_width / tile_width;
+
+        let pieces = tiles.iter().into_group_map_by(|t| piece_slot(t.position));
+        for tiles in pieces.values() {
+            let center = tiles
+                .iter()
+                .map(|t| layout.hex_to_world_pos(t.position))
+                .sum::<Vec2>()
+                / tiles.len() as f32;
+            let corners = tiles
+                .iter()
+                .flat_map(|t| layout.hex_corners(t.position))
+                .collect_vec();
+            let width = extent(&corners, |c| c.x) * scale;
+            let height = extent(&corners, |c| c.y) * scale;
+
+            painter.text(
+                egui::pos2(center.x, center.y),
+                Align2::CENTER_CENTER,
+                format!("{width:.1} × {height:.1} cm"),
+                font.clone(),
+                color,
+            );
+        }
+    }
+}
+
+/// Distance between the smallest and the largest coordinate of the points.
+fn extent(points: &[Vec2], coordinate: impl Fn(&Vec2) -> f32) -> f32 {
+    match points.iter().map(coordinate).minmax_by(f32::total_cmp) {
+        MinMaxResult::MinMax(min, max) => max - min,
+        MinMaxResult::NoElements | MinMaxResult::OneElement(_) => 0.0,
+    }
+}
@@ -0,0 +1,23 @@
+//! A reduced panel for players who are new to Cryptid. It hides cheating, inverted clues and
+//! the analysis, and keeps building the map, entering answers and seeing where the cryptid
+//! can be.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use notan::egui;
+
+use crate::i18n::tr;
+
+static SIMPLE_MODE: AtomicBool = AtomicBool::new(false);
+
+/// True if only the basics should be shown.
+pub fn enabled() -> bool {
+    SIMPLE_MODE.load(Ordering::Relaxed)
+}
+
+pub fn gui(ui: &mut egui::Ui) {
+    let mut simple = enabled();
+    ui.checkbox(&mut simple, tr("Simple mode"))
+        .on_hover_text(tr("Show only the basics, for players new to Cryptid"));
+    SIMPLE_MODE.store(simple, Ordering::Relaxed);
+}
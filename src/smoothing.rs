@@ -0,0 +1,74 @@
+//! Smooth edges of the hexagons. The window is multisampled where the graphics driver supports
+//! it. On top of that the map can be drawn at a higher resolution and scaled down to the window,
+//! which keeps edges from shimmering while zooming, at the cost of speed.
+
+use notan::{egui, math::Vec2, prelude::*};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::i18n::{tr, tr_name};
+
+/// Samples per pixel of the window.
+pub const MULTISAMPLING: u8 = 4;
+
+/// How much larger the map is drawn with [EdgeQuality::High].
+pub const SUPERSAMPLING: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumIter, Display)]
+pub enum EdgeQuality {
+    /// Only the multisampling of the window.
+    #[default]
+    Normal,
+    /// The map is supersampled.
+    High,
+}
+
+#[derive(Debug, Default)]
+pub struct Smoothing {
+    pub quality: EdgeQuality,
+    /// The map is drawn into this texture with [EdgeQuality::High].
+    texture: Option<RenderTexture>,
+}
+
+impl Smoothing {
+    pub fn gui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(tr("Edge quality"));
+            egui::ComboBox::new("edge-quality", "")
+                .selected_text(tr_name(self.quality))
+                .show_ui(ui, |ui| {
+                    for quality in EdgeQuality::iter() {
+                        ui.selectable_value(&mut self.quality, quality, tr_name(quality));
+                    }
+                })
+                .response
+                .on_hover_text(tr("High quality is slower on large screens"));
+        });
+    }
+
+    /// Create a texture for a window of the size, if the map is supersampled. Textures can't be
+    /// created while they are drawn into, so call this before [Smoothing::texture].
+    pub fn prepare(&mut self, gfx: &mut Graphics, window: Vec2) {
+        if self.quality == EdgeQuality::Normal {
+            self.texture = None;
+            return;
+        }
+        let size = (window * SUPERSAMPLING).ceil();
+        if self
+            .texture
+            .as_ref()
+            .is_some_and(|t| Vec2::from(t.size()) == size)
+        {
+            return;
+        }
+        self.texture = gfx
+            .create_render_texture(size.x as i32, size.y as i32)
+            .with_filter(TextureFilter::Linear, TextureFilter::Linear)
+            .build()
+            .ok();
+    }
+
+    /// Texture to draw the map into, or None to draw it into the window directly.
+    pub fn texture(&self) -> Option<&RenderTexture> {
+        self.texture.as_ref()
+    }
+}
@@ -1,35 +1,68 @@
 use std::collections::HashSet;
 
-use hexx::{Hex, OffsetHexMode};
+use hexx::Hex;
 use itertools::Itertools;
 use notan::egui::{self, color_picker, Align, Layout};
+use serde::Serialize;
 use strum::IntoEnumIterator;
 
 use crate::{
-    model::{Piece, PieceChoice, PlayerColor, PlayerList, Tile},
-    LAYOUT_SPACE,
+    houserules::HouseRules,
+    i18n::{tr, tr_name, trf},
+    model::{
+        coordinate, piece_slot, Animal, Map, Piece, PieceChoice, PlayerColor, PlayerList,
+        Structure, Tile, DEFAULT_MAP_COLUMNS, DEFAULT_MAP_ROWS,
+    },
+    notation::Board,
+    report, LAYOUT_SPACE,
 };
 
-use super::Common;
+use super::{
+    widgets::board_code, ChoosingScenario, Common, EditingPiece, SubState, Transition, Verifying,
+};
+
+/// Largest number of pieces in either direction.
+const MAX_MAP_SIZE: usize = 4;
 
 /// A sub state for functionality for building a map.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct BuildingMap {
-    selected_pieces: [PieceChoice; 6],
+    /// Pieces of the map, row by row.
+    selected_pieces: Vec<PieceChoice>,
+    /// Number of pieces next to each other.
+    columns: usize,
+    /// Number of pieces below each other.
+    rows: usize,
     tiles: Vec<Tile>,
+    /// House rules of the game that is played on the map.
+    rules: HouseRules,
+    /// Structures from a board code, placed instead of the default ones.
+    pub structures: Vec<(Hex, Structure)>,
     pub players: PlayerList,
+    /// True if the user wants to play a puzzle instead of setting up a game.
+    pub puzzles: bool,
+    /// True if the user wants to create custom pieces.
+    pub editing_pieces: bool,
+    /// Pieces the user created in the piece editor.
+    pub custom_pieces: Vec<Piece>,
+    /// Animals the user added for custom pieces.
+    pub custom_animals: Vec<Animal>,
 }
 
 impl Default for BuildingMap {
     fn default() -> Self {
         let mut s = Self {
-            selected_pieces: Piece::iter()
-                .map(Into::into)
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap(),
+            selected_pieces: Piece::iter().map(Into::into).collect(),
+            columns: DEFAULT_MAP_COLUMNS,
+            rows: DEFAULT_MAP_ROWS,
             tiles: Vec::new(),
+            rules: HouseRules::default(),
+            structures: Vec::new(),
             players: PlayerList::default(),
+            puzzles: false,
+            editing_pieces: false,
+            custom_pieces: Vec::new(),
+            custom_animals: Vec::new(),
         };
 
         s.rebuild_tiles();
@@ -37,6 +70,34 @@ impl Default for BuildingMap {
     }
 }
 
+impl From<&EditingPiece> for BuildingMap {
+    fn from(value: &EditingPiece) -> Self {
+        let mut s = value.building_map().clone();
+        s.editing_pieces = false;
+        s
+    }
+}
+
+impl From<&Verifying> for BuildingMap {
+    fn from(value: &Verifying) -> Self {
+        Self {
+            players: value.players().clone(),
+            rules: value.house_rules().clone(),
+            custom_pieces: value.custom_pieces().to_vec(),
+            custom_animals: value
+                .custom_pieces()
+                .iter()
+                .flat_map(|p| match p {
+                    Piece::Custom(p) => p.animals.clone(),
+                    _ => Vec::new(),
+                })
+                .unique()
+                .collect(),
+            ..Self::default()
+        }
+    }
+}
+
 impl Common for BuildingMap {
     fn tiles(&self) -> &[Tile] {
         &self.tiles
@@ -44,38 +105,54 @@ impl Common for BuildingMap {
     fn tiles_mut(&mut self) -> &mut [Tile] {
         &mut self.tiles
     }
+    fn house_rules(&self) -> &HouseRules {
+        &self.rules
+    }
+    fn house_rules_mut(&mut self) -> Option<&mut HouseRules> {
+        Some(&mut self.rules)
+    }
+    fn custom_pieces(&self) -> &[Piece] {
+        &self.custom_pieces
+    }
 
-    fn gui(&mut self, ui: &mut egui::Ui) -> bool {
-        let selected_pieces_before = self.selected_pieces;
+    fn gui(&mut self, ui: &mut egui::Ui) -> Option<Transition> {
+        let size_before = (self.columns, self.rows);
         let mut map_ready = false;
         let mut players_ready = false;
 
-        ui.label(
+        ui.label(tr(
             "Helper/cheat tool for the board game Cryptid. Check GitHub for more information.",
-        );
+        ));
         ui.add_space(LAYOUT_SPACE);
 
-        ui.heading("Map");
+        ui.heading(tr("Map"));
+        ui.horizontal(|ui| {
+            ui.label(tr("Size"));
+            ui.add(egui::DragValue::new(&mut self.columns).clamp_range(1..=MAX_MAP_SIZE));
+            ui.label("×");
+            ui.add(egui::DragValue::new(&mut self.rows).clamp_range(1..=MAX_MAP_SIZE));
+            if ui.button(tr("Reset")).clicked() {
+                self.columns = DEFAULT_MAP_COLUMNS;
+                self.rows = DEFAULT_MAP_ROWS;
+            }
+        });
+        if size_before != (self.columns, self.rows) {
+            self.resize();
+        }
+
+        ui.add(
+            egui::Label::new(tr(
+                "Drag pieces on the map to arrange them. Click a piece to rotate it. \
+                Shift-click a piece to swap in a piece that is not on the map.",
+            ))
+            .wrap(true),
+        );
         ui.columns(1, |ui| {
             let ui = &mut ui[0];
             egui::Grid::new("map-setup-grid").show(ui, |ui| {
-                for i in 0..6 {
-                    egui::ComboBox::new(format!("map-setup-choice-{i}"), "")
-                        .selected_text(format!("{}", self.selected_pieces[i]))
-                        .show_ui(ui, |ui| {
-                            for piece in Piece::iter() {
-                                for rotated in [false, true] {
-                                    let choice = PieceChoice { piece, rotated };
-                                    ui.selectable_value(
-                                        &mut self.selected_pieces[i],
-                                        choice,
-                                        format!("{choice}"),
-                                    );
-                                }
-                            }
-                        });
-
-                    if i % 2 > 0 {
+                for (i, choice) in self.selected_pieces.iter().enumerate() {
+                    ui.label(format!("{choice}"));
+                    if i % self.columns == self.columns - 1 {
                         ui.end_row();
                     }
                 }
@@ -85,11 +162,21 @@ impl Common for BuildingMap {
         if are_selected_pieces_valid(&self.selected_pieces) {
             map_ready = true;
         } else {
-            ui.label("Select every piece once to continue");
+            ui.label(tr("Select a different piece for every slot to continue"));
+        }
+
+        let current = Board::new(&self.selected_pieces, self.columns, &[]);
+        let pieces = Piece::all(&self.custom_pieces);
+        board_code(ui, Ok(current), &pieces, |board| self.load_board(board));
+        if !self.structures.is_empty() {
+            ui.label(trf(
+                "{} structures from the board code will be placed.",
+                &[&self.structures.len()],
+            ));
         }
 
         ui.add_space(LAYOUT_SPACE);
-        ui.heading("Players");
+        ui.heading(tr("Players"));
 
         let mut remove = None;
         for player in self.players.iter_mut() {
@@ -98,15 +185,24 @@ impl Common for BuildingMap {
 
                 let icon_color = player.color.into();
                 egui::ComboBox::new(format!("color-for-player-{:?}", player.id), "")
-                    .selected_text(format!("{}", player.color))
+                    .selected_text(tr_name(player.color))
                     .icon(move |ui, rect, _visuals, _is_open, _above_or_below| {
                         color_picker::show_color_at(ui.painter(), icon_color, rect);
                     })
                     .show_ui(ui, |ui| {
                         for option in PlayerColor::iter() {
-                            ui.selectable_value(&mut player.color, option, format!("{option}"));
+                            ui.selectable_value(&mut player.color, option, tr_name(option));
+                        }
+                        if !matches!(player.color, PlayerColor::Custom(_))
+                            && ui.selectable_label(false, tr("Custom")).clicked()
+                        {
+                            let [r, g, b, _] = egui::Color32::from(player.color).to_array();
+                            player.color = PlayerColor::Custom([r, g, b]);
                         }
                     });
+                if let PlayerColor::Custom(rgb) = &mut player.color {
+                    color_picker::color_edit_button_srgb(ui, rgb);
+                }
 
                 if ui.button("X").clicked() {
                     remove = Some(player.id);
@@ -119,13 +215,18 @@ impl Common for BuildingMap {
         }
 
         ui.horizontal(|ui| {
-            if self.players.len() < 5 && ui.button("Add").clicked() {
+            if self.players.len() < 5 && ui.button(tr("Add")).clicked() {
                 self.players.push_new();
             }
 
             let block = if self.players.len() < 3 || self.players.len() > 5 {
                 Some("Add 3 to 5 players to continue")
-            } else if !self.players.iter().map(|p| p.color).all_unique() {
+            } else if !self
+                .players
+                .iter()
+                .map(|p| egui::Color32::from(p.color))
+                .all_unique()
+            {
                 Some("Use colors only once to continue")
             } else if self.players.iter().any(|p| p.name.is_empty()) {
                 Some("Enter a name for every player to continue")
@@ -136,35 +237,103 @@ impl Common for BuildingMap {
             };
 
             if let Some(block) = block {
-                ui.label(block);
+                ui.label(tr(block));
             } else {
                 players_ready = true;
             }
         });
 
-        if selected_pieces_before != self.selected_pieces {
-            self.rebuild_tiles();
-        }
-
         let mut switch_states = false;
 
         if map_ready && players_ready {
             ui.add_space(LAYOUT_SPACE);
             ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
-                if ui.button("Ready").clicked() {
+                if ui.button(tr("Ready")).clicked() {
                     switch_states = true;
                 }
             });
         }
 
-        switch_states
+        ui.add_space(LAYOUT_SPACE);
+        ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
+            if ui
+                .button(tr("Puzzles"))
+                .on_hover_text(tr("Solve puzzles without the physical game"))
+                .clicked()
+            {
+                self.puzzles = true;
+                switch_states = true;
+            }
+            if ui
+                .button(tr("Edit pieces"))
+                .on_hover_text(tr("Create your own pieces for the map"))
+                .clicked()
+            {
+                self.editing_pieces = true;
+                switch_states = true;
+            }
+        });
+
+        switch_states.then_some(Transition::Next)
+    }
+
+    fn next(&self) -> Result<SubState, String> {
+        Ok(if self.puzzles {
+            SubState::ChoosingScenario(ChoosingScenario::default())
+        } else if self.editing_pieces {
+            SubState::EditingPiece(self.into())
+        } else {
+            SubState::PlacingStructures(self.into())
+        })
+    }
+
+    fn confirm_switch(&self) -> Option<String> {
+        if self.puzzles || self.editing_pieces {
+            return None;
+        }
+        Some(trf(
+            "The map has {} pieces and there are {} players. Both can't be changed after this.",
+            &[&self.selected_pieces.len(), &self.players.len()],
+        ))
     }
 
     fn highlights(&self) -> Vec<Hex> {
         Vec::new()
     }
 
-    fn click(&mut self, _hex: Hex) {}
+    fn click(&mut self, hex: Hex) {
+        if let Some(i) = self.slot(hex) {
+            self.selected_pieces[i].rotated ^= true;
+            self.rebuild_tiles();
+        }
+    }
+
+    fn modified_click(&mut self, hex: Hex) {
+        let Some(i) = self.slot(hex) else {
+            return;
+        };
+        // Take the next piece that is not on the map yet.
+        let all = Piece::all(&self.custom_pieces);
+        let current = all.iter().position(|&p| p == self.selected_pieces[i].piece);
+        let next = (1..all.len())
+            .map(|step| all[(current.unwrap_or(0) + step) % all.len()])
+            .find(|&piece| self.selected_pieces.iter().all(|c| c.piece != piece));
+        if let Some(piece) = next {
+            self.selected_pieces[i] = piece.into();
+            self.rebuild_tiles();
+        }
+    }
+
+    fn drags_pieces(&self) -> bool {
+        true
+    }
+
+    fn drag_piece(&mut self, from: Hex, to: Hex) {
+        if let (Some(from), Some(to)) = (self.slot(from), self.slot(to)) {
+            self.selected_pieces.swap(from, to);
+            self.rebuild_tiles();
+        }
+    }
 
     fn players(&self) -> &PlayerList {
         &self.players
@@ -172,33 +341,67 @@ impl Common for BuildingMap {
 }
 
 impl BuildingMap {
+    /// Add or remove slots after the user changed the size of the map.
+    /// New slots get pieces that are not used yet, as long as there are any.
+    fn resize(&mut self) {
+        let slots = self.columns * self.rows;
+        self.selected_pieces.truncate(slots);
+        while self.selected_pieces.len() < slots {
+            let unused = Piece::all(&self.custom_pieces)
+                .into_iter()
+                .find(|&piece| self.selected_pieces.iter().all(|c| c.piece != piece))
+                .unwrap_or(Piece::One);
+            self.selected_pieces.push(unused.into());
+        }
+        self.rebuild_tiles();
+    }
+
+    /// Set up the pieces of a board code. Its structures are kept for placing them later.
+    fn load_board(&mut self, board: Board) -> Result<(), String> {
+        if board.columns > MAX_MAP_SIZE || board.rows() > MAX_MAP_SIZE {
+            return Err(trf(
+                "The map can have at most {} pieces in each direction.",
+                &[&MAX_MAP_SIZE],
+            ));
+        }
+        let map = Map::from_pieces(&board.pieces, board.columns)?;
+        if let Some((hex, _)) = board
+            .structures
+            .iter()
+            .find(|(hex, _)| map.get(*hex).is_none())
+        {
+            return Err(trf("{} is not on the map.", &[&coordinate(*hex)]));
+        }
+
+        self.columns = board.columns;
+        self.rows = board.rows();
+        self.selected_pieces = board.pieces;
+        self.structures = board.structures;
+        self.tiles = map.tiles;
+        Ok(())
+    }
+
+    /// Index of the piece that contains the hex.
+    fn slot(&self, hex: Hex) -> Option<usize> {
+        let [col, row] = piece_slot(hex);
+        let col = usize::try_from(col).ok().filter(|&c| c < self.columns)?;
+        let row = usize::try_from(row).ok().filter(|&r| r < self.rows)?;
+        Some(row * self.columns + col)
+    }
+
     /// Update tiles after user changed something
     fn rebuild_tiles(&mut self) {
-        let offsets = [
-            Hex::ZERO,
-            Hex::from_offset_coordinates([6, 0], OffsetHexMode::OddColumns),
-            Hex::from_offset_coordinates([0, 3], OffsetHexMode::OddColumns),
-            Hex::from_offset_coordinates([6, 3], OffsetHexMode::OddColumns),
-            Hex::from_offset_coordinates([0, 6], OffsetHexMode::OddColumns),
-            Hex::from_offset_coordinates([6, 6], OffsetHexMode::OddColumns),
-        ];
-        self.tiles = offsets
-            .iter()
-            .zip(self.selected_pieces.iter())
-            .flat_map(|(&offset, piece)| {
-                let mut tiles = piece.piece.parse();
-                if piece.rotated {
-                    tiles.rotate();
-                }
-                tiles.translate(offset);
-                tiles.0
-            })
-            .collect();
+        // Structures of a board code don't fit a changed map.
+        self.structures.clear();
+        match Map::from_pieces(&self.selected_pieces, self.columns) {
+            Ok(map) => self.tiles = map.tiles,
+            Err(e) => report::toast(e),
+        }
     }
 }
 
-/// Returns true if six different [Piece]s were selected.
+/// Returns true if every slot has a different [Piece].
 fn are_selected_pieces_valid(pieces: &[PieceChoice]) -> bool {
-    let pieces: HashSet<Piece> = pieces.iter().map(|choice| choice.piece).collect();
-    pieces.len() == 6
+    let unique: HashSet<Piece> = pieces.iter().map(|choice| choice.piece).collect();
+    unique.len() == pieces.len()
 }
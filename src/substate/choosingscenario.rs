@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use hexx::Hex;
+use notan::egui::{self, Align, Label, Layout};
+use serde::Serialize;
+
+use crate::{
+    houserules::HouseRules,
+    i18n::{tr, trf},
+    model::{PlayerList, Tile},
+    scenario::{Scenario, Setup},
+    LAYOUT_SPACE,
+};
+
+use super::{Common, Difficulty, SubState, Transition};
+
+/// A sub state for picking a puzzle to play.
+#[derive(Debug, Serialize)]
+pub struct ChoosingScenario {
+    scenarios: Vec<Scenario>,
+    selected: usize,
+    /// The selected scenario, ready to be shown on the map.
+    preview: Setup,
+    /// Scenario file pasted by the user.
+    pasted: String,
+    /// Message for the user if the pasted scenario can't be used.
+    error: Option<String>,
+    /// Estimated difficulty of the scenarios, by index.
+    difficulties: HashMap<usize, Difficulty>,
+    /// The scenario to play, once the user chose one.
+    pub chosen: Option<Scenario>,
+}
+
+impl Default for ChoosingScenario {
+    fn default() -> Self {
+        let scenarios = Scenario::examples();
+        let preview = scenarios[0].build().expect("example scenarios are valid");
+        Self {
+            scenarios,
+            selected: 0,
+            preview,
+            pasted: String::new(),
+            error: None,
+            difficulties: HashMap::new(),
+            chosen: None,
+        }
+    }
+}
+
+impl Common for ChoosingScenario {
+    fn tiles(&self) -> &[Tile] {
+        &self.preview.map.tiles
+    }
+    fn tiles_mut(&mut self) -> &mut [Tile] {
+        &mut self.preview.map.tiles
+    }
+    fn house_rules(&self) -> &HouseRules {
+        &self.preview.map.rules
+    }
+
+    fn gui(&mut self, ui: &mut egui::Ui) -> Option<Transition> {
+        let selected_before = self.selected;
+        let mut transition = None;
+
+        ui.heading(tr("Puzzles"));
+        for (i, scenario) in self.scenarios.iter().enumerate() {
+            ui.selectable_value(&mut self.selected, i, &scenario.name);
+        }
+
+        let scenario = &self.scenarios[self.selected];
+        ui.add_space(LAYOUT_SPACE);
+        if !scenario.description.is_empty() {
+            ui.add(Label::new(&scenario.description).wrap(true));
+        }
+        if let Some(objective) = scenario.objective {
+            ui.add(Label::new(objective.to_string()).wrap(true));
+        }
+        if let Some(difficulty) = self.difficulties.get(&self.selected) {
+            ui.add(Label::new(trf("Difficulty: {}", &[difficulty])).wrap(true));
+        }
+
+        ui.add_space(LAYOUT_SPACE);
+        ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
+            if ui.button(tr("Play")).clicked() {
+                self.chosen = Some(self.scenarios[self.selected].clone());
+                transition = Some(Transition::Next);
+            }
+            if ui.button(tr("Back")).clicked() {
+                transition = Some(Transition::Back);
+            }
+        });
+
+        ui.add_space(LAYOUT_SPACE);
+        egui::CollapsingHeader::new(tr("Load scenario file")).show(ui, |ui| {
+            ui.add(Label::new(tr("Paste the contents of a scenario file.")).wrap(true));
+            ui.text_edit_multiline(&mut self.pasted);
+            if ui.button(tr("Load")).clicked() {
+                match Scenario::parse(&self.pasted) {
+                    Ok(scenario) => {
+                        self.scenarios.push(scenario);
+                        self.selected = self.scenarios.len() - 1;
+                        self.pasted.clear();
+                        self.error = None;
+                    }
+                    Err(e) => self.error = Some(trf("Can't load scenario: {}", &[&e])),
+                }
+            }
+            if let Some(error) = &self.error {
+                ui.add(Label::new(error).wrap(true));
+            }
+        });
+
+        if selected_before != self.selected {
+            self.preview = self.scenarios[self.selected]
+                .build()
+                .expect("scenarios are checked when they are loaded");
+        }
+
+        transition
+    }
+
+    fn next(&self) -> Result<SubState, String> {
+        match self.chosen {
+            Some(_) => Ok(SubState::Practice(self.into())),
+            None => Err("Choose a scenario first.".to_owned()),
+        }
+    }
+
+    fn prev(&self) -> Result<SubState, String> {
+        Ok(SubState::default())
+    }
+
+    fn highlights(&self) -> Vec<Hex> {
+        Vec::new()
+    }
+
+    fn click(&mut self, _hex: Hex) {}
+
+    fn players(&self) -> &PlayerList {
+        &self.preview.players
+    }
+
+    fn idle(&mut self) {
+        self.difficulties
+            .entry(self.selected)
+            .or_insert_with(|| Difficulty::of(&self.preview));
+    }
+}
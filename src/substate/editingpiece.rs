@@ -0,0 +1,242 @@
+use hexx::{Hex, OffsetHexMode};
+use itertools::Itertools;
+use notan::egui::{self, color_picker, Align, Label, Layout};
+use serde::Serialize;
+use strum::IntoEnumIterator;
+
+use crate::{
+    export,
+    houserules::HouseRules,
+    i18n::{tr, tr_name, trf},
+    model::{Animal, ParsedPiece, Piece, PlayerList, Terrain, Tile, PIECE_HEIGHT, PIECE_WIDTH},
+    LAYOUT_SPACE,
+};
+
+use super::{buildingmap::BuildingMap, Common, SubState, Transition};
+
+/// A sub state for drawing custom pieces.
+#[derive(Debug, Serialize)]
+pub struct EditingPiece {
+    piece: ParsedPiece,
+    name: String,
+    /// Result of the last save or export to show to the user.
+    notice: Option<String>,
+    /// True if the piece was changed since it was last saved.
+    unsaved: bool,
+    /// The map setup to return to.
+    building_map: BuildingMap,
+    /// Name, letter and color of an animal the user is adding.
+    new_animal: (String, String, [u8; 3]),
+    /// Result of adding the last animal.
+    animal_notice: Option<String>,
+}
+
+impl From<&BuildingMap> for EditingPiece {
+    fn from(value: &BuildingMap) -> Self {
+        Self {
+            piece: blank_piece(),
+            name: String::new(),
+            notice: None,
+            unsaved: false,
+            building_map: value.clone(),
+            new_animal: (String::new(), String::new(), [120, 80, 40]),
+            animal_notice: None,
+        }
+    }
+}
+
+impl Common for EditingPiece {
+    fn tiles(&self) -> &[Tile] {
+        &self.piece.0
+    }
+    fn tiles_mut(&mut self) -> &mut [Tile] {
+        &mut self.piece.0
+    }
+    fn house_rules(&self) -> &HouseRules {
+        self.building_map.house_rules()
+    }
+    fn house_rules_mut(&mut self) -> Option<&mut HouseRules> {
+        self.building_map.house_rules_mut()
+    }
+    fn custom_pieces(&self) -> &[Piece] {
+        self.building_map.custom_pieces()
+    }
+
+    fn gui(&mut self, ui: &mut egui::Ui) -> Option<Transition> {
+        let mut back = false;
+
+        ui.heading(tr("Piece editor"));
+        ui.add(
+            Label::new(tr(
+                "Click a tile to change its terrain. Shift-click it to add an animal territory.",
+            ))
+            .wrap(true),
+        );
+
+        ui.add_space(LAYOUT_SPACE);
+        ui.horizontal(|ui| {
+            ui.label(tr("Name"));
+            ui.text_edit_singleline(&mut self.name);
+        });
+        ui.horizontal(|ui| {
+            if ui.button(tr("Save")).clicked() {
+                self.notice = Some(self.save());
+            }
+            if ui.button(tr("Export")).clicked() {
+                let file_name = format!("piece-{}.txt", self.name.trim());
+                let result = export::save(&file_name, self.piece.definition().as_bytes());
+                self.notice = Some(result.unwrap_or_else(|e| trf("Export failed: {}", &[&e])));
+            }
+            if ui.button(tr("Clear")).clicked() {
+                self.piece = blank_piece();
+            }
+        });
+        if let Some(notice) = &self.notice {
+            ui.add(Label::new(notice).wrap(true));
+        }
+
+        ui.add_space(LAYOUT_SPACE);
+        self.gui_for_animals(ui);
+
+        ui.add_space(LAYOUT_SPACE);
+        ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
+            if ui.button(tr("Back")).clicked() {
+                back = true;
+            }
+        });
+
+        back.then_some(Transition::Back)
+    }
+
+    fn prev(&self) -> Result<SubState, String> {
+        Ok(SubState::BuildingMap(self.into()))
+    }
+
+    fn confirm_switch(&self) -> Option<String> {
+        self.unsaved
+            .then(|| tr("The piece is not saved and will be lost.").to_owned())
+    }
+
+    fn highlights(&self) -> Vec<Hex> {
+        Vec::new()
+    }
+
+    fn click(&mut self, hex: Hex) {
+        let Some(tile) = self.piece.0.iter_mut().find(|t| t.position == hex) else {
+            return;
+        };
+        let terrains = Terrain::iter().collect_vec();
+        let i = terrains
+            .iter()
+            .position(|&t| t == tile.terrain)
+            .unwrap_or(0);
+        tile.terrain = terrains[(i + 1) % terrains.len()];
+        self.unsaved = true;
+    }
+
+    fn modified_click(&mut self, hex: Hex) {
+        // Go through all animals, then no animal.
+        let animals = self.animals();
+        let Some(tile) = self.piece.0.iter_mut().find(|t| t.position == hex) else {
+            return;
+        };
+        let next = match tile.animal {
+            None => 0,
+            Some(animal) => animals
+                .iter()
+                .position(|&a| a == animal)
+                .map_or(0, |i| i + 1),
+        };
+        tile.animal = animals.get(next).copied();
+        self.unsaved = true;
+    }
+
+    fn players(&self) -> &PlayerList {
+        &self.building_map.players
+    }
+}
+
+impl EditingPiece {
+    /// The map setup to return to.
+    pub fn building_map(&self) -> &BuildingMap {
+        &self.building_map
+    }
+
+    /// The animals of the game and the ones the user added.
+    fn animals(&self) -> Vec<Animal> {
+        Animal::iter()
+            .chain(self.building_map.custom_animals.iter().copied())
+            .collect()
+    }
+
+    /// Animals that can be placed, and adding animals of fan maps.
+    fn gui_for_animals(&mut self, ui: &mut egui::Ui) {
+        ui.heading(tr("Animals"));
+        let animals = self.animals();
+        for &animal in &animals {
+            ui.label(trf("{}: {}", &[&animal.letter(), &tr_name(animal)]));
+        }
+
+        let (name, letter, color) = &mut self.new_animal;
+        ui.horizontal(|ui| {
+            ui.label(tr("Name"));
+            ui.text_edit_singleline(name);
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("Letter"));
+            ui.add(egui::TextEdit::singleline(letter).desired_width(20.0));
+            color_picker::color_edit_button_srgb(ui, color);
+            if ui.button(tr("Add")).clicked() {
+                let name = name.trim();
+                let result = match letter.trim().chars().collect_vec().as_slice() {
+                    _ if name.is_empty() => Err(tr("Enter a name to add the animal.").to_owned()),
+                    &[letter] => Animal::custom(name, letter, *color, &animals),
+                    _ => Err(tr("Enter one letter for the animal in piece files.").to_owned()),
+                };
+                self.animal_notice = Some(match result {
+                    Ok(animal) => {
+                        self.building_map.custom_animals.push(animal);
+                        trf("Added {}.", &[&animal])
+                    }
+                    Err(e) => e,
+                });
+            }
+        });
+        if let Some(notice) = &self.animal_notice {
+            ui.add(Label::new(notice).wrap(true));
+        }
+    }
+
+    /// Add the piece to the custom pieces of the map setup. Returns a message for the user.
+    fn save(&mut self) -> String {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return tr("Enter a name to save the piece.").to_owned();
+        }
+        let existing = Piece::all(&self.building_map.custom_pieces);
+        match Piece::custom(name, &self.piece.definition(), &self.animals(), &existing) {
+            Ok(piece) => {
+                self.building_map.custom_pieces.push(piece);
+                self.unsaved = false;
+                trf("Saved. Piece {} can now be selected for the map.", &[&name])
+            }
+            Err(e) => trf("Saving failed: {}", &[&e]),
+        }
+    }
+}
+
+/// A piece with desert everywhere.
+fn blank_piece() -> ParsedPiece {
+    let tiles = (0..PIECE_HEIGHT)
+        .cartesian_product(0..PIECE_WIDTH)
+        .map(|(row, col)| Tile {
+            position: Hex::from_offset_coordinates([col, row], OffsetHexMode::OddColumns),
+            terrain: Terrain::Desert,
+            animal: None,
+            structure: None,
+            small: false,
+            answers: Default::default(),
+        })
+        .collect();
+    ParsedPiece(tiles)
+}
@@ -1,37 +1,193 @@
 use enum_dispatch::enum_dispatch;
 use hexx::Hex;
 use notan::egui;
+use serde::Serialize;
+use strum::IntoStaticStr;
 
-use crate::model::{PlayerList, Tile};
+use crate::{
+    highlight::HighlightChannel,
+    houserules::HouseRules,
+    i18n::{tr, trf},
+    model::{Answer, Piece, PlayerColor, PlayerID, PlayerList, Tile},
+};
 
 mod buildingmap;
+mod choosingscenario;
+mod editingpiece;
 mod placingstructures;
+mod practice;
 mod tryingclues;
+mod verifying;
+mod widgets;
 
 pub use buildingmap::BuildingMap;
+pub use choosingscenario::ChoosingScenario;
+pub use editingpiece::EditingPiece;
 pub use placingstructures::PlacingStructures;
+pub use practice::{Difficulty, Practice};
 pub use tryingclues::TryingClues;
+pub use verifying::Verifying;
 
 #[enum_dispatch]
 pub trait Common {
     /// Tiles to draw in the window.
     fn tiles(&self) -> &[Tile];
     fn tiles_mut(&mut self) -> &mut [Tile];
-    /// Show an egui. Return true to switch to the next state.
-    fn gui(&mut self, ui: &mut egui::Ui) -> bool;
-    /// Draw a highlight around a tile, if needed.
+    /// House rules of the map.
+    fn house_rules(&self) -> &HouseRules;
+    /// House rules to change. They can only be changed before the game starts, None after.
+    fn house_rules_mut(&mut self) -> Option<&mut HouseRules> {
+        None
+    }
+    /// Pieces the user created in the piece editor. They are kept for the next game.
+    fn custom_pieces(&self) -> &[Piece] {
+        &[]
+    }
+    /// Show an egui. Return a transition to leave the state.
+    fn gui(&mut self, ui: &mut egui::Ui) -> Option<Transition>;
+    /// The state that comes after this one, or why this state can't be left that way.
+    fn next(&self) -> Result<SubState, String> {
+        Err("There is no state after this one. This is a bug.".to_owned())
+    }
+    /// The state to go back to, or why this state can't be left that way.
+    fn prev(&self) -> Result<SubState, String> {
+        Err("There is no state before this one. This is a bug.".to_owned())
+    }
+    /// What would be lost by leaving the state, to ask the user first.
+    /// None if the switch can happen without asking.
+    fn confirm_switch(&self) -> Option<String> {
+        None
+    }
+    /// The user does not want to switch states after all.
+    fn cancel_switch(&mut self) {}
+    /// Draw a highlight around a tile, if needed. These are the tiles the user selected.
     fn highlights(&self) -> Vec<Hex>;
+    /// Highlights that are shown next to the selection, in the colors of their channels.
+    fn channel_highlights(&self) -> Vec<(HighlightChannel, Vec<Hex>)> {
+        Vec::new()
+    }
     /// Click on a tile.
     fn click(&mut self, hex: Hex);
+    /// Click on a tile while holding shift.
+    fn modified_click(&mut self, hex: Hex) {
+        self.click(hex)
+    }
     fn players(&self) -> &PlayerList;
+    /// Tiles that are part of a region the user selected.
+    fn region(&self) -> Vec<Hex> {
+        Vec::new()
+    }
+    /// Tiles to draw darker, because the user is interested in the other tiles.
+    fn shaded(&self) -> Vec<Hex> {
+        Vec::new()
+    }
+    /// Short texts to write on tiles.
+    fn tile_labels(&self) -> Vec<(Hex, String)> {
+        Vec::new()
+    }
+    /// The user pressed escape.
+    fn escape(&mut self) {}
+    /// The user pressed the key for answering the selected tile for themselves.
+    fn answer_shortcut(&mut self, _answer: Answer) {}
+    /// The user clicked the answer marker of the player on the tile, to go on to the next
+    /// answer. Returns a notice about the change, or None to handle the click like any other.
+    fn cycle_answer(&mut self, _hex: Hex, _player: PlayerID) -> Option<String> {
+        None
+    }
+    /// The user pressed the key for showing the next hint.
+    fn next_hint(&mut self) {}
+    /// True if dragging over tiles should paint them instead of moving the map.
+    fn paints(&self) -> bool {
+        false
+    }
+    /// Paint a tile by dragging over it.
+    fn paint(&mut self, _hex: Hex) {}
+    /// True if dragging over tiles should move the pieces of the map.
+    fn drags_pieces(&self) -> bool {
+        false
+    }
+    /// Move the piece containing the first tile to where the second tile is.
+    fn drag_piece(&mut self, _from: Hex, _to: Hex) {}
+    /// Move the structure on the first tile to the second tile, which has room for it.
+    fn move_structure(&mut self, from: Hex, to: Hex) {
+        let tiles = self.tiles_mut();
+        let from = tiles.iter().position(|t| t.position == from);
+        let to = tiles.iter().position(|t| t.position == to);
+        if let (Some(from), Some(to)) = (from, to) {
+            tiles[to].structure = tiles[from].structure.take();
+            self.map_changed();
+        }
+    }
+    /// The structures on the map changed outside of [Common::gui], for example by dragging one.
+    /// Forget everything that was worked out from them. This is called once per change, so
+    /// states don't need to compare the map with the last frame to notice it.
+    fn map_changed(&mut self) {}
+    /// The map from the perspective of one player, if the user wants to see it: the color of the
+    /// player and the tiles that only their answers or clue rule out.
+    fn player_layer(&self) -> Option<(PlayerColor, Vec<Hex>)> {
+        None
+    }
+    /// Why the cryptid can't be on the tile, if it was ruled out.
+    fn elimination_reasons(&self, _hex: Hex) -> Vec<String> {
+        Vec::new()
+    }
+    /// True if the app filled in the answer of the player on the tile.
+    fn is_auto_answer(&self, _hex: Hex, _player: PlayerID) -> bool {
+        false
+    }
+    /// The user has been inactive for a while. Use the time for expensive work.
+    fn idle(&mut self) {}
+}
+
+/// How the user wants to leave a state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Transition {
+    /// On to the state that comes after it, see [Common::next].
+    Next,
+    /// Back to an earlier state, see [Common::prev].
+    Back,
 }
 
 #[enum_dispatch(Common)]
-#[derive(Debug)]
+#[derive(Debug, Serialize, IntoStaticStr)]
+#[allow(clippy::large_enum_variant)] // There is only ever one of these around.
 pub enum SubState {
     BuildingMap,
     PlacingStructures,
     TryingClues,
+    Verifying,
+    Practice,
+    ChoosingScenario,
+    EditingPiece,
+}
+
+impl SubState {
+    /// Name of the state for logging.
+    pub fn name(&self) -> &'static str {
+        self.into()
+    }
+
+    /// The state the transition leads to, if this state can be left that way.
+    pub fn after(&self, transition: Transition) -> Result<SubState, String> {
+        match transition {
+            Transition::Next => self.next(),
+            Transition::Back => self.prev(),
+        }
+    }
+
+    /// What the user is doing, for the title of the window.
+    pub fn title(&self) -> String {
+        match self {
+            SubState::BuildingMap(_) => tr("Building the map").to_owned(),
+            SubState::PlacingStructures(_) => tr("Placing structures").to_owned(),
+            SubState::TryingClues(s) if s.history.is_empty() => tr("Playing").to_owned(),
+            SubState::TryingClues(s) => trf("{} answers", &[&s.history.len()]),
+            SubState::Verifying(_) => tr("Verifying").to_owned(),
+            SubState::Practice(_) => tr("Practice").to_owned(),
+            SubState::ChoosingScenario(_) => tr("Choosing a scenario").to_owned(),
+            SubState::EditingPiece(_) => tr("Editing a piece").to_owned(),
+        }
+    }
 }
 
 impl Default for SubState {
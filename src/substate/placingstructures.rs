@@ -1,36 +1,113 @@
-use hexx::Hex;
-use notan::egui::{self, Align, Layout};
+use std::collections::HashSet;
+
+use hexx::{Hex, OffsetHexMode};
+use itertools::Itertools;
+use notan::{
+    egui::{self, Align, Label, Layout},
+    random::rand::{rngs::StdRng, seq::SliceRandom, thread_rng, Rng, SeedableRng},
+};
+use serde::Serialize;
 use strum::IntoEnumIterator;
 
 use crate::{
-    model::{PlayerList, Structure, StructureColor, StructureKind, Tile},
+    houserules::HouseRules,
+    i18n::{tr, tr_name, trf},
+    model::{
+        coordinate, piece_slot, Piece, PlayerList, Structure, StructureColor, StructureKind, Tile,
+    },
+    notation::Board,
     LAYOUT_SPACE,
 };
 
-use super::{buildingmap::BuildingMap, Common};
+use super::{
+    buildingmap::BuildingMap,
+    verifying::Verifying,
+    widgets::{board_code, describe_tile},
+    Common, SubState, Transition,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct PlacingStructures {
     map: Vec<Tile>,
+    /// House rules of the game, chosen with the map.
+    rules: HouseRules,
+    /// Pieces from the piece editor, to recognize them on the map.
+    custom_pieces: Vec<Piece>,
     pub players: PlayerList,
+    /// True if the user wants to play against simulated players instead of a real game.
+    pub practice: bool,
+    /// Seed for random structure placement, so that a placement can be repeated. Small enough
+    /// to be edited exactly, which would not work with every u64.
+    seed: u32,
+    /// Structure to place with the next click on a tile.
+    selected: Option<Structure>,
+    /// Pieces the user compared with the physical board, by their slot.
+    verified: HashSet<[i32; 2]>,
+    /// Tile the user points at in the board check.
+    checking: Option<Hex>,
+    /// Structures the user moved, from and to which tile, for undoing them.
+    moves: Vec<(Hex, Hex)>,
+    /// Tiles the app put structures on when their color was added, not the user.
+    added: Vec<(Hex, Structure)>,
 }
 
 impl From<&BuildingMap> for PlacingStructures {
     fn from(value: &BuildingMap) -> Self {
         let mut s = Self {
             map: value.tiles().to_vec(),
+            rules: value.house_rules().clone(),
+            custom_pieces: value.custom_pieces().to_vec(),
             players: value.players.clone(),
+            practice: false,
+            seed: thread_rng().gen(),
+            selected: None,
+            verified: HashSet::new(),
+            checking: None,
+            moves: Vec::new(),
+            added: Vec::new(),
         };
 
-        // Add default colors
-        s.add(StructureColor::White);
-        s.add(StructureColor::Green);
-        s.add(StructureColor::Blue);
+        if value.structures.is_empty() {
+            // Add default colors
+            s.add(StructureColor::White);
+            s.add(StructureColor::Green);
+            s.add(StructureColor::Blue);
+        } else {
+            s.place(&value.structures);
+        }
 
         s
     }
 }
 
+impl From<&Verifying> for PlacingStructures {
+    fn from(value: &Verifying) -> Self {
+        // Keep the map with its structures, but start without answers.
+        let map = value
+            .tiles()
+            .iter()
+            .map(|tile| Tile {
+                small: false,
+                answers: Default::default(),
+                ..tile.clone()
+            })
+            .collect();
+        Self {
+            map,
+            rules: value.house_rules().clone(),
+            custom_pieces: value.custom_pieces().to_vec(),
+            players: value.players().clone(),
+            practice: false,
+            seed: thread_rng().gen(),
+            selected: None,
+            verified: HashSet::new(),
+            checking: None,
+            moves: Vec::new(),
+            added: Vec::new(),
+        }
+    }
+}
+
 impl Common for PlacingStructures {
     fn tiles(&self) -> &[Tile] {
         &self.map
@@ -38,15 +115,24 @@ impl Common for PlacingStructures {
     fn tiles_mut(&mut self) -> &mut [Tile] {
         &mut self.map
     }
+    fn house_rules(&self) -> &HouseRules {
+        &self.rules
+    }
+    fn house_rules_mut(&mut self) -> Option<&mut HouseRules> {
+        Some(&mut self.rules)
+    }
+    fn custom_pieces(&self) -> &[Piece] {
+        &self.custom_pieces
+    }
 
-    fn gui(&mut self, ui: &mut egui::Ui) -> bool {
+    fn gui(&mut self, ui: &mut egui::Ui) -> Option<Transition> {
         let mut next_state = false;
 
-        ui.heading("Structures");
+        ui.heading(tr("Structures"));
         for color in StructureColor::iter() {
             let mut has = self.has(color);
             if ui
-                .checkbox(&mut has, format!("{color} structures"))
+                .checkbox(&mut has, trf("{} structures", &[&tr_name(color)]))
                 .changed()
             {
                 if has {
@@ -58,23 +144,183 @@ impl Common for PlacingStructures {
         }
 
         ui.add_space(LAYOUT_SPACE);
-        ui.label("Drag structures into position on the map.");
+        ui.add(
+            Label::new(tr(
+                "Drag structures into position on the map. \
+                Or select a structure here or on the map, and click where it should go.",
+            ))
+            .wrap(true),
+        );
+        let placed = self.map.iter().filter_map(|t| t.structure).collect_vec();
+        ui.horizontal_wrapped(|ui| {
+            for structure in placed {
+                let text = trf(
+                    "{} {}",
+                    &[&tr_name(structure.color), &tr_name(structure.kind)],
+                );
+                if ui
+                    .selectable_label(self.selected == Some(structure), text)
+                    .clicked()
+                {
+                    self.selected = (self.selected != Some(structure)).then_some(structure);
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !self.moves.is_empty(),
+                    egui::Button::new(tr("Undo last move")),
+                )
+                .clicked()
+            {
+                self.undo_move();
+            }
+            if ui
+                .button(tr("Reset to default layout"))
+                .on_hover_text(tr("Put all structures back on the first tiles of the map"))
+                .clicked()
+            {
+                self.reset_layout();
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .button(tr("Randomize structures"))
+                .on_hover_text(tr("Place the structures like a setup card would"))
+                .clicked()
+            {
+                self.randomize();
+            }
+            ui.label(tr("Seed"));
+            ui.add(egui::DragValue::new(&mut self.seed));
+            if ui.button(tr("New seed")).clicked() {
+                self.seed = thread_rng().gen();
+            }
+        });
+        for warning in self.warnings() {
+            ui.add(Label::new(warning).wrap(true));
+        }
+        let pieces = Piece::all(&self.custom_pieces);
+        board_code(
+            ui,
+            Board::from_tiles(&self.map, &pieces),
+            &pieces,
+            |board| self.load_board(board),
+        );
+        self.gui_for_check(ui);
         ui.add_space(LAYOUT_SPACE);
 
+        let block = self.block();
+        if let Some(block) = &block {
+            ui.add(Label::new(block).wrap(true));
+        }
         ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
-            if ui.button("Ready").clicked() {
+            if ui
+                .add_enabled(block.is_none(), egui::Button::new(tr("Ready")))
+                .clicked()
+            {
+                // Practice may have been tried before and failed.
+                self.practice = false;
+                next_state = true;
+            }
+            // The game is made for three to five players.
+            let possible = block.is_none() && (3..=5).contains(&self.players.len());
+            if ui
+                .add_enabled(possible, egui::Button::new(tr("Practice")))
+                .on_hover_text(tr("Play against 2 to 4 simulated players"))
+                .on_disabled_hover_text(tr("Practice needs 3 to 5 players"))
+                .clicked()
+            {
+                self.practice = true;
                 next_state = true;
             }
         });
 
-        next_state
+        next_state.then_some(Transition::Next)
+    }
+
+    fn next(&self) -> Result<SubState, String> {
+        if let Some(block) = self.block() {
+            return Err(block);
+        }
+        Ok(if self.practice {
+            SubState::Practice(self.try_into()?)
+        } else {
+            SubState::TryingClues(self.into())
+        })
+    }
+
+    fn confirm_switch(&self) -> Option<String> {
+        let structures = self.map.iter().filter(|t| t.structure.is_some()).count();
+        let mut question = trf(
+            "{} structures are placed. They can't be moved after this.",
+            &[&structures],
+        );
+        let unmoved = self
+            .unmoved()
+            .into_iter()
+            .map(|s| trf("{} {}", &[&tr_name(s.color), &tr_name(s.kind)]))
+            .join(", ");
+        if !unmoved.is_empty() {
+            question.push(' ');
+            question.push_str(&trf(
+                "These structures are still where they were added: {}. \
+                Did you forget to drag them?",
+                &[&unmoved],
+            ));
+        }
+        Some(question)
+    }
+
+    fn cancel_switch(&mut self) {
+        self.practice = false;
     }
 
     fn highlights(&self) -> Vec<Hex> {
-        Vec::new()
+        self.map
+            .iter()
+            .filter(|t| t.structure.is_some() && t.structure == self.selected)
+            .map(|t| t.position)
+            .chain(self.checking)
+            .collect()
+    }
+
+    fn click(&mut self, hex: Hex) {
+        let Some(to) = self.map.iter().position(|t| t.position == hex) else {
+            return;
+        };
+        // Select the structure on the tile, or move the selected one there.
+        let Some(selected) = self.selected else {
+            self.selected = self.map[to].structure;
+            return;
+        };
+        if self.map[to].structure.is_some() {
+            self.selected = self.map[to].structure.filter(|&s| s != selected);
+            return;
+        }
+        let Some(from) = self.map.iter().find(|t| t.structure == Some(selected)) else {
+            // The color was removed in the meantime.
+            self.selected = None;
+            return;
+        };
+        self.move_structure(from.position, hex);
+        self.selected = None;
     }
 
-    fn click(&mut self, _hex: Hex) {}
+    fn move_structure(&mut self, from: Hex, to: Hex) {
+        let from_index = self.map.iter().position(|t| t.position == from);
+        let to_index = self.map.iter().position(|t| t.position == to);
+        let (Some(from_index), Some(to_index)) = (from_index, to_index) else {
+            return;
+        };
+        if from == to || self.map[to_index].structure.is_some() {
+            return;
+        }
+        self.map[to_index].structure = self.map[from_index].structure.take();
+        self.moves.push((from, to));
+        self.map_changed();
+    }
 
     fn players(&self) -> &PlayerList {
         &self.players
@@ -82,6 +328,202 @@ impl Common for PlacingStructures {
 }
 
 impl PlacingStructures {
+    /// Explain why the structures can't be used for a game, if they can't.
+    /// Tiles only have room for one structure, so that does not need to be checked.
+    fn block(&self) -> Option<String> {
+        for color in StructureColor::iter() {
+            let kinds = self
+                .map
+                .iter()
+                .filter_map(|t| t.structure)
+                .filter(|s| s.color == color)
+                .map(|s| s.kind)
+                .collect_vec();
+            let complete = kinds.len() == 2
+                && kinds.contains(&StructureKind::Shack)
+                && kinds.contains(&StructureKind::Stone);
+            if !kinds.is_empty() && !complete {
+                return Some(trf(
+                    "Place one shack and one standing stone for {} to continue",
+                    &[&tr_name(color)],
+                ));
+            }
+        }
+        None
+    }
+
+    /// List every tile piece by piece, so that the map can be compared with the physical board.
+    fn gui_for_check(&mut self, ui: &mut egui::Ui) {
+        self.checking = None;
+        let pieces = Board::from_tiles(&self.map, &Piece::all(&self.custom_pieces)).ok();
+        let slots = self
+            .map
+            .iter()
+            .into_group_map_by(|t| piece_slot(t.position));
+        let columns = slots.keys().map(|[col, _]| col + 1).max().unwrap_or(0);
+
+        egui::CollapsingHeader::new(tr("Check the board"))
+            .id_source("board-check")
+            .show(ui, |ui| {
+                ui.add(
+                    Label::new(tr("Compare every tile with the physical board. \
+                        Point at a tile to find it on the map."))
+                    .wrap(true),
+                );
+                ui.label(trf(
+                    "{} of {} pieces are verified.",
+                    &[&self.verified.len(), &slots.len()],
+                ));
+
+                for (&[col, row], tiles) in
+                    slots.iter().sorted_by_key(|([col, row], _)| (*row, *col))
+                {
+                    ui.separator();
+                    let (row_number, col_number) = (row + 1, col + 1);
+                    let name = match &pieces {
+                        Some(board) => trf(
+                            "Piece {} (row {}, column {})",
+                            &[
+                                &board.pieces[(row * columns + col) as usize],
+                                &row_number,
+                                &col_number,
+                            ],
+                        ),
+                        None => trf("Row {}, column {}", &[&row_number, &col_number]),
+                    };
+                    let mut verified = self.verified.contains(&[col, row]);
+                    if ui.checkbox(&mut verified, name).changed() {
+                        if verified {
+                            self.verified.insert([col, row]);
+                        } else {
+                            self.verified.remove(&[col, row]);
+                        }
+                    }
+                    let sorted = tiles.iter().sorted_by_key(|t| {
+                        let [col, row] =
+                            t.position.to_offset_coordinates(OffsetHexMode::OddColumns);
+                        (col, row)
+                    });
+                    for tile in sorted {
+                        if ui.label(describe_tile(tile)).hovered() {
+                            self.checking = Some(tile.position);
+                        }
+                    }
+                }
+            });
+    }
+
+    /// Take the structures of a board code that was made for this map.
+    fn load_board(&mut self, board: Board) -> Result<(), String> {
+        let current = Board::from_tiles(&self.map, &Piece::all(&self.custom_pieces))?;
+        if (&current.pieces, current.columns) != (&board.pieces, board.columns) {
+            return Err(tr("The code is for a different map.").to_owned());
+        }
+        for tile in self.map.iter_mut() {
+            tile.structure = None;
+        }
+        self.place(&board.structures);
+        self.selected = None;
+        self.moves.clear();
+        self.added.clear();
+        Ok(())
+    }
+
+    /// Move the structure that was moved last back to where it came from.
+    fn undo_move(&mut self) {
+        let Some((from, to)) = self.moves.pop() else {
+            return;
+        };
+        let from_index = self.map.iter().position(|t| t.position == from);
+        let to_index = self.map.iter().position(|t| t.position == to);
+        if let (Some(from_index), Some(to_index)) = (from_index, to_index) {
+            if self.map[from_index].structure.is_none() {
+                self.map[from_index].structure = self.map[to_index].structure.take();
+            }
+        }
+        self.selected = None;
+    }
+
+    /// Put the structures of all present colors back where they were added.
+    fn reset_layout(&mut self) {
+        let colors = StructureColor::iter()
+            .filter(|&c| self.has(c))
+            .collect_vec();
+        for tile in self.map.iter_mut() {
+            tile.structure = None;
+        }
+        self.added.clear();
+        for color in colors {
+            self.add(color);
+        }
+        self.selected = None;
+        self.moves.clear();
+    }
+
+    /// Put structures on their tiles.
+    fn place(&mut self, structures: &[(Hex, Structure)]) {
+        for (hex, structure) in structures {
+            if let Some(tile) = self.map.iter_mut().find(|t| t.position == *hex) {
+                tile.structure = Some(*structure);
+            }
+        }
+    }
+
+    /// Setups that are allowed, but unusual for the official setups. Mistakes are likely.
+    fn warnings(&self) -> Vec<String> {
+        let structures = self
+            .map
+            .iter()
+            .filter(|t| t.structure.is_some())
+            .map(|t| t.position)
+            .collect_vec();
+        let mut warnings = structures
+            .iter()
+            .tuple_combinations()
+            .filter(|(a, b)| a.distance_to(**b) == 1)
+            .map(|(&a, &b)| {
+                trf(
+                    "The structures on {} and {} are next to each other. \
+                    That is unusual for official setups, check your setup card.",
+                    &[&coordinate(a), &coordinate(b)],
+                )
+            })
+            .collect_vec();
+
+        // Setup cards spread the two structures of a color over different terrains.
+        for color in StructureColor::iter() {
+            let terrains = self
+                .map
+                .iter()
+                .filter(|t| t.structure.is_some_and(|s| s.color == color))
+                .map(|t| t.terrain)
+                .collect_vec();
+            if let [a, b] = terrains[..] {
+                if a == b {
+                    warnings.push(trf(
+                        "Both {} structures stand on {}. \
+                        That is unusual for official setups, check your setup card.",
+                        &[&tr_name(color), &tr_name(a)],
+                    ));
+                }
+            }
+        }
+        warnings
+    }
+
+    /// Structures that are still on the tiles they were added on.
+    fn unmoved(&self) -> Vec<Structure> {
+        self.added
+            .iter()
+            .filter(|(hex, structure)| {
+                self.map
+                    .iter()
+                    .any(|t| t.position == *hex && t.structure == Some(*structure))
+            })
+            .map(|(_, structure)| *structure)
+            .collect()
+    }
+
     /// Returns true if the structure color is present.
     fn has(&self, color: StructureColor) -> bool {
         self.map
@@ -110,11 +552,50 @@ impl PlacingStructures {
                 i += 1;
             }
             self.map[i].structure = Some(to_add);
+            self.added.push((self.map[i].position, to_add));
+        }
+    }
+
+    /// Move the structures of all present colors to random tiles, given by the seed.
+    /// Structures don't end up next to each other, unless the map is too small for that.
+    fn randomize(&mut self) {
+        self.moves.clear();
+        self.added.clear();
+        let structures = self
+            .map
+            .iter_mut()
+            .filter_map(|t| t.structure.take())
+            .collect_vec();
+
+        let mut rng = StdRng::seed_from_u64(self.seed.into());
+        let mut order = (0..self.map.len()).collect_vec();
+        order.shuffle(&mut rng);
+
+        for structure in structures {
+            let occupied = self
+                .map
+                .iter()
+                .filter(|t| t.structure.is_some())
+                .map(|t| t.position)
+                .collect_vec();
+            let free = order
+                .iter()
+                .copied()
+                .filter(|&i| self.map[i].structure.is_none());
+            let apart = free.clone().find(|&i| {
+                occupied
+                    .iter()
+                    .all(|o| o.distance_to(self.map[i].position) > 1)
+            });
+            if let Some(i) = apart.or_else(|| free.clone().next()) {
+                self.map[i].structure = Some(structure);
+            }
         }
     }
 
     /// Delete the structures for the given color from the map.
     fn delete(&mut self, color: StructureColor) {
+        self.added.retain(|(_, s)| s.color != color);
         for tile in &mut self.map {
             let Some(structure) = tile.structure else {
                 continue;
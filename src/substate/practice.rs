@@ -0,0 +1,922 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use hexx::Hex;
+use itertools::Itertools;
+use notan::{
+    egui::{self, Align, Label, Layout},
+    random::rand::{seq::SliceRandom, thread_rng},
+};
+use serde::Serialize;
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::{
+    houserules::HouseRules,
+    i18n::{tr, tr_name, trf},
+    model::{coordinate, Answer, Clue, Map, PlayerID, PlayerList, Tile},
+    scenario::{Objective, Setup},
+    LAYOUT_SPACE,
+};
+
+use super::{
+    choosingscenario::ChoosingScenario, placingstructures::PlacingStructures, Common, Transition,
+};
+
+/// Number of log entries to show.
+const LOG_LENGTH: usize = 8;
+/// Number of games to play for estimating the difficulty.
+const DIFFICULTY_SAMPLES: usize = 20;
+
+/// A sub state for playing a game against players simulated by the app.
+#[derive(Debug, Clone, Serialize)]
+pub struct Practice {
+    map: Map,
+    players: PlayerList,
+    /// The player that is using this software. Every other player is simulated.
+    user: PlayerID,
+    /// The secret clue of every player.
+    clues: HashMap<PlayerID, Clue>,
+    /// The only tile where all clues apply.
+    cryptid: Hex,
+    phase: Phase,
+    /// How each simulated player chooses their questions.
+    policies: HashMap<PlayerID, Policy>,
+    /// Every clue with the positions it applies to.
+    #[serde(skip)]
+    coverage: Vec<(Clue, HashSet<Hex>)>,
+    /// What happened so far, newest last.
+    log: Vec<String>,
+    /// Message for the user when they tried to do something that isn't allowed.
+    error: Option<String>,
+    highlights: Vec<Hex>,
+    /// Goal of the scenario, if one is played. Simulated players don't take turns in scenarios.
+    objective: Option<Objective>,
+    /// Number of questions the user asked. Failed searches count as questions.
+    questions: usize,
+    /// True if only the user takes turns, like in scenarios.
+    solo: bool,
+    /// How hard it is to find the cryptid from here. Estimated when the user is idle.
+    difficulty: Option<Difficulty>,
+}
+
+/// How many questions are needed to find the cryptid, estimated by letting the app
+/// play the game a few times. The app asks the questions with the most uncertain answers,
+/// which is close to optimal play.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Difficulty {
+    pub average: f32,
+    pub best: usize,
+    pub worst: usize,
+}
+
+impl Difficulty {
+    /// Estimate how many questions the first player needs to find the cryptid,
+    /// if only they take turns.
+    pub fn of(setup: &Setup) -> Self {
+        Practice::new(setup.clone(), None).estimate_difficulty()
+    }
+
+    pub fn rating(&self) -> &'static str {
+        match self.average {
+            a if a <= 5.0 => tr("Easy"),
+            a if a <= 10.0 => tr("Medium"),
+            _ => tr("Hard"),
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = trf(
+            "{}, {} questions on average ({} to {})",
+            &[
+                &self.rating(),
+                &format!("{:.1}", self.average),
+                &self.best,
+                &self.worst,
+            ],
+        );
+        write!(f, "{text}")
+    }
+}
+
+/// What the game is waiting for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+enum Phase {
+    /// The player can ask a question or search.
+    Turn(PlayerID),
+    /// The user has to answer another player on a tile.
+    UserAnswers {
+        asker: PlayerID,
+        tile: Hex,
+        search: bool,
+    },
+    /// The user received a "no" and has to place a "no" of their own.
+    UserPlacesNo,
+    /// The player found the cryptid.
+    Won(PlayerID),
+}
+
+/// How a simulated player chooses their questions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, EnumIter, Display, Serialize)]
+enum Policy {
+    /// Ask a random question on a tile where the cryptid might be.
+    #[default]
+    Random,
+    /// Ask the question that rules out the most clues on average.
+    Greedy,
+    /// Ask the question with the most uncertain answer, like the hints do.
+    Entropy,
+}
+
+impl TryFrom<&PlacingStructures> for Practice {
+    type Error = String;
+
+    fn try_from(value: &PlacingStructures) -> Result<Self, Self::Error> {
+        let map = Map {
+            tiles: value.tiles().to_vec(),
+            rules: value.house_rules().clone(),
+        };
+        let players = value.players.clone();
+        let user = players
+            .iter()
+            .next()
+            .map(|p| p.id)
+            .expect("empty PlayerList is not possible");
+        let coverage = map.coverage(false);
+
+        let (cryptid, clues) = generate_game(&map, &coverage, players.len())
+            .ok_or("No game is possible on this map. Place more structures.")?;
+        let clues = players.iter().map(|p| p.id).zip(clues).collect();
+        let policies = players
+            .iter()
+            .filter(|p| p.id != user)
+            .map(|p| (p.id, Policy::default()))
+            .collect();
+
+        Ok(Self {
+            map,
+            players,
+            user,
+            clues,
+            cryptid,
+            phase: Phase::Turn(user),
+            policies,
+            coverage,
+            log: vec![tr("The game started. It's your turn.").to_owned()],
+            error: None,
+            highlights: Vec::new(),
+            objective: None,
+            questions: 0,
+            solo: false,
+            difficulty: None,
+        })
+    }
+}
+
+impl From<&ChoosingScenario> for Practice {
+    fn from(value: &ChoosingScenario) -> Self {
+        let scenario = value
+            .chosen
+            .as_ref()
+            .expect("a scenario must be chosen to play it");
+        let setup = scenario
+            .build()
+            .expect("scenarios are checked when they are loaded");
+
+        let mut s = Self::new(setup, scenario.objective);
+        s.log.push(scenario.name.clone());
+        if !scenario.description.is_empty() {
+            s.log.push(scenario.description.clone());
+        }
+        if let Some(objective) = scenario.objective {
+            s.log.push(objective.to_string());
+        }
+        s
+    }
+}
+
+impl Common for Practice {
+    fn tiles(&self) -> &[Tile] {
+        &self.map.tiles
+    }
+    fn tiles_mut(&mut self) -> &mut [Tile] {
+        &mut self.map.tiles
+    }
+    fn house_rules(&self) -> &HouseRules {
+        &self.map.rules
+    }
+
+    fn gui(&mut self, ui: &mut egui::Ui) -> Option<Transition> {
+        ui.heading(tr("Practice"));
+        ui.add(
+            Label::new(trf(
+                "You are {}. Your clue: the cryptid is {}.",
+                &[&self.players.get(self.user).name, &self.clues[&self.user]],
+            ))
+            .wrap(true),
+        );
+
+        if let Some(difficulty) = self.difficulty {
+            ui.add(Label::new(trf("Difficulty from here: {}", &[&difficulty])).wrap(true));
+        }
+
+        ui.add_space(LAYOUT_SPACE);
+        for line in self.log.iter().rev().take(LOG_LENGTH).rev() {
+            ui.add(Label::new(line).wrap(true));
+        }
+        ui.add_space(LAYOUT_SPACE);
+
+        match self.phase {
+            Phase::Turn(player) if player == self.user => self.gui_for_user_turn(ui),
+            Phase::Turn(player) => {
+                ui.label(trf("It's {}'s turn.", &[&self.players.get(player).name]));
+                ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
+                    if ui.button(tr("Continue")).clicked() {
+                        self.ai_turn(player);
+                    }
+                });
+            }
+            Phase::UserAnswers { asker, tile, .. } => {
+                ui.add(
+                    Label::new(trf(
+                        "{} asks you about {}. Could the cryptid be there?",
+                        &[&self.players.get(asker).name, &coordinate(tile)],
+                    ))
+                    .wrap(true),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Yes")).clicked() {
+                        self.user_answers(Answer::Yes);
+                    }
+                    if ui.button(tr("No")).clicked() {
+                        self.user_answers(Answer::No);
+                    }
+                });
+            }
+            Phase::UserPlacesNo => {
+                ui.add(
+                    Label::new(tr("Select a tile where your clue rules out the cryptid."))
+                        .wrap(true),
+                );
+                if let Some(tile) = self.selection() {
+                    if ui
+                        .button(trf("Place 'no' on {}", &[&coordinate(tile)]))
+                        .clicked()
+                    {
+                        self.user_places_no(tile);
+                    }
+                }
+            }
+            Phase::Won(player) => {
+                ui.label(trf(
+                    "{} found the cryptid on {}.",
+                    &[&self.players.get(player).name, &coordinate(self.cryptid)],
+                ));
+                if let Some(objective) = self.objective {
+                    let text = if self.questions <= objective.questions {
+                        trf("You needed {} questions. Well done!", &[&self.questions])
+                    } else {
+                        trf(
+                            "You needed {} questions. Try again with at most {}.",
+                            &[&self.questions, &objective.questions],
+                        )
+                    };
+                    ui.add(Label::new(text).wrap(true));
+                }
+            }
+        }
+
+        if let Some(error) = &self.error {
+            ui.add(Label::new(error).wrap(true));
+        }
+
+        ui.add_space(LAYOUT_SPACE);
+        if !self.solo {
+            self.gui_for_policies(ui);
+        }
+        egui::CollapsingHeader::new(tr("Reveal solution")).show(ui, |ui| {
+            for player in self.players.iter() {
+                ui.add(
+                    Label::new(format!("{}: {}", player.name, self.clues[&player.id])).wrap(true),
+                );
+            }
+            ui.label(trf("The cryptid is on {}.", &[&coordinate(self.cryptid)]));
+        });
+
+        None
+    }
+
+    fn highlights(&self) -> Vec<Hex> {
+        match self.phase {
+            Phase::UserAnswers { tile, .. } => vec![tile],
+            _ => self.highlights.to_vec(),
+        }
+    }
+
+    fn click(&mut self, hex: Hex) {
+        self.highlights = self
+            .map
+            .get(hex)
+            .is_some()
+            .then_some(hex)
+            .into_iter()
+            .collect();
+    }
+
+    fn players(&self) -> &PlayerList {
+        &self.players
+    }
+
+    fn idle(&mut self) {
+        let playing = !matches!(self.phase, Phase::Won(_));
+        if playing && self.difficulty.is_none() {
+            self.difficulty = Some(self.estimate_difficulty());
+        }
+    }
+}
+
+impl Practice {
+    /// Play a scenario, where only the user takes turns.
+    fn new(setup: Setup, objective: Option<Objective>) -> Self {
+        let user = setup
+            .players
+            .iter()
+            .next()
+            .map(|p| p.id)
+            .expect("scenarios have players");
+        let with_inverted = setup.clues.values().any(|c| c.inverted);
+        let coverage = setup.map.coverage(with_inverted);
+
+        Self {
+            map: setup.map,
+            players: setup.players,
+            user,
+            clues: setup.clues,
+            cryptid: setup.cryptid,
+            phase: Phase::Turn(user),
+            policies: HashMap::new(),
+            coverage,
+            log: Vec::new(),
+            error: None,
+            highlights: Vec::new(),
+            objective,
+            questions: 0,
+            solo: true,
+            difficulty: None,
+        }
+    }
+
+    fn gui_for_policies(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new(tr("Opponents")).show(ui, |ui| {
+            egui::Grid::new("practice-policies").show(ui, |ui| {
+                for player in self.players.iter().filter(|p| p.id != self.user) {
+                    ui.label(&player.name);
+                    let policy = self.policies.entry(player.id).or_default();
+                    egui::ComboBox::new(format!("policy-for-player-{:?}", player.id), "")
+                        .selected_text(tr_name(*policy))
+                        .show_ui(ui, |ui| {
+                            for option in Policy::iter() {
+                                ui.selectable_value(policy, option, tr_name(option));
+                            }
+                        });
+                    ui.end_row();
+                }
+            });
+        });
+    }
+
+    fn gui_for_user_turn(&mut self, ui: &mut egui::Ui) {
+        let Some(tile) = self.selection() else {
+            ui.label(tr("Your turn. Select a tile to ask about or search."));
+            return;
+        };
+
+        ui.label(trf("Your turn. What about {}?", &[&coordinate(tile)]));
+        let opponents = self
+            .players
+            .iter()
+            .filter(|p| p.id != self.user)
+            .map(|p| (p.id, p.name.clone()))
+            .collect_vec();
+        ui.horizontal_wrapped(|ui| {
+            for (opponent, name) in opponents {
+                if ui.button(trf("Ask {}", &[&name])).clicked() {
+                    self.error = None;
+                    if self.has_no(tile) {
+                        self.error = Some(tr("You can't ask about a tile with a 'no'.").to_owned());
+                    } else {
+                        self.question(self.user, opponent, tile);
+                    }
+                }
+            }
+            if ui.button(tr("Search")).clicked() {
+                self.error = None;
+                if self.has_no(tile) {
+                    self.error = Some(tr("You can't search a tile with a 'no'.").to_owned());
+                } else if !self.applies(self.user, tile) {
+                    self.error = Some(tr("Your own clue rules out this tile.").to_owned());
+                } else {
+                    self.search(self.user, tile);
+                }
+            }
+        });
+    }
+
+    /// The single selected tile.
+    fn selection(&self) -> Option<Hex> {
+        match self.highlights.as_slice() {
+            [tile] => Some(*tile),
+            _ => None,
+        }
+    }
+
+    fn name(&self, player: PlayerID) -> &str {
+        &self.players.get(player).name
+    }
+
+    /// True if the clue of the player allows the cryptid on the tile.
+    fn applies(&self, player: PlayerID, tile: Hex) -> bool {
+        self.map.clue_applies(self.clues[&player], tile)
+    }
+
+    fn answer(&self, player: PlayerID, tile: Hex) -> Answer {
+        self.map
+            .get(tile)
+            .and_then(|t| t.answers.get(&player))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Questions and searches are not allowed on tiles where anybody placed a "no".
+    fn has_no(&self, tile: Hex) -> bool {
+        self.map
+            .get(tile)
+            .map(|t| t.answers.values().any(|&a| a == Answer::No))
+            .unwrap_or(false)
+    }
+
+    /// The answer the player has to give on the tile.
+    fn truthful_answer(&self, player: PlayerID, tile: Hex) -> Answer {
+        if self.applies(player, tile) {
+            Answer::Yes
+        } else {
+            Answer::No
+        }
+    }
+
+    fn set_answer(&mut self, player: PlayerID, tile: Hex, answer: Answer) {
+        if let Some(tile) = self.map.get_mut(tile) {
+            tile.answers.insert(player, answer);
+        }
+        self.difficulty = None;
+    }
+
+    /// The asker asks the answerer about the tile.
+    fn question(&mut self, asker: PlayerID, answerer: PlayerID, tile: Hex) {
+        self.log.push(trf(
+            "{} asks {} about {}.",
+            &[&self.name(asker), &self.name(answerer), &coordinate(tile)],
+        ));
+        if asker == self.user {
+            self.questions += 1;
+        }
+
+        if answerer == self.user {
+            self.phase = Phase::UserAnswers {
+                asker,
+                tile,
+                search: false,
+            };
+            return;
+        }
+
+        let answer = self.truthful_answer(answerer, tile);
+        self.set_answer(answerer, tile, answer);
+        self.log.push(trf(
+            "{} answers {}.",
+            &[&self.name(answerer), &tr_name(answer)],
+        ));
+        self.after_question(asker, answer);
+    }
+
+    /// After receiving a "no" the asker has to place a "no" themselves.
+    fn after_question(&mut self, asker: PlayerID, answer: Answer) {
+        if answer == Answer::No {
+            if asker == self.user {
+                self.phase = Phase::UserPlacesNo;
+                return;
+            }
+            self.ai_places_no(asker);
+        }
+        self.end_turn();
+    }
+
+    /// The searcher claims that the cryptid is on the tile.
+    fn search(&mut self, searcher: PlayerID, tile: Hex) {
+        self.log.push(trf(
+            "{} searches {}.",
+            &[&self.name(searcher), &coordinate(tile)],
+        ));
+        self.set_answer(searcher, tile, Answer::Yes);
+        self.continue_search(searcher, tile);
+    }
+
+    /// Let the other players answer on the searched tile, in turn order.
+    fn continue_search(&mut self, searcher: PlayerID, tile: Hex) {
+        let order = self.turn_order_after(searcher);
+        for player in order {
+            if player == searcher || self.answer(player, tile) == Answer::Yes {
+                continue;
+            }
+
+            if player == self.user {
+                self.phase = Phase::UserAnswers {
+                    asker: searcher,
+                    tile,
+                    search: true,
+                };
+                return;
+            }
+
+            let answer = self.truthful_answer(player, tile);
+            self.set_answer(player, tile, answer);
+            self.log.push(trf(
+                "{} answers {}.",
+                &[&self.name(player), &tr_name(answer)],
+            ));
+
+            if answer == Answer::No {
+                self.log.push(tr("The search failed.").to_owned());
+                if searcher == self.user {
+                    // Otherwise guessing would be free in scenarios.
+                    self.questions += 1;
+                }
+                self.end_turn();
+                return;
+            }
+        }
+
+        self.log
+            .push(trf("{} found the cryptid!", &[&self.name(searcher)]));
+        self.phase = Phase::Won(searcher);
+    }
+
+    fn user_answers(&mut self, answer: Answer) {
+        let Phase::UserAnswers {
+            asker,
+            tile,
+            search,
+        } = self.phase
+        else {
+            return;
+        };
+
+        if answer != self.truthful_answer(self.user, tile) {
+            self.error = Some(tr("That's not what your clue says.").to_owned());
+            return;
+        }
+
+        self.error = None;
+        self.set_answer(self.user, tile, answer);
+        self.log.push(trf("You answer {}.", &[&tr_name(answer)]));
+
+        match (search, answer) {
+            (true, Answer::No) => {
+                self.log.push(tr("The search failed.").to_owned());
+                self.end_turn();
+            }
+            (true, _) => self.continue_search(asker, tile),
+            (false, answer) => self.after_question(asker, answer),
+        }
+    }
+
+    fn user_places_no(&mut self, tile: Hex) {
+        if self.applies(self.user, tile) {
+            self.error = Some(tr("Your clue allows the cryptid there.").to_owned());
+            return;
+        }
+        if self.answer(self.user, tile) != Answer::Unknown {
+            self.error = Some(tr("You already answered on that tile.").to_owned());
+            return;
+        }
+
+        self.error = None;
+        self.set_answer(self.user, tile, Answer::No);
+        self.log
+            .push(trf("You place a 'no' on {}.", &[&coordinate(tile)]));
+        self.end_turn();
+    }
+
+    /// Place a "no" on a random tile where the clue of the player does not apply.
+    fn ai_places_no(&mut self, player: PlayerID) {
+        let options = self
+            .map
+            .tiles
+            .iter()
+            .map(|t| t.position)
+            .filter(|&t| !self.applies(player, t) && self.answer(player, t) == Answer::Unknown)
+            .collect_vec();
+        if let Some(&tile) = options.choose(&mut thread_rng()) {
+            self.set_answer(player, tile, Answer::No);
+            self.log.push(trf(
+                "{} places a 'no' on {}.",
+                &[&self.name(player), &coordinate(tile)],
+            ));
+        }
+    }
+
+    /// Let a simulated player ask a question, or search if they know where the cryptid is.
+    fn ai_turn(&mut self, player: PlayerID) {
+        let candidates = self.candidates(player);
+        if let [tile] = candidates.as_slice() {
+            self.search(player, *tile);
+            return;
+        }
+
+        let policy = self.policies.get(&player).copied().unwrap_or_default();
+        match self.choose_question(player, policy, &candidates) {
+            Some((opponent, tile)) => self.question(player, opponent, tile),
+            None => {
+                self.log.push(trf("{} passes.", &[&self.name(player)]));
+                self.end_turn();
+            }
+        }
+    }
+
+    /// The question the player asks next, given the tiles where they think the cryptid could be.
+    fn choose_question(
+        &self,
+        player: PlayerID,
+        policy: Policy,
+        candidates: &[Hex],
+    ) -> Option<(PlayerID, Hex)> {
+        let question = match policy {
+            Policy::Random => None,
+            Policy::Greedy => self.best_question(player, |yes, no| {
+                // Every clue is equally likely, so a "yes" happens with a chance of yes / total
+                // and rules out the clues that would have said "no".
+                2.0 * yes * no / (yes + no)
+            }),
+            Policy::Entropy => self.best_question(player, |yes, no| {
+                let p = yes / (yes + no);
+                let q = 1.0 - p;
+                -(p * p.log2() + q * q.log2())
+            }),
+        };
+        question.or_else(|| self.random_question(player, candidates))
+    }
+
+    /// Let the app make every move for the user, until the cryptid was found.
+    /// Returns the number of questions that were needed.
+    fn play_alone(&mut self) -> usize {
+        self.solo = true;
+        // Every move answers a question, so this many moves are never needed.
+        for _ in 0..self.map.tiles.len() * self.players.len() {
+            match self.phase {
+                Phase::Won(_) => break,
+                Phase::UserPlacesNo => {
+                    self.ai_places_no(self.user);
+                    self.end_turn();
+                }
+                Phase::Turn(_) => {
+                    let candidates = self.candidates(self.user);
+                    if let [tile] = candidates.as_slice() {
+                        self.search(self.user, *tile);
+                        continue;
+                    }
+                    match self.choose_question(self.user, Policy::Entropy, &candidates) {
+                        Some((opponent, tile)) => self.question(self.user, opponent, tile),
+                        None => match candidates.choose(&mut thread_rng()) {
+                            Some(&tile) => self.search(self.user, tile),
+                            None => break,
+                        },
+                    }
+                }
+                // Only the user takes turns, so nobody asks the user.
+                Phase::UserAnswers { .. } => break,
+            }
+        }
+        self.questions
+    }
+
+    /// Estimate how many questions the user needs from the current position on.
+    fn estimate_difficulty(&self) -> Difficulty {
+        let results = (0..DIFFICULTY_SAMPLES)
+            .map(|_| {
+                let mut game = self.clone();
+                game.phase = Phase::Turn(self.user);
+                game.questions = 0;
+                game.play_alone()
+            })
+            .collect_vec();
+
+        Difficulty {
+            average: results.iter().sum::<usize>() as f32 / results.len() as f32,
+            best: results.iter().copied().min().unwrap_or_default(),
+            worst: results.iter().copied().max().unwrap_or_default(),
+        }
+    }
+
+    /// A random legal question on a tile where the cryptid could be.
+    fn random_question(&self, player: PlayerID, candidates: &[Hex]) -> Option<(PlayerID, Hex)> {
+        let mut rng = thread_rng();
+        let tile = candidates
+            .iter()
+            .copied()
+            .filter(|&t| !self.has_no(t))
+            .collect_vec()
+            .choose(&mut rng)
+            .copied()?;
+        let opponent = self
+            .players
+            .iter()
+            .map(|p| p.id)
+            .filter(|&p| p != player && self.answer(p, tile) == Answer::Unknown)
+            .collect_vec()
+            .choose(&mut rng)
+            .copied()?;
+        Some((opponent, tile))
+    }
+
+    /// The legal question with the highest score. The score is calculated from how many
+    /// possible clues of the opponent would answer "yes" and "no" on the tile.
+    fn best_question(
+        &self,
+        player: PlayerID,
+        score: impl Fn(f32, f32) -> f32,
+    ) -> Option<(PlayerID, Hex)> {
+        let mut questions = Vec::new();
+        let opponents = self.players.iter().map(|p| p.id).filter(|&p| p != player);
+        for opponent in opponents {
+            let clues = self.possible_clues(opponent);
+            if clues.len() <= 1 {
+                // Nothing left to learn about this opponent.
+                continue;
+            }
+
+            let tiles = self
+                .map
+                .tiles
+                .iter()
+                .map(|t| t.position)
+                .filter(|&t| !self.has_no(t) && self.answer(opponent, t) == Answer::Unknown);
+            for tile in tiles {
+                let yes = clues.iter().filter(|c| c.contains(&tile)).count();
+                let no = clues.len() - yes;
+                if yes == 0 || no == 0 {
+                    // The answer is already known.
+                    continue;
+                }
+                questions.push((score(yes as f32, no as f32), opponent, tile));
+            }
+        }
+
+        // Choose randomly between equally good questions.
+        questions.shuffle(&mut thread_rng());
+        questions
+            .into_iter()
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, opponent, tile)| (opponent, tile))
+    }
+
+    /// Tiles where the player thinks the cryptid could be, knowing their own clue
+    /// and all answers on the map.
+    fn candidates(&self, player: PlayerID) -> Vec<Hex> {
+        let others = self
+            .players
+            .iter()
+            .map(|p| p.id)
+            .filter(|&p| p != player)
+            .map(|p| self.possible_clues(p))
+            .collect_vec();
+
+        self.map
+            .tiles
+            .iter()
+            .map(|t| t.position)
+            .filter(|&t| self.applies(player, t))
+            .filter(|t| {
+                others
+                    .iter()
+                    .all(|clues| clues.iter().any(|covered| covered.contains(t)))
+            })
+            .collect()
+    }
+
+    /// Positions of every clue the player might have according to their answers.
+    fn possible_clues(&self, player: PlayerID) -> Vec<&HashSet<Hex>> {
+        let answers = self
+            .map
+            .tiles
+            .iter()
+            .filter_map(|t| t.answers.get(&player).map(|&a| (t.position, a)))
+            .collect_vec();
+
+        self.coverage
+            .iter()
+            .map(|(_, covered)| covered)
+            .filter(|covered| {
+                answers.iter().all(|(position, answer)| match answer {
+                    Answer::Unknown => true,
+                    Answer::Yes => covered.contains(position),
+                    Answer::No => !covered.contains(position),
+                })
+            })
+            .collect()
+    }
+
+    /// All players, starting with the one after the given player.
+    fn turn_order_after(&self, player: PlayerID) -> Vec<PlayerID> {
+        let ids = self.players.iter().map(|p| p.id).collect_vec();
+        let start = ids.iter().position(|&p| p == player).unwrap_or(0);
+        ids.iter()
+            .cycle()
+            .skip(start + 1)
+            .take(ids.len())
+            .copied()
+            .collect()
+    }
+
+    fn end_turn(&mut self) {
+        let current = match self.phase {
+            Phase::Turn(player) => player,
+            Phase::UserAnswers { asker, .. } => asker,
+            Phase::UserPlacesNo => self.user,
+            Phase::Won(_) => return,
+        };
+        let next = if self.solo {
+            self.user
+        } else {
+            self.turn_order_after(current)[0]
+        };
+        self.phase = Phase::Turn(next);
+        if next == self.user {
+            self.log.push(tr("It's your turn.").to_owned());
+        }
+    }
+}
+
+/// Pick a cryptid and one clue per player, so that only the cryptid matches all clues.
+/// Every clue is required to find the cryptid, like in the official setups.
+fn generate_game(
+    map: &Map,
+    coverage: &[(Clue, HashSet<Hex>)],
+    players: usize,
+) -> Option<(Hex, Vec<Clue>)> {
+    let mut rng = thread_rng();
+    let positions = map.tiles.iter().map(|t| t.position).collect_vec();
+
+    for _ in 0..1000 {
+        let cryptid = *positions.choose(&mut rng)?;
+        let mut options = coverage
+            .iter()
+            .filter(|(_, covered)| covered.contains(&cryptid))
+            .collect_vec();
+        options.shuffle(&mut rng);
+
+        // Add clues that narrow down the possible tiles, until only the last clue is missing.
+        let mut chosen: Vec<&(Clue, HashSet<Hex>)> = Vec::new();
+        let mut remaining: HashSet<Hex> = positions.iter().copied().collect();
+        for option in &options {
+            if chosen.len() + 1 == players {
+                break;
+            }
+            let next: HashSet<Hex> = remaining.intersection(&option.1).copied().collect();
+            if next.len() < remaining.len() && next.len() > 1 {
+                chosen.push(option);
+                remaining = next;
+            }
+        }
+
+        // The last clue has to single out the cryptid.
+        let last = options.iter().find(|(clue, covered)| {
+            !chosen.iter().any(|(c, _)| c == clue) && remaining.intersection(covered).count() == 1
+        });
+        let Some(last) = last else {
+            continue;
+        };
+        chosen.push(last);
+
+        let all_required = (0..chosen.len()).all(|skip| {
+            let without = chosen
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != skip)
+                .map(|(_, (_, covered))| covered);
+            positions
+                .iter()
+                .filter(|p| without.clone().all(|covered| covered.contains(p)))
+                .count()
+                > 1
+        });
+
+        if chosen.len() == players && all_required {
+            return Some((cryptid, chosen.into_iter().map(|(c, _)| *c).collect()));
+        }
+    }
+
+    None
+}
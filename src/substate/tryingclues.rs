@@ -1,36 +1,299 @@
-use std::{collections::HashMap, hash::Hash};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use hexx::Hex;
 use itertools::Itertools;
-use notan::egui::{self, Grid, Label};
-use strum::IntoEnumIterator;
+use notan::egui::{self, Align, Color32, Grid, Label, Layout, RichText};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, IntoEnumIterator};
 
 use crate::{
+    analysis::Analysis,
+    export,
+    highlight::HighlightChannel,
+    houserules::HouseRules,
+    i18n::{tr, tr_name, trf},
     model::{
-        Animal, Answer, Clue, ClueKind, Hint, Map, PlayerID, PlayerList, StructureColor,
-        StructureKind, Terrain, Tile,
+        coordinate, piece_slot, Answer, AnswerEvent, Clue, ClueKind, ClueTarget, Hint, Map, Piece,
+        PlayerColor, PlayerID, PlayerList, Terrain, Tile, TileMask,
     },
+    prefs, report, simplemode, theme,
+    winchance::{self, Estimation, WinChances},
     LAYOUT_SPACE,
 };
 
-use super::{placingstructures::PlacingStructures, Common};
+use super::{
+    placingstructures::PlacingStructures,
+    widgets::{clue_editor, describe_tile, tile_editor},
+    Common, SubState, Transition,
+};
+
+/// Searching is recommended if the cryptid is on the tile in at least this share of combinations.
+const SEARCH_CHANCE: f64 = 0.5;
 
-#[derive(Debug)]
+/// Number of candidate tiles to list.
+const CANDIDATE_COUNT: usize = 10;
+
+#[derive(Debug, Serialize)]
 pub struct TryingClues {
     map: Map,
+    /// Pieces from the piece editor, for the next game.
+    custom_pieces: Vec<Piece>,
     /// Manually entered clues
     clues: HashMap<PlayerID, Clue>,
     /// Map from player to a bool. True: We know the clue; False: The clue should be deduced.
     known_clues: HashMap<PlayerID, bool>,
     /// Cache for clues deduced from answers.
     deduced_clues: HashMap<PlayerID, Vec<Clue>>,
-    /// True if the game is played with inverted clues.
-    with_inverted: bool,
+    /// Every clue, inverted ones included, with the tiles it applies to. Only changes with the
+    /// map, see [Common::map_changed].
+    #[serde(skip)]
+    masks: Vec<(Clue, TileMask)>,
+    /// True for players whose clue might be inverted. Depending on the setup card
+    /// only some players get inverted clues in advanced games.
+    pub with_inverted: HashMap<PlayerID, bool>,
     highlights: Vec<Hex>,
-    players: PlayerList,
+    /// Tiles of hints and previews the user wants to see, besides the selection.
+    #[serde(skip)]
+    shown: HashMap<HighlightChannel, Vec<Hex>>,
+    /// Player whose eliminations are the only ones shown on the map.
+    #[serde(skip)]
+    layer: Option<PlayerID>,
+    /// Player whose answer clicking a tile places or takes back.
+    #[serde(skip)]
+    active: Option<PlayerID>,
+    /// The answer clicking a tile places for the active player.
+    #[serde(skip)]
+    active_answer: Answer,
+    pub players: PlayerList,
     hints: Vec<Hint>,
     /// The player that is using this software. Used for cheating from the correct perspective.
     user: PlayerID,
+    /// Opponent whose remaining clues are projected onto the remaining tiles.
+    projected: Option<PlayerID>,
+    /// Result of the last report export to show to the user.
+    export_notice: Option<String>,
+    /// Answers in the order they were given.
+    pub history: Vec<AnswerEvent>,
+    /// Hints only consider these tiles, unless this is empty.
+    region: HashSet<Hex>,
+    /// True if clicking tiles adds them to the region.
+    selecting_region: bool,
+    /// How the board is divided for the summary.
+    partition: Partition,
+    /// True if the answer of the user is filled in on the tile they are asked about.
+    auto_fill: bool,
+    /// True if the answers of the user are filled in on every tile, not only where they are
+    /// asked.
+    prefill_all: bool,
+    /// True if the hints include where opponents will probably ask next.
+    predict_questions: bool,
+    /// Tiles where the answer of a player was filled in by the app, for each player who was the
+    /// user at the time.
+    auto_answers: HashMap<PlayerID, HashSet<Hex>>,
+    /// Tiles where a player took back an answer the app filled in. They are not filled in again.
+    taken_back: HashMap<PlayerID, HashSet<Hex>>,
+    /// The answers before each change of many answers at once, the latest last.
+    #[serde(skip)]
+    bulk_undo: Vec<BulkUndo>,
+    /// True if the clue of the user should not show up anywhere, for playing on a shared screen.
+    hide_own: bool,
+    /// Possible clue the user is looking at. Tiles where it does not apply are shaded.
+    focus: Option<Clue>,
+    /// Consistent combinations of clues. Computed when the app is idle, None until then.
+    #[serde(skip)]
+    analysis: Option<Analysis>,
+    /// Number of games to play for estimating the chances to win.
+    win_samples: usize,
+    /// Estimated chance to win of every player. Only made when the user asks for it.
+    #[serde(skip)]
+    win_chances: Option<WinChances>,
+    /// Games that are still being played for the chances to win.
+    #[serde(skip)]
+    estimation: Option<Estimation>,
+    /// How the deductions came about, step by step. Only made when the user asks for it.
+    #[serde(skip)]
+    explanation: Option<Vec<String>>,
+    /// What to write on the tiles that might have the cryptid.
+    tile_label: TileLabel,
+    /// The player who asks the questions that are entered. None if answers are entered freely.
+    asker: Option<PlayerID>,
+    /// Questions in the order they were asked.
+    questions: Vec<Question>,
+    /// Player who was answered with a "no" and has to place a cube of their own.
+    owes_cube: Option<PlayerID>,
+    /// Explanation why the last entered answer breaks the rules.
+    rule_warning: Option<String>,
+    /// Current step of entering a turn with guidance. None if answers are entered freely.
+    wizard: Option<Wizard>,
+}
+
+/// Ways to divide the board for the summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Display, Serialize)]
+enum Partition {
+    #[strum(to_string = "Board pieces")]
+    Pieces,
+    #[strum(to_string = "Selected region")]
+    Region,
+}
+
+/// Something to do with all answers of a player at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bulk {
+    Clear(PlayerID),
+    /// Answer no on every tile of the region the player did not answer yet.
+    NoInRegion(PlayerID),
+    /// Give the player the answers of another one, to fix answers entered for the wrong player.
+    Copy {
+        from: PlayerID,
+        to: PlayerID,
+    },
+}
+
+/// Everything a change of many answers at once changes, to take it back.
+#[derive(Debug, Clone)]
+struct BulkUndo {
+    /// The answers on every tile, in the order of the tiles.
+    answers: Vec<BTreeMap<PlayerID, Answer>>,
+    auto_answers: HashMap<PlayerID, HashSet<Hex>>,
+    taken_back: HashMap<PlayerID, HashSet<Hex>>,
+}
+
+/// Number of changes of many answers at once that can be taken back.
+const BULK_UNDO_LENGTH: usize = 20;
+
+/// A question one player asked another.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+struct Question {
+    asker: PlayerID,
+    answerer: PlayerID,
+    position: Hex,
+    answer: Answer,
+}
+
+/// Steps of entering a turn with guidance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+enum Wizard {
+    /// Whose turn is it?
+    Turn,
+    /// Does the player ask a question or search?
+    Action { player: PlayerID },
+    /// Which tile is the question or search about?
+    Tile { player: PlayerID, search: bool },
+    /// Whom did the player ask and what was the answer?
+    Ask { player: PlayerID, tile: Hex },
+    /// Where does the player put their cube after a "no"?
+    Cube { player: PlayerID },
+    /// The other players answer the search one after another, until one says "no".
+    Search {
+        player: PlayerID,
+        tile: Hex,
+        answered: usize,
+    },
+    /// The search found the cryptid.
+    Found { tile: Hex },
+}
+
+/// Parts of the panel, which can be collapsed and reordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter, Display, Serialize, Deserialize)]
+enum Section {
+    Answers,
+    #[strum(to_string = "Selected tile")]
+    SelectedTile,
+    Cheat,
+    Projection,
+    Summary,
+    Candidates,
+    Clues,
+    Export,
+    #[strum(to_string = "Fix map")]
+    MapFixes,
+}
+
+impl Section {
+    /// True for sections that are shown in simple mode.
+    fn is_simple(self) -> bool {
+        matches!(
+            self,
+            Section::Answers | Section::SelectedTile | Section::Summary | Section::MapFixes
+        )
+    }
+}
+
+/// Order of the sections and which of them are open, kept for the next start.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SectionLayout {
+    order: Vec<Section>,
+    open: HashSet<Section>,
+}
+
+impl Default for SectionLayout {
+    fn default() -> Self {
+        Self {
+            order: Section::iter().collect(),
+            open: Section::iter()
+                .filter(|&s| s != Section::MapFixes)
+                .collect(),
+        }
+    }
+}
+
+impl SectionLayout {
+    const FILE: &'static str = "sections.json";
+
+    /// The layout of the last session. Sections that are newer than it go to the end.
+    fn load() -> Self {
+        let Some(json) = prefs::load(Self::FILE) else {
+            return Self::default();
+        };
+        match serde_json::from_str::<Self>(&json) {
+            Ok(mut layout) => {
+                for section in Section::iter() {
+                    if !layout.order.contains(&section) {
+                        layout.order.push(section);
+                    }
+                }
+                layout
+            }
+            Err(e) => {
+                report::log(format!("Could not read the sections: {e}"));
+                Self::default()
+            }
+        }
+    }
+
+    fn store(&self) {
+        let result = serde_json::to_string(self)
+            .map_err(|e| e.to_string())
+            .and_then(|json| prefs::store(Self::FILE, &json));
+        if let Err(e) = result {
+            report::log(format!("Could not remember the sections: {e}"));
+        }
+    }
+}
+
+/// What to write on the tiles of the map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Display, Serialize)]
+enum TileLabel {
+    Nothing,
+    Combinations,
+    Percentage,
+}
+
+/// File formats for the deduction report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Display)]
+enum ReportFormat {
+    #[strum(to_string = "CSV")]
+    Csv,
+    Markdown,
+}
+
+impl ReportFormat {
+    fn file_name(self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "cryptid-deductions.csv",
+            ReportFormat::Markdown => "cryptid-deductions.md",
+        }
+    }
 }
 
 impl From<&PlacingStructures> for TryingClues {
@@ -42,16 +305,51 @@ impl From<&PlacingStructures> for TryingClues {
             .map(|p| p.id)
             .expect("empty PlayerList is not possible");
 
+        let map = Map {
+            tiles: value.tiles().to_vec(),
+            rules: value.house_rules().clone(),
+        };
         let mut s = Self {
-            map: Map(value.tiles().to_vec()),
+            masks: map.masks(true),
+            map,
+            custom_pieces: value.custom_pieces().to_vec(),
             highlights: Vec::new(),
+            shown: HashMap::new(),
+            layer: None,
+            active: None,
+            active_answer: Answer::No,
             players,
             clues: Default::default(),
             known_clues: Default::default(),
             deduced_clues: Default::default(),
             hints: Default::default(),
             user,
-            with_inverted: false,
+            projected: None,
+            export_notice: None,
+            history: Vec::new(),
+            region: HashSet::new(),
+            selecting_region: false,
+            with_inverted: HashMap::new(),
+            partition: Partition::Pieces,
+            auto_fill: false,
+            prefill_all: false,
+            predict_questions: false,
+            auto_answers: HashMap::new(),
+            taken_back: HashMap::new(),
+            bulk_undo: Vec::new(),
+            hide_own: false,
+            focus: None,
+            analysis: None,
+            win_samples: winchance::DEFAULT_SAMPLES,
+            win_chances: None,
+            estimation: None,
+            explanation: None,
+            tile_label: TileLabel::Nothing,
+            asker: None,
+            questions: Vec::new(),
+            owes_cube: None,
+            rule_warning: None,
+            wizard: None,
         };
 
         s.deduce_clues();
@@ -66,262 +364,1707 @@ impl From<&PlacingStructures> for TryingClues {
 
 impl Common for TryingClues {
     fn tiles(&self) -> &[Tile] {
-        &self.map.0
+        &self.map.tiles
     }
     fn tiles_mut(&mut self) -> &mut [Tile] {
-        &mut self.map.0
+        &mut self.map.tiles
+    }
+    fn house_rules(&self) -> &HouseRules {
+        &self.map.rules
+    }
+    fn custom_pieces(&self) -> &[Piece] {
+        &self.custom_pieces
+    }
+
+    fn gui(&mut self, ui: &mut egui::Ui) -> Option<Transition> {
+        let clues_before = self.clues.clone();
+        let known_clues_before = self.known_clues.clone();
+        let answers_before = self.answers();
+        let user_before = self.user;
+        let region_before = self.region.clone();
+        let with_inverted_before = self.with_inverted.clone();
+        let hide_own_before = self.hide_own;
+
+        let mut everyone_inverted = self
+            .players
+            .iter()
+            .filter(|p| !self.is_hidden(p.id))
+            .all(|p| self.may_be_inverted(p.id));
+        if !simplemode::enabled()
+            && ui
+                .checkbox(&mut everyone_inverted, tr("Enable inverted clues"))
+                .changed()
+        {
+            for player in self.players.iter() {
+                self.with_inverted.insert(player.id, everyone_inverted);
+            }
+        }
+
+        self.gui_for_sections(ui);
+
+        let clues_changed = clues_before != self.clues;
+        let known_clues_changed = known_clues_before != self.known_clues;
+        // Changes to the map itself call map_changed, only answers are compared here.
+        let answers_changed = answers_before != self.answers();
+        let user_changed = user_before != self.user;
+        let with_inverted_changed = with_inverted_before != self.with_inverted;
+        let region_changed = region_before != self.region;
+        let hidden_changed = hide_own_before != self.hide_own || (self.hide_own && user_changed);
+
+        if answers_changed {
+            self.record_answers(&answers_before);
+        }
+
+        if answers_changed || with_inverted_changed || hidden_changed {
+            // The tiles i.e. the answers have changed so we need to think about the possible clues again.
+            self.deduce_clues();
+        }
+
+        if clues_changed
+            || known_clues_changed
+            || answers_changed
+            || with_inverted_changed
+            || hidden_changed
+        {
+            self.update_map_from_clues();
+        }
+
+        if clues_changed
+            || known_clues_changed
+            || answers_changed
+            || user_changed
+            || with_inverted_changed
+            || region_changed
+        {
+            // Something changed that influences the hints. Recomputing those is expensive,
+            // so just clear them. The user can refresh them by pressing a button.
+            self.hints.clear();
+            self.shown.remove(&HighlightChannel::Hint);
+        }
+
+        if clues_changed
+            || known_clues_changed
+            || answers_changed
+            || with_inverted_changed
+            || hidden_changed
+        {
+            // Recomputed when the app is idle. The tiles that are shown came from the old
+            // results.
+            self.analysis = None;
+            self.explanation = None;
+            self.win_chances = None;
+            self.estimation = None;
+            self.shown.clear();
+        }
+
+        ui.add_space(LAYOUT_SPACE);
+        let mut found = false;
+        ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
+            if ui.button(tr("Cryptid found")).clicked() {
+                found = true;
+            }
+        });
+
+        found.then_some(Transition::Next)
+    }
+
+    fn next(&self) -> Result<SubState, String> {
+        Ok(SubState::Verifying(self.into()))
+    }
+
+    fn confirm_switch(&self) -> Option<String> {
+        let answers = self
+            .map
+            .tiles
+            .iter()
+            .flat_map(|t| t.answers.values())
+            .filter(|&&a| a != Answer::Unknown)
+            .count();
+        Some(trf(
+            "{} answers are entered. They can't be changed after this.",
+            &[&answers],
+        ))
+    }
+
+    fn highlights(&self) -> Vec<Hex> {
+        self.highlights.to_vec()
+    }
+
+    fn channel_highlights(&self) -> Vec<(HighlightChannel, Vec<Hex>)> {
+        self.shown
+            .iter()
+            .map(|(&channel, tiles)| (channel, tiles.to_vec()))
+            .collect()
+    }
+
+    fn click(&mut self, hex: Hex) {
+        if self.selecting_region {
+            if self.region.remove(&hex) {
+                self.region_changed();
+            } else {
+                self.paint(hex);
+            }
+            return;
+        }
+
+        if let (Some(player), Some(tile)) = (self.active, self.map.get(hex)) {
+            let current = tile.answers.get(&player).copied().unwrap_or_default();
+            let answer = if current == self.active_answer {
+                Answer::Unknown
+            } else {
+                self.active_answer
+            };
+            self.answer(hex, player, answer);
+        }
+
+        self.highlights = self
+            .map
+            .get(hex)
+            .is_some()
+            .then_some(hex)
+            .into_iter()
+            .collect();
+    }
+
+    fn players(&self) -> &PlayerList {
+        &self.players
+    }
+
+    fn region(&self) -> Vec<Hex> {
+        self.region.iter().copied().collect()
+    }
+
+    fn paints(&self) -> bool {
+        self.selecting_region
+    }
+
+    fn map_changed(&mut self) {
+        self.masks = self.map.masks(true);
+        self.deduce_clues();
+        self.update_map_from_clues();
+        self.hints.clear();
+        self.analysis = None;
+        self.explanation = None;
+        self.win_chances = None;
+        self.estimation = None;
+        self.shown.clear();
+        // The answers before could belong to other tiles now.
+        self.bulk_undo.clear();
+    }
+
+    fn paint(&mut self, hex: Hex) {
+        if self.map.get(hex).is_some() && self.region.insert(hex) {
+            self.region_changed();
+        }
+    }
+
+    fn shaded(&self) -> Vec<Hex> {
+        let Some(focus) = self.focus else {
+            return Vec::new();
+        };
+        self.map
+            .tiles
+            .iter()
+            .map(|t| t.position)
+            .filter(|&t| !self.map.clue_applies(focus, t))
+            .collect()
+    }
+
+    fn tile_labels(&self) -> Vec<(Hex, String)> {
+        let Some(analysis) = &self.analysis else {
+            return Vec::new();
+        };
+        self.map
+            .tiles
+            .iter()
+            .filter(|t| !t.small)
+            .filter_map(|t| {
+                let count = analysis
+                    .counts
+                    .get(&t.position)
+                    .copied()
+                    .unwrap_or_default();
+                let label = match self.tile_label {
+                    TileLabel::Nothing => return None,
+                    TileLabel::Combinations => count.to_string(),
+                    TileLabel::Percentage => {
+                        format!("{:.0}%", analysis.chance(t.position) * 100.0)
+                    }
+                };
+                Some((t.position, label))
+            })
+            .collect()
+    }
+
+    fn escape(&mut self) {
+        self.focus = None;
+        self.shown.clear();
+    }
+
+    fn player_layer(&self) -> Option<(PlayerColor, Vec<Hex>)> {
+        let player = self.layer.filter(|&p| !self.is_hidden(p))?;
+        let clues = self.possible_clues(player);
+        let tiles = self
+            .map
+            .tiles
+            .iter()
+            .map(|t| t.position)
+            .filter(|&hex| !clues.iter().any(|&c| self.map.clue_applies(c, hex)))
+            .collect();
+        Some((self.players.get(player).color, tiles))
+    }
+
+    fn elimination_reasons(&self, hex: Hex) -> Vec<String> {
+        // The same checks that make tiles small when updating the map.
+        self.players
+            .iter()
+            .filter(|p| !self.is_hidden(p.id))
+            .filter_map(|p| {
+                let known = self.known_clues.get(&p.id).copied().unwrap_or_default();
+                let known_clue = self.clues.get(&p.id).filter(|_| known);
+                if let Some(&clue) = known_clue {
+                    if !self.map.clue_applies(clue, hex) {
+                        return Some(trf("The clue of {} excludes it: {}", &[&p.name, &clue]));
+                    }
+                }
+                let deduced = self.deduced_clues.get(&p.id).map_or(&[][..], |c| c);
+                if deduced.iter().any(|&c| self.map.clue_applies(c, hex)) {
+                    None
+                } else if deduced.is_empty() {
+                    Some(trf("{} has no possible clue left", &[&p.name]))
+                } else {
+                    Some(trf(
+                        "All {} possible clues of {} exclude it",
+                        &[&deduced.len(), &p.name],
+                    ))
+                }
+            })
+            .collect()
+    }
+
+    fn answer_shortcut(&mut self, answer: Answer) {
+        if let [hex] = *self.highlights.as_slice() {
+            self.answer(hex, self.user, answer);
+        }
+    }
+
+    fn cycle_answer(&mut self, hex: Hex, player: PlayerID) -> Option<String> {
+        if self.active.is_some() {
+            // The click is meant for the answer of the active player.
+            return None;
+        }
+        let current = self.map.get(hex)?.answers.get(&player).copied();
+        let answer = match current.unwrap_or_default() {
+            Answer::Unknown => Answer::Yes,
+            Answer::Yes => Answer::No,
+            Answer::No => Answer::Unknown,
+        };
+        self.answer(hex, player, answer);
+        let answer = self.map.get(hex)?.answers.get(&player).copied();
+        let name = &self.players.get(player).name;
+        Some(trf(
+            "Answer of {} changed to {}",
+            &[&name, &tr_name(answer.unwrap_or_default())],
+        ))
+    }
+
+    fn next_hint(&mut self) {
+        if self.hints.is_empty() {
+            self.calculate_hints();
+        }
+        let shown = self.shown.get(&HighlightChannel::Hint);
+        let next = self
+            .hints
+            .iter()
+            .position(|h| Some(&h.tiles) == shown)
+            .map_or(0, |i| i + 1);
+        // Going past the last hint hides them again.
+        match self.hints.get(next) {
+            Some(hint) => self
+                .shown
+                .insert(HighlightChannel::Hint, hint.tiles.clone()),
+            None => self.shown.remove(&HighlightChannel::Hint),
+        };
+    }
+
+    fn is_auto_answer(&self, hex: Hex, player: PlayerID) -> bool {
+        self.auto_answers
+            .get(&player)
+            .is_some_and(|tiles| tiles.contains(&hex))
+    }
+
+    fn idle(&mut self) {
+        if self.hints.is_empty() {
+            self.calculate_hints();
+        }
+        if self.analysis.is_none() {
+            self.analyze();
+        }
+    }
+}
+
+impl TryingClues {
+    /// Every section in a collapsible header, in the order the user chose. The order and
+    /// which sections are open are kept for the next start.
+    fn gui_for_sections(&mut self, ui: &mut egui::Ui) {
+        let id = egui::Id::new("trying-clues-sections");
+        let mut layout: SectionLayout = ui.data().get_temp(id).unwrap_or_else(SectionLayout::load);
+        let before = layout.clone();
+
+        for section in layout.order.clone() {
+            if section == Section::SelectedTile && self.highlights.len() != 1 {
+                continue;
+            }
+            if simplemode::enabled() && !section.is_simple() {
+                continue;
+            }
+            let response = egui::CollapsingHeader::new(RichText::new(tr_name(section)).heading())
+                .id_source(("trying-clues-section", section))
+                .open(Some(layout.open.contains(&section)))
+                .show(ui, |ui| match section {
+                    Section::Answers => self.gui_for_answers(ui),
+                    Section::SelectedTile => self.gui_for_selected_tile(ui),
+                    Section::Cheat => self.gui_for_cheats(ui),
+                    Section::Projection => self.gui_for_projection(ui),
+                    Section::Summary => self.gui_for_summary(ui),
+                    Section::Candidates => self.gui_for_candidates(ui),
+                    Section::Clues => self.gui_for_clues(ui),
+                    Section::Export => self.gui_for_export(ui),
+                    Section::MapFixes => self.gui_for_map_fixes(ui),
+                });
+            if response.header_response.clicked() && !layout.open.remove(&section) {
+                layout.open.insert(section);
+            }
+            ui.add_space(LAYOUT_SPACE);
+        }
+
+        egui::CollapsingHeader::new(tr("Order of the sections")).show(ui, |ui| {
+            let order = &mut layout.order;
+            let mut swap = None;
+            for (i, section) in order.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(i > 0, egui::Button::new("⬆")).clicked() {
+                        swap = Some(i - 1);
+                    }
+                    if ui
+                        .add_enabled(i + 1 < order.len(), egui::Button::new("⬇"))
+                        .clicked()
+                    {
+                        swap = Some(i);
+                    }
+                    ui.label(tr_name(section));
+                });
+            }
+            if let Some(i) = swap {
+                order.swap(i, i + 1);
+            }
+            if ui.button(tr("Reset")).clicked() {
+                layout.order = SectionLayout::default().order;
+            }
+        });
+
+        if layout != before {
+            layout.store();
+        }
+        ui.data().insert_temp(id, layout);
+    }
+
+    fn gui_for_cheats(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(tr("You are"));
+            egui::ComboBox::new("cheat-player-select", "")
+                .selected_text(&self.players.get(self.user).name)
+                .show_ui(ui, |ui| {
+                    for player in self.players.iter() {
+                        ui.selectable_value(&mut self.user, player.id, &player.name);
+                    }
+                });
+        });
+
+        ui.checkbox(&mut self.hide_own, tr("Hide my deductions"))
+            .on_hover_text(tr(
+                "Don't show anything about your clue, for playing on a shared screen",
+            ));
+        ui.checkbox(&mut self.selecting_region, tr("Select region for hints"));
+        if self.selecting_region {
+            ui.add(
+                Label::new(tr("Click or drag over tiles to add them to the region. Click again to remove them."))
+                    .wrap(true),
+            );
+        }
+        if !self.region.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label(trf(
+                    "Hints only use the {} tiles of the region.",
+                    &[&self.region.len()],
+                ));
+                if ui.button(tr("Clear")).clicked() {
+                    self.region.clear();
+                }
+            });
+        }
+
+        if ui
+            .checkbox(
+                &mut self.predict_questions,
+                tr("Predict the questions of opponents"),
+            )
+            .on_hover_text(tr(
+                "Assumes that opponents ask where an answer rules out the most clues",
+            ))
+            .changed()
+        {
+            self.hints.clear();
+            self.shown.remove(&HighlightChannel::Hint);
+        }
+
+        if self.hints.is_empty() {
+            ui.horizontal(|ui| {
+                if ui.button(tr("Refresh")).clicked() {
+                    self.calculate_hints();
+                }
+                ui.add(Label::new(tr("No hints available or map changed.")).wrap(true));
+            });
+        }
+
+        for hint in &self.hints {
+            ui.horizontal(|ui| {
+                show_button(ui, &mut self.shown, HighlightChannel::Hint, &hint.tiles);
+                ui.add(Label::new(&hint.text).wrap(true));
+            });
+        }
+
+        ui.add_space(LAYOUT_SPACE);
+        self.gui_for_win_chances(ui);
+    }
+
+    fn gui_for_win_chances(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(tr("Games"));
+            ui.add(egui::Slider::new(&mut self.win_samples, 10..=2000).logarithmic(true));
+        });
+        if ui
+            .button(tr("Estimate chances to win"))
+            .on_hover_text(tr(
+                "Play the rest of the game many times with clues that fit the answers",
+            ))
+            .clicked()
+        {
+            // The user's own clue stays out of it when it should be hidden.
+            let possible_clues = self
+                .players
+                .iter()
+                .map(|p| {
+                    if self.is_hidden(p.id) {
+                        self.deduced_clues.get(&p.id).cloned().unwrap_or_default()
+                    } else {
+                        self.possible_clues(p.id)
+                    }
+                })
+                .collect_vec();
+            self.estimation = Some(Estimation::new(&self.map, possible_clues, self.win_samples));
+            self.win_chances = None;
+        }
+        if let Some(estimation) = &mut self.estimation {
+            let done = estimation.step(winchance::GAMES_PER_FRAME);
+            ui.add(egui::ProgressBar::new(estimation.progress()).show_percentage());
+            if done {
+                self.win_chances = Some(estimation.chances());
+                self.estimation = None;
+            } else {
+                ui.ctx().request_repaint();
+            }
+        }
+        let Some(estimate) = &self.win_chances else {
+            return;
+        };
+        if estimate.games == 0 {
+            ui.add(Label::new(tr("No clues were found that fit all answers.")).wrap(true));
+            return;
+        }
+        Grid::new("win-chances").show(ui, |ui| {
+            for (player, chance) in self.players.iter().zip(&estimate.chances) {
+                ui.label(&player.name);
+                ui.label(format!("{:.0}%", chance * 100.0));
+                ui.end_row();
+            }
+        });
+        ui.label(trf("Out of {} games", &[&estimate.games]));
+    }
+
+    fn gui_for_projection(&mut self, ui: &mut egui::Ui) {
+        if self.projected == Some(self.user) {
+            // You can't project yourself, you already know your clue.
+            self.projected = None;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(tr("Opponent"));
+            let selected_text = match self.projected {
+                Some(id) => self.players.get(id).name.as_str(),
+                None => tr("None"),
+            };
+            egui::ComboBox::new("projection-player-select", "")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.projected, None, tr("None"));
+                    for player in self.players.iter().filter(|p| p.id != self.user) {
+                        ui.selectable_value(&mut self.projected, Some(player.id), &player.name);
+                    }
+                });
+        });
+
+        let Some(player) = self.projected else {
+            return;
+        };
+
+        let clues = self.possible_clues(player);
+
+        // For every clue collect the remaining tiles that are compatible with it.
+        let projections = clues
+            .into_iter()
+            .map(|clue| {
+                let tiles: Vec<Hex> = self
+                    .map
+                    .tiles
+                    .iter()
+                    .filter(|t| !t.small && self.map.clue_applies(clue, t.position))
+                    .map(|t| t.position)
+                    .collect();
+                (clue, tiles)
+            })
+            .sorted_by_key(|(_, tiles)| tiles.len());
+
+        ui.add(
+            Label::new(tr(
+                "Highlighted clues would reveal the cryptid if they were confirmed.",
+            ))
+            .wrap(true),
+        );
+        for (clue, tiles) in projections {
+            ui.horizontal(|ui| {
+                show_button(ui, &mut self.shown, HighlightChannel::Preview, &tiles);
+                let text = RichText::new(trf("{}: {} tiles", &[&clue, &tiles.len()]));
+                let text = if tiles.len() == 1 {
+                    text.strong().color(Color32::YELLOW)
+                } else {
+                    text
+                };
+                ui.add(Label::new(text).wrap(true));
+            });
+        }
+    }
+
+    fn gui_for_summary(&mut self, ui: &mut egui::Ui) {
+        if self.region.is_empty() {
+            self.partition = Partition::Pieces;
+        }
+        if !simplemode::enabled() {
+            let players = self
+                .players
+                .iter()
+                .filter(|p| !self.is_hidden(p.id))
+                .map(|p| (p.id, p.name.clone()))
+                .collect_vec();
+            ui.horizontal(|ui| {
+                ui.label(tr("Show eliminations of"));
+                let selected_text = match self.layer {
+                    Some(id) => self.players.get(id).name.as_str(),
+                    None => tr("Everyone"),
+                };
+                egui::ComboBox::new("layer-player-select", "")
+                    .selected_text(selected_text)
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.layer, None, tr("Everyone"));
+                        for (id, name) in players {
+                            ui.selectable_value(&mut self.layer, Some(id), name);
+                        }
+                    })
+                    .response
+                    .on_hover_text(tr(
+                        "See what the answers of one player rule out, as far as they can tell",
+                    ));
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label(tr("Divide by"));
+            egui::ComboBox::new("summary-partition-select", "")
+                .selected_text(tr_name(self.partition))
+                .show_ui(ui, |ui| {
+                    for partition in Partition::iter() {
+                        if partition == Partition::Region && self.region.is_empty() {
+                            continue;
+                        }
+                        ui.selectable_value(&mut self.partition, partition, tr_name(partition));
+                    }
+                });
+        });
+
+        // The same chances as the candidates, so both sections agree.
+        let Some(analysis) = &self.analysis else {
+            ui.label(tr("Counting combinations of clues…"));
+            return;
+        };
+        if analysis.combinations == 0 {
+            ui.label(tr("The cryptid can't be anywhere. Check the answers."));
+            return;
+        }
+        let chances = self
+            .partitions()
+            .into_iter()
+            .map(|(name, tiles)| {
+                let candidates = tiles
+                    .into_iter()
+                    .filter(|t| analysis.counts.contains_key(t))
+                    .collect_vec();
+                let chance: f64 = candidates.iter().map(|&t| analysis.chance(t)).sum();
+                (name, candidates, chance)
+            })
+            .collect_vec();
+
+        for (name, candidates, chance) in chances {
+            ui.horizontal(|ui| {
+                show_button(ui, &mut self.shown, HighlightChannel::Hint, &candidates);
+                let text = trf(
+                    "{}: {} tiles, {}%",
+                    &[&name, &candidates.len(), &format!("{:.0}", chance * 100.0)],
+                );
+                ui.add(Label::new(text).wrap(true));
+            });
+        }
+    }
+
+    /// Named groups of tiles, according to the selected partition.
+    fn partitions(&self) -> Vec<(String, Vec<Hex>)> {
+        let positions = self.map.tiles.iter().map(|t| t.position);
+        match self.partition {
+            Partition::Pieces => {
+                // Number the pieces like the map setup does, row by row.
+                let pieces = positions.into_group_map_by(|&t| piece_slot(t));
+                pieces
+                    .into_iter()
+                    .sorted_by_key(|([col, row], _)| (*row, *col))
+                    .enumerate()
+                    .map(|(i, (_, tiles))| (trf("Piece {}", &[&(i + 1)]), tiles))
+                    .collect()
+            }
+            Partition::Region => {
+                let (inside, outside) = positions.partition(|t| self.region.contains(t));
+                vec![
+                    (tr("Inside the region").to_owned(), inside),
+                    (tr("Outside the region").to_owned(), outside),
+                ]
+            }
+        }
+    }
+
+    fn gui_for_export(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            for format in ReportFormat::iter() {
+                if ui.button(tr_name(format)).clicked() {
+                    let report = self.report(format);
+                    let result = export::save(format.file_name(), report.as_bytes());
+                    self.export_notice =
+                        Some(result.unwrap_or_else(|e| trf("Export failed: {}", &[&e])));
+                }
+            }
+        });
+        if let Some(notice) = &self.export_notice {
+            ui.label(notice);
+        }
+    }
+
+    fn gui_for_map_fixes(&mut self, ui: &mut egui::Ui) {
+        ui.add(
+            Label::new(tr("Correct a tile that does not match the physical map. \
+                Answers are kept."))
+            .wrap(true),
+        );
+        let animals = self.map.animals();
+        let selection = match self.highlights.as_slice() {
+            [hex] => self.map.get_mut(*hex),
+            _ => None,
+        };
+        let Some(tile) = selection else {
+            ui.label(tr("Select a tile to fix it."));
+            return;
+        };
+        let before = (tile.terrain, tile.animal);
+        tile_editor(ui, tile, &animals);
+        if before != (tile.terrain, tile.animal) {
+            self.refill_auto_answers();
+            self.map_changed();
+        }
+    }
+
+    fn gui_for_answers(&mut self, ui: &mut egui::Ui) {
+        // Answers can only be placed when there is a single selection.
+        let selection = if self.highlights.len() == 1 {
+            self.highlights.first().copied()
+        } else {
+            None
+        };
+
+        self.gui_for_active_player(ui);
+
+        let mut guided = self.wizard.is_some();
+        if ui
+            .checkbox(&mut guided, tr("Guided turns"))
+            .on_hover_text(tr("Enter turns step by step instead of editing answers"))
+            .changed()
+        {
+            self.wizard = guided.then_some(Wizard::Turn);
+        }
+        if self.wizard.is_some() {
+            self.gui_for_wizard(ui, selection);
+            return;
+        }
+
+        if self.known_clue(self.user).is_some() {
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.auto_fill, tr("Fill in my answers"))
+                    .on_hover_text(tr(
+                        "Fill in my answer on the selected tile when someone asks me",
+                    ));
+                if ui
+                    .checkbox(&mut self.prefill_all, tr("Pre-fill all"))
+                    .on_hover_text(tr("Fill in my answers on every tile right away"))
+                    .changed()
+                    && self.prefill_all
+                {
+                    // Asked for explicitly, so this includes answers that were taken back.
+                    self.taken_back.remove(&self.user);
+                }
+            });
+            if self.prefill_all {
+                let positions = self.map.tiles.iter().map(|t| t.position).collect_vec();
+                for position in positions {
+                    self.auto_fill_answer(position);
+                }
+            } else if self.auto_fill && self.asker.is_some_and(|asker| asker != self.user) {
+                if let Some(hex) = selection {
+                    self.auto_fill_answer(hex);
+                }
+            }
+        }
+
+        ui.horizontal(|ui| {
+            ui.label(tr("Asked by"));
+            let name = |id: Option<PlayerID>| match id {
+                Some(id) => self.players.get(id).name.clone(),
+                None => tr("Nobody").to_owned(),
+            };
+            egui::ComboBox::new("answers-asker", "")
+                .selected_text(name(self.asker))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.asker, None, name(None));
+                    for player in self.players.iter() {
+                        ui.selectable_value(&mut self.asker, Some(player.id), &player.name);
+                    }
+                });
+        });
+        if let Some(owes) = self.owes_cube {
+            ui.add(
+                Label::new(trf(
+                    "{} was answered with a \"no\" and has to place a cube where their clue does not apply.",
+                    &[&self.players.get(owes).name],
+                ))
+                .wrap(true),
+            );
+        }
+        if let Some(warning) = &self.rule_warning {
+            ui.colored_label(ui.visuals().warn_fg_color, warning);
+        }
+
+        let mut changes = Vec::new();
+        let mut edited = None;
+        if let Some(selected_tile) = selection.and_then(|hex| self.map.get_mut(hex)) {
+            ui.label(tr("Set answers for the selected tile."));
+            let answers_before = selected_tile.answers.clone();
+            Grid::new("answer-grid").show(ui, |ui| {
+                for player in self.players.iter() {
+                    let answer = selected_tile.answers.entry(player.id).or_default();
+                    let answer_before = *answer;
+                    ui.label(&player.name);
+                    egui::ComboBox::new(format!("player-answer-{:?}", player.id), "")
+                        .selected_text(tr_name(*answer))
+                        .show_ui(ui, |ui| {
+                            for a in Answer::iter() {
+                                ui.selectable_value(answer, a, tr_name(a));
+                            }
+                        });
+
+                    if *answer != answer_before {
+                        changes.push((player.id, *answer));
+                    }
+                    let position = selected_tile.position;
+                    let auto_answers = self.auto_answers.entry(player.id).or_default();
+                    if *answer != answer_before {
+                        // The answer was entered by hand.
+                        if auto_answers.remove(&position) && *answer == Answer::Unknown {
+                            self.taken_back
+                                .entry(player.id)
+                                .or_default()
+                                .insert(position);
+                        }
+                    }
+                    if auto_answers.contains(&position) {
+                        ui.label(tr("Filled in"));
+                    }
+                    ui.end_row();
+                }
+            });
+            edited = Some((selected_tile.position, answers_before));
+        } else {
+            ui.label(tr("Select a tile to place anwers."));
+        }
+
+        if let Some((position, answers_before)) = edited {
+            for (player, answer) in changes {
+                self.record_question(position, &answers_before, player, answer, self.asker);
+            }
+        }
+    }
+
+    /// Buttons in the colors of the players. While a player is active, clicking a tile places
+    /// their answer there, or takes it back.
+    fn gui_for_active_player(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(tr("Active player"));
+            if ui
+                .selectable_label(self.active.is_none(), tr("Nobody"))
+                .clicked()
+            {
+                self.active = None;
+            }
+            for player in self.players.iter() {
+                let fill = Color32::from(player.color);
+                let [r, g, b, a] = theme::readable_on(fill).rgba_u8();
+                let text =
+                    RichText::new(&player.name).color(Color32::from_rgba_unmultiplied(r, g, b, a));
+                let selected = self.active == Some(player.id);
+                let stroke = if selected {
+                    (3.0, ui.visuals().strong_text_color())
+                } else {
+                    (1.0, ui.visuals().weak_text_color())
+                };
+                if ui
+                    .add(egui::Button::new(text).fill(fill).stroke(stroke))
+                    .clicked()
+                {
+                    self.active = (!selected).then_some(player.id);
+                }
+            }
+        });
+        if self.active.is_some() {
+            ui.horizontal(|ui| {
+                ui.label(tr("Clicking a tile answers"));
+                for answer in [Answer::Yes, Answer::No] {
+                    ui.selectable_value(&mut self.active_answer, answer, tr_name(answer));
+                }
+            });
+        }
+    }
+
+    /// Everything about the selected tile: what is on it, the answers and the clues that
+    /// allow the cryptid there.
+    fn gui_for_selected_tile(&self, ui: &mut egui::Ui) {
+        let [hex] = self.highlights.as_slice() else {
+            return;
+        };
+        let Some(tile) = self.map.get(*hex) else {
+            return;
+        };
+
+        ui.label(describe_tile(tile));
+        let reasons = self.elimination_reasons(*hex);
+        if !reasons.is_empty() {
+            ui.label(tr("The cryptid can't be here:"));
+            for reason in reasons {
+                ui.add(Label::new(format!("• {reason}")).wrap(true));
+            }
+        }
+        Grid::new("selected-tile-answers").show(ui, |ui| {
+            for player in self.players.iter() {
+                let answer = tile.answers.get(&player.id).copied().unwrap_or_default();
+                ui.label(&player.name);
+                ui.label(tr_name(answer));
+                ui.end_row();
+            }
+        });
+        for player in self.players.iter() {
+            if self.is_hidden(player.id) {
+                continue;
+            }
+            let clues = self
+                .possible_clues(player.id)
+                .into_iter()
+                .filter(|&c| self.map.clue_applies(c, *hex))
+                .collect_vec();
+            egui::CollapsingHeader::new(trf(
+                "{}: {} clues allow the cryptid here",
+                &[&player.name, &clues.len()],
+            ))
+            .id_source(("selected-tile-clues", player.id))
+            .show(ui, |ui| {
+                for clue in clues {
+                    ui.add(Label::new(format!("{clue}")).wrap(true));
+                }
+            });
+        }
+    }
+
+    fn gui_for_wizard(&mut self, ui: &mut egui::Ui, selection: Option<Hex>) {
+        let Some(step) = self.wizard else {
+            return;
+        };
+        let name = |id: PlayerID| self.players.get(id).name.clone();
+        let mut next = step;
+
+        match step {
+            Wizard::Turn => {
+                ui.label(tr("Whose turn is it?"));
+                ui.horizontal_wrapped(|ui| {
+                    for player in self.players.iter() {
+                        if ui.button(&player.name).clicked() {
+                            next = Wizard::Action { player: player.id };
+                        }
+                    }
+                });
+            }
+            Wizard::Action { player } => {
+                ui.label(trf("Does {} ask a question or search?", &[&name(player)]));
+                ui.horizontal(|ui| {
+                    if ui.button(tr("Question")).clicked() {
+                        next = Wizard::Tile {
+                            player,
+                            search: false,
+                        };
+                    }
+                    if ui.button(tr("Search")).clicked() {
+                        next = Wizard::Tile {
+                            player,
+                            search: true,
+                        };
+                    }
+                });
+            }
+            Wizard::Tile { player, search } => {
+                ui.label(tr("Select the tile on the map."));
+                if let Some(tile) = selection {
+                    if ui
+                        .button(trf("Continue with {}", &[&coordinate(tile)]))
+                        .clicked()
+                    {
+                        next = if search {
+                            self.enter_answer(tile, player, Answer::Yes, None);
+                            Wizard::Search {
+                                player,
+                                tile,
+                                answered: 0,
+                            }
+                        } else {
+                            Wizard::Ask { player, tile }
+                        };
+                    }
+                }
+            }
+            Wizard::Ask { player, tile } => {
+                ui.label(trf(
+                    "Whom did {} ask about {}, and what was the answer?",
+                    &[&name(player), &coordinate(tile)],
+                ));
+                let mut given = None;
+                Grid::new("wizard-ask-grid").show(ui, |ui| {
+                    for other in self.players.iter().filter(|p| p.id != player) {
+                        ui.label(&other.name);
+                        for answer in [Answer::Yes, Answer::No] {
+                            if ui.button(tr_name(answer)).clicked() {
+                                given = Some((other.id, answer));
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some((other, answer)) = given {
+                    self.enter_answer(tile, other, answer, Some(player));
+                    next = match answer {
+                        Answer::No => Wizard::Cube { player },
+                        _ => Wizard::Turn,
+                    };
+                }
+            }
+            Wizard::Cube { player } => {
+                ui.add(
+                    Label::new(trf(
+                        "Select the tile where {} placed their cube.",
+                        &[&name(player)],
+                    ))
+                    .wrap(true),
+                );
+                if let Some(tile) = selection {
+                    if ui.button(trf("Cube on {}", &[&coordinate(tile)])).clicked() {
+                        self.enter_answer(tile, player, Answer::No, None);
+                        next = Wizard::Turn;
+                    }
+                }
+            }
+            Wizard::Search {
+                player,
+                tile,
+                answered,
+            } => {
+                // The others answer in turn order, starting after the searching player.
+                let ids = self.players.iter().map(|p| p.id).collect_vec();
+                let order = ids
+                    .iter()
+                    .copied()
+                    .cycle()
+                    .skip_while(|&p| p != player)
+                    .skip(1)
+                    .take(ids.len() - 1)
+                    .collect_vec();
+                match order.get(answered) {
+                    None => next = Wizard::Found { tile },
+                    Some(&other) => {
+                        ui.label(trf("What does {} answer?", &[&name(other)]));
+                        ui.horizontal(|ui| {
+                            if ui.button(tr("Yes")).clicked() {
+                                self.enter_answer(tile, other, Answer::Yes, None);
+                                next = Wizard::Search {
+                                    player,
+                                    tile,
+                                    answered: answered + 1,
+                                };
+                            }
+                            if ui.button(tr("No")).clicked() {
+                                self.enter_answer(tile, other, Answer::No, None);
+                                next = Wizard::Turn;
+                            }
+                        });
+                    }
+                }
+            }
+            Wizard::Found { tile } => {
+                ui.label(trf("The cryptid is on {}!", &[&coordinate(tile)]));
+                if ui.button(tr("Next turn")).clicked() {
+                    next = Wizard::Turn;
+                }
+            }
+        }
+
+        if step != Wizard::Turn && ui.button(tr("Start the turn over")).clicked() {
+            next = Wizard::Turn;
+        }
+        self.wizard = Some(next);
+    }
+
+    fn set_answer(&mut self, player: PlayerID, hex: Hex, answer: Answer) {
+        if let Some(tile) = self.map.get_mut(hex) {
+            tile.answers.insert(player, answer);
+        }
+    }
+
+    /// Set an answer from the map instead of the gui. The gui only notices changes it made
+    /// itself, so this does what follows from a new answer right away.
+    fn answer(&mut self, hex: Hex, player: PlayerID, answer: Answer) {
+        let answers_before = self.answers();
+        self.enter_answer(hex, player, answer, self.asker);
+        self.record_answers(&answers_before);
+        self.map_changed();
+    }
+
+    /// Set an answer and check it against the rules, like the answer grid does.
+    fn enter_answer(
+        &mut self,
+        hex: Hex,
+        player: PlayerID,
+        answer: Answer,
+        asker: Option<PlayerID>,
+    ) {
+        let Some(answers_before) = self.map.get(hex).map(|t| t.answers.clone()) else {
+            return;
+        };
+        self.set_answer(player, hex, answer);
+        self.answered_by_hand(player, hex, answer);
+        self.record_question(hex, &answers_before, player, answer, asker);
+    }
+
+    /// Remember the question that lead to the answer, and check that it follows the rules.
+    /// Answers without an asker, like those of a search, are only checked.
+    fn record_question(
+        &mut self,
+        position: Hex,
+        answers_before: &BTreeMap<PlayerID, Answer>,
+        player: PlayerID,
+        answer: Answer,
+        asker: Option<PlayerID>,
+    ) {
+        self.rule_warning = None;
+        if answer == Answer::Unknown {
+            return;
+        }
+
+        let name = |id: PlayerID| self.players.get(id).name.clone();
+        let cube = answers_before
+            .iter()
+            .find(|(&id, &a)| id != player && a == Answer::No);
+        if let Some((&other, _)) = cube {
+            self.rule_warning = Some(trf(
+                "{} already placed a cube on {}. Nobody may ask about it or place a cube there.",
+                &[&name(other), &coordinate(position)],
+            ));
+        }
+        if self.possible_masks(player).next().is_none() {
+            self.rule_warning = Some(trf(
+                "No clue allows all answers of {} anymore. The answer on {} contradicts the earlier ones.",
+                &[&name(player), &coordinate(position)],
+            ));
+        }
+
+        if self.owes_cube == Some(player) && answer == Answer::No {
+            // This is the cube the player had to place.
+            self.owes_cube = None;
+            return;
+        }
+        let Some(asker) = asker.filter(|&a| a != player) else {
+            return;
+        };
+        if let Some(owes) = self.owes_cube {
+            self.rule_warning = Some(trf(
+                "{} has to place a cube before the next question.",
+                &[&name(owes)],
+            ));
+        }
+        self.questions.push(Question {
+            asker,
+            answerer: player,
+            position,
+            answer,
+        });
+        if answer == Answer::No {
+            self.owes_cube = Some(asker);
+        }
+    }
+
+    /// The tiles of the region changed on the map, outside of the gui. Hints and the analysis
+    /// only looked at the old region.
+    fn region_changed(&mut self) {
+        self.hints.clear();
+        self.analysis = None;
+        self.shown.remove(&HighlightChannel::Hint);
+    }
+
+    /// The answer of the player on the tile is no longer one the app filled in. Taking it back
+    /// keeps the app from filling it in again.
+    fn answered_by_hand(&mut self, player: PlayerID, position: Hex, answer: Answer) {
+        let filled_in = self
+            .auto_answers
+            .get_mut(&player)
+            .is_some_and(|tiles| tiles.remove(&position));
+        if filled_in && answer == Answer::Unknown {
+            self.taken_back.entry(player).or_default().insert(position);
+        }
+    }
+
+    /// Set the answer of the user on the tile according to their known clue,
+    /// unless they already answered there or took back an answer the app filled in.
+    fn auto_fill_answer(&mut self, position: Hex) {
+        self.auto_fill_answer_of(self.user, position);
+    }
+
+    fn auto_fill_answer_of(&mut self, player: PlayerID, position: Hex) {
+        let Some(clue) = self.known_clue(player) else {
+            return;
+        };
+        if self
+            .taken_back
+            .get(&player)
+            .is_some_and(|tiles| tiles.contains(&position))
+        {
+            return;
+        }
+        let applies = self.map.clue_applies(clue, position);
+        let Some(tile) = self.map.get_mut(position) else {
+            return;
+        };
+
+        let answer = tile.answers.entry(player).or_default();
+        if *answer == Answer::Unknown {
+            *answer = if applies { Answer::Yes } else { Answer::No };
+            self.auto_answers
+                .entry(player)
+                .or_default()
+                .insert(position);
+        }
+    }
+
+    /// Fill in the answers again that were filled in by the app, after the map changed.
+    fn refill_auto_answers(&mut self) {
+        for (player, positions) in std::mem::take(&mut self.auto_answers) {
+            for position in positions {
+                if let Some(tile) = self.map.get_mut(position) {
+                    tile.answers.insert(player, Answer::Unknown);
+                }
+                self.auto_fill_answer_of(player, position);
+            }
+        }
+    }
+
+    fn gui_for_clues(&mut self, ui: &mut egui::Ui) {
+        let remaining_tiles = self.map.tiles.iter().filter(|t| !t.small).count();
+
+        ui.label(trf("{} tiles remain.", &[&remaining_tiles]));
+
+        let mut bulk = None;
+        for player in self.players.iter().map(|p| p.id) {
+            ui.separator();
+            if self.is_hidden(player) {
+                ui.label(trf("{}: hidden", &[&self.players.get(player).name]));
+                continue;
+            }
+            // Dont add and remove the clue for a player, just switch to deduction mode, remembering the clue.
+            {
+                let clue = self.clues.entry(player).or_insert(
+                    ClueKind::within(ClueTarget::Terrain(Terrain::Desert), &self.map.rules).into(),
+                );
+                let known = self.known_clues.entry(player).or_default();
+                let with_inverted = self.with_inverted.entry(player).or_default();
+                ui.horizontal(|ui| {
+                    ui.label(self.players.get(player).name.to_string());
+                    ui.checkbox(known, tr("Known Clue"));
+                    ui.checkbox(with_inverted, tr("Inverted clues"));
+                });
+                if *known {
+                    clue_editor(ui, player, clue, *with_inverted, &self.map);
+                } else {
+                    // Show deduced clues.
+                    let clues = self.deduced_clues.entry(player).or_default();
+                    egui::CollapsingHeader::new(trf("{} possible clues", &[&clues.len()]))
+                        .id_source(player)
+                        .show(ui, |ui| {
+                            for &mut clue in clues {
+                                let focused = self.focus == Some(clue);
+                                if ui
+                                    .selectable_label(focused, format!("{clue}"))
+                                    .on_hover_text(tr("Show where this clue applies"))
+                                    .clicked()
+                                {
+                                    self.focus = (!focused).then_some(clue);
+                                }
+                            }
+                        });
+                }
+            }
+            ui.horizontal_wrapped(|ui| {
+                if ui.small_button(tr("Clear all answers")).clicked() {
+                    bulk = Some(Bulk::Clear(player));
+                }
+                if ui
+                    .add_enabled(
+                        !self.region.is_empty(),
+                        egui::Button::new(tr("Fill in No in region")).small(),
+                    )
+                    .on_hover_text(tr("Answer no on every unanswered tile of the region"))
+                    .on_disabled_hover_text(tr("Select a region first"))
+                    .clicked()
+                {
+                    bulk = Some(Bulk::NoInRegion(player));
+                }
+                egui::ComboBox::new(("copy-answers", player), "")
+                    .selected_text(tr("Copy answers from"))
+                    .show_ui(ui, |ui| {
+                        for other in self.players.iter().filter(|p| p.id != player) {
+                            if ui.selectable_label(false, &other.name).clicked() {
+                                bulk = Some(Bulk::Copy {
+                                    from: other.id,
+                                    to: player,
+                                });
+                            }
+                        }
+                    });
+            });
+        }
+        ui.separator();
+        if ui
+            .add_enabled(
+                !self.bulk_undo.is_empty(),
+                egui::Button::new(tr("Undo")).small(),
+            )
+            .on_hover_text(tr("Take back the last change of many answers at once"))
+            .clicked()
+        {
+            self.undo_bulk();
+        }
+        if let Some(bulk) = bulk {
+            self.apply_bulk(bulk);
+        }
     }
 
-    fn gui(&mut self, ui: &mut egui::Ui) -> bool {
-        let clues_before = self.clues.clone();
-        let known_clues_before = self.known_clues.clone();
-        let tiles_before = self.tiles().to_vec();
-        let user_before = self.user;
-        let with_inverted_before = self.with_inverted;
-
-        ui.checkbox(&mut self.with_inverted, "Enable inverted clues");
-
-        self.gui_for_answers(ui);
-        ui.add_space(LAYOUT_SPACE);
-        self.gui_for_cheats(ui);
-        ui.add_space(LAYOUT_SPACE);
-        self.gui_for_clues(ui);
-
-        let clues_changed = clues_before != self.clues;
-        let known_clues_changed = known_clues_before != self.known_clues;
-        let tiles_changed = !itertools::equal(&tiles_before, self.tiles());
-        let user_changed = user_before != self.user;
-        let with_inverted_changed = with_inverted_before != self.with_inverted;
-
-        if tiles_changed || with_inverted_changed {
-            // The tiles i.e. the answers have changed so we need to think about the possible clues again.
-            self.deduce_clues();
+    /// Change many answers at once. The answers before can be restored with
+    /// [Self::undo_bulk]. Both are recorded in the history like answers on single tiles.
+    fn apply_bulk(&mut self, bulk: Bulk) {
+        let player = match bulk {
+            Bulk::Clear(player) | Bulk::NoInRegion(player) | Bulk::Copy { to: player, .. } => {
+                player
+            }
+        };
+        if self.bulk_undo.len() == BULK_UNDO_LENGTH {
+            self.bulk_undo.remove(0);
         }
-
-        if clues_changed || known_clues_changed || tiles_changed || with_inverted_changed {
-            self.update_map_from_clues();
+        self.bulk_undo.push(BulkUndo {
+            answers: self.answers(),
+            auto_answers: self.auto_answers.clone(),
+            taken_back: self.taken_back.clone(),
+        });
+        let region = self.region.clone();
+        let mut changed = Vec::new();
+        for tile in &mut self.map.tiles {
+            let before = tile.answers.get(&player).copied().unwrap_or_default();
+            let after = match bulk {
+                Bulk::Clear(_) => Answer::Unknown,
+                Bulk::NoInRegion(_)
+                    if before == Answer::Unknown && region.contains(&tile.position) =>
+                {
+                    Answer::No
+                }
+                Bulk::NoInRegion(_) => before,
+                Bulk::Copy { from, .. } => tile.answers.get(&from).copied().unwrap_or_default(),
+            };
+            if after != before {
+                tile.answers.insert(player, after);
+                changed.push((tile.position, after));
+            }
         }
-
-        if clues_changed
-            || known_clues_changed
-            || tiles_changed
-            || user_changed
-            || with_inverted_changed
-        {
-            // Something changed that influences the hints. Recomputing those is expensive,
-            // so just clear them. The user can refresh them by pressing a button.
-            self.hints.clear();
+        for (position, answer) in changed {
+            self.answered_by_hand(player, position, answer);
         }
-
-        false
     }
 
-    fn highlights(&self) -> Vec<Hex> {
-        self.highlights.to_vec()
+    /// Restore the answers from before the last change of many answers at once.
+    fn undo_bulk(&mut self) {
+        let Some(undo) = self.bulk_undo.pop() else {
+            return;
+        };
+        for (tile, answers) in self.map.tiles.iter_mut().zip(undo.answers) {
+            tile.answers = answers;
+        }
+        self.auto_answers = undo.auto_answers;
+        self.taken_back = undo.taken_back;
     }
 
-    fn click(&mut self, hex: Hex) {
-        self.highlights = self
-            .map
-            .get(hex)
-            .is_some()
-            .then_some(hex)
-            .into_iter()
-            .collect();
+    /// The map of the game, with its house rules.
+    pub fn map(&self) -> &Map {
+        &self.map
     }
 
-    fn players(&self) -> &PlayerList {
-        &self.players
+    /// The clue of the player, if it is known.
+    pub fn known_clue(&self, player: PlayerID) -> Option<Clue> {
+        if self.known_clues.get(&player).copied().unwrap_or_default() {
+            self.clues.get(&player).copied()
+        } else {
+            None
+        }
     }
-}
 
-impl TryingClues {
-    fn gui_for_cheats(&mut self, ui: &mut egui::Ui) {
-        ui.heading("Cheat");
+    fn gui_for_candidates(&mut self, ui: &mut egui::Ui) {
+        let text = if self.explanation.is_some() {
+            tr("Hide the explanation")
+        } else {
+            tr("Explain")
+        };
+        if ui
+            .button(text)
+            .on_hover_text(tr("Show step by step how the answers lead to these tiles"))
+            .clicked()
+        {
+            self.explanation = match self.explanation {
+                Some(_) => None,
+                None => Some(self.explain()),
+            };
+        }
+        if let Some(explanation) = &self.explanation {
+            for step in explanation {
+                ui.add(Label::new(step).wrap(true));
+            }
+            ui.separator();
+        }
+
         ui.horizontal(|ui| {
-            ui.label("You are");
-            egui::ComboBox::new("cheat-player-select", "")
-                .selected_text(&self.players.get(self.user).name)
+            ui.label(tr("Label tiles with"));
+            egui::ComboBox::new("candidates-tile-label", "")
+                .selected_text(tr_name(self.tile_label))
                 .show_ui(ui, |ui| {
-                    for player in self.players.iter() {
-                        ui.selectable_value(&mut self.user, player.id, &player.name);
+                    for label in TileLabel::iter() {
+                        ui.selectable_value(&mut self.tile_label, label, tr_name(label));
                     }
                 });
         });
-
-        if self.hints.is_empty() {
-            ui.horizontal(|ui| {
-                if ui.button("Refresh").clicked() {
-                    self.calculate_hints();
-                }
-                ui.add(Label::new("No hints available or map changed.").wrap(true));
-            });
+        let Some(analysis) = &self.analysis else {
+            ui.label(tr("Counting combinations of clues…"));
+            return;
+        };
+        ui.add(
+            Label::new(trf(
+                "{} combinations of one clue per player point to exactly one tile.",
+                &[&analysis.combinations],
+            ))
+            .wrap(true),
+        );
+        if !analysis.complete {
+            ui.add(
+                Label::new(tr("There are too many combinations to count them all yet.")).wrap(true),
+            );
         }
 
-        for hint in &self.hints {
+        for (hex, count) in analysis.candidates().into_iter().take(CANDIDATE_COUNT) {
             ui.horizontal(|ui| {
-                if ui.button("Show").clicked() {
-                    self.highlights = hint.tiles.to_vec();
-                }
-                ui.add(Label::new(&hint.text).wrap(true));
+                show_button(ui, &mut self.shown, HighlightChannel::Hint, &[hex]);
+                let text = trf(
+                    "{}: {} combinations, {}%",
+                    &[
+                        &coordinate(hex),
+                        &count,
+                        &format!("{:.0}", analysis.chance(hex) * 100.0),
+                    ],
+                );
+                ui.add(Label::new(text).wrap(true));
             });
         }
     }
 
-    fn gui_for_answers(&mut self, ui: &mut egui::Ui) {
-        // Answers can only be placed when there is a single selection.
-        let selection = if self.highlights.len() == 1 {
-            self.highlights.first().copied()
-        } else {
-            None
+    /// Explain the deductions in words: what each answer ruled out, which clues are left and
+    /// where the cryptid can be because of that.
+    fn explain(&self) -> Vec<String> {
+        /// Listing more clues or tiles than this is not helpful.
+        const LIST_AT_MOST: usize = 5;
+        let list = |items: Vec<String>| {
+            if items.len() <= LIST_AT_MOST {
+                items.join(", ")
+            } else {
+                let shown = items[..LIST_AT_MOST].join(", ");
+                trf("{} and {} more", &[&shown, &(items.len() - LIST_AT_MOST)])
+            }
         };
 
-        ui.heading("Answers");
-        if let Some(selected_tile) = selection.and_then(|hex| self.map.get_mut(hex)) {
-            ui.label("Set answers for the selected tile.");
-            Grid::new("answer-grid").show(ui, |ui| {
-                for player in self.players.iter() {
-                    let answer = selected_tile.answers.entry(player.id).or_default();
-                    ui.label(&player.name);
-                    egui::ComboBox::new(format!("player-answer-{:?}", player.id), "")
-                        .selected_text(format!("{answer}"))
-                        .show_ui(ui, |ui| {
-                            for a in Answer::iter() {
-                                ui.selectable_value(answer, a, format!("{a}"));
-                            }
-                        });
-                    ui.end_row();
-                }
-            });
-        } else {
-            ui.label("Select a tile to place anwers.");
+        let mut steps = Vec::new();
+        for player in self.players.iter().filter(|p| !self.is_hidden(p.id)) {
+            if let Some(clue) = self.known_clue(player.id) {
+                steps.push(trf("The clue of {} is known: {}.", &[&player.name, &clue]));
+                continue;
+            }
+
+            let mut clues = Clue::all(
+                &self.map.animals(),
+                &self.map.structure_colors(),
+                &self.map.structure_kinds(),
+                self.may_be_inverted(player.id),
+                &self.map.rules,
+            )
+            .collect_vec();
+            // Answers in the order they were given, then any that are not in the history.
+            let answered = self
+                .map
+                .tiles
+                .iter()
+                .filter_map(|t| Some((t.position, *t.answers.get(&player.id)?)))
+                .filter(|(_, a)| *a != Answer::Unknown)
+                .sorted_by_key(|(hex, _)| {
+                    self.history
+                        .iter()
+                        .position(|e| e.player == player.id && e.position == *hex)
+                        .unwrap_or(usize::MAX)
+                });
+            for (hex, answer) in answered {
+                let (kept, ruled_out): (Vec<_>, Vec<_>) = clues
+                    .into_iter()
+                    .partition(|&c| self.map.clue_applies(c, hex) == (answer == Answer::Yes));
+                clues = kept;
+                let ruled_out = ruled_out.iter().map(|c| c.to_string()).collect_vec();
+                steps.push(if ruled_out.is_empty() {
+                    trf(
+                        "{} answered {} on {}, which rules out no further clues.",
+                        &[&player.name, &tr_name(answer), &coordinate(hex)],
+                    )
+                } else {
+                    trf(
+                        "{} answered {} on {}, which rules out {} clues: {}.",
+                        &[
+                            &player.name,
+                            &tr_name(answer),
+                            &coordinate(hex),
+                            &ruled_out.len(),
+                            &list(ruled_out),
+                        ],
+                    )
+                });
+            }
+            let left = clues.iter().map(|c| c.to_string()).collect_vec();
+            steps.push(trf(
+                "So {} has one of {} clues: {}.",
+                &[&player.name, &left.len(), &list(left)],
+            ));
         }
+
+        let candidates = self
+            .map
+            .tiles
+            .iter()
+            .filter(|t| !t.small)
+            .map(|t| coordinate(t.position))
+            .collect_vec();
+        steps.push(trf(
+            "Therefore the cryptid is on one of {} tiles: {}.",
+            &[&candidates.len(), &list(candidates)],
+        ));
+        steps
     }
 
-    fn gui_for_clues(&mut self, ui: &mut egui::Ui) {
-        let remaining_tiles = self.map.0.iter().filter(|t| !t.small).count();
+    /// Count the consistent combinations of clues.
+    fn analyze(&mut self) {
+        let possible_clues = self
+            .players
+            .iter()
+            .filter(|p| !self.is_hidden(p.id))
+            .map(|p| self.possible_clues(p.id))
+            .collect_vec();
+        self.analysis = Some(Analysis::new(&self.map, &possible_clues));
+    }
 
-        ui.heading("Clues");
-        ui.label(format!("{remaining_tiles} tiles remain."));
+    /// The clues a player might have. This is the known clue, if there is one.
+    fn possible_clues(&self, player: PlayerID) -> Vec<Clue> {
+        match self.known_clue(player) {
+            Some(clue) => vec![clue],
+            None => self.deduced_clues.get(&player).cloned().unwrap_or_default(),
+        }
+    }
 
-        for player in self.players.iter().map(|p| p.id) {
-            ui.separator();
-            // Dont add and remove the clue for a player, just switch to deduction mode, remembering the clue.
-            {
-                let clue = self
-                    .clues
-                    .entry(player)
-                    .or_insert(ClueKind::Terrain(Terrain::Desert).into());
-                let known = self.known_clues.entry(player).or_default();
-                ui.horizontal(|ui| {
-                    ui.label(self.players.get(player).name.to_string());
-                    ui.checkbox(known, "Known Clue");
-                });
-                if *known {
-                    // Change clue type
-                    egui::ComboBox::new(format!("combobox-clue-{player:?}"), "")
-                        .selected_text("Edit type")
-                        .show_ui(ui, |ui| {
-                            if ui.button("Within one space of terrain").clicked() {
-                                *clue = ClueKind::Terrain(Terrain::Desert).into();
-                            }
-                            if ui.button("One of two terrains").clicked() {
-                                *clue =
-                                    ClueKind::TwoTerrains(Terrain::Desert, Terrain::Forest).into();
-                            }
-                            if ui.button("Within one space of either animal").clicked() {
-                                *clue = ClueKind::EitherAnimal.into();
-                            }
-                            if ui.button("Within two spaces of animal").clicked() {
-                                *clue = ClueKind::Animal(Animal::Bear).into();
-                            }
-                            if ui.button("Within two spaces of structure type").clicked() {
-                                *clue = ClueKind::StructureKind(StructureKind::Shack).into();
-                            }
-                            if ui
-                                .button("Within three spaces of structure color")
-                                .clicked()
-                            {
-                                *clue = ClueKind::StructureColor(StructureColor::Black).into();
-                            }
-                        });
+    /// Players whose possible clues all rule out the position. A hidden player is left out,
+    /// because the tiles it rules out tell its clue.
+    fn eliminating_players(&self, position: Hex) -> Vec<PlayerID> {
+        self.players
+            .iter()
+            .map(|p| p.id)
+            .filter(|&p| !self.is_hidden(p))
+            .filter(|&p| {
+                !self
+                    .possible_clues(p)
+                    .into_iter()
+                    .any(|c| self.map.clue_applies(c, position))
+            })
+            .collect()
+    }
 
-                    // Edit clue
-                    match &mut clue.kind {
-                        ClueKind::Terrain(terrain) => {
-                            ui.horizontal(|ui| {
-                                ui.label("Within one space of");
-                                terrain_switcher(format!("terrain-{player:?}"), ui, terrain);
-                            });
-                        }
-                        ClueKind::TwoTerrains(a, b) => {
-                            ui.horizontal(|ui| {
-                                ui.label("On");
-                                terrain_switcher(format!("terrain-{player:?}-a"), ui, a);
-                                ui.label("or");
-                                terrain_switcher(format!("terrain-{player:?}-b"), ui, b);
-                            });
-                        }
-                        ClueKind::EitherAnimal => {
-                            ui.label("Within one space of either animal");
-                        }
-                        ClueKind::Animal(animal) => {
-                            ui.horizontal(|ui| {
-                                ui.label("Within two spaces of");
-                                egui::ComboBox::new(format!("animal-{player:?}"), "Territory")
-                                    .selected_text(format!("{animal}"))
-                                    .show_ui(ui, |ui| {
-                                        for a in Animal::iter() {
-                                            ui.selectable_value(animal, a, format!("{a}"));
-                                        }
-                                    });
-                            });
-                        }
-                        ClueKind::StructureKind(kind) => {
-                            ui.horizontal(|ui| {
-                                ui.label("Within two spaces of");
-                                egui::ComboBox::new(format!("structurekind-{player:?}"), "")
-                                    .selected_text(format!("{kind}"))
-                                    .show_ui(ui, |ui| {
-                                        for k in StructureKind::iter() {
-                                            ui.selectable_value(kind, k, format!("{k}"));
-                                        }
-                                    });
-                            });
-                        }
-                        ClueKind::StructureColor(color) => {
-                            ui.horizontal(|ui| {
-                                ui.label("Within three spaces of");
-                                egui::ComboBox::new(
-                                    format!("structurecolor-{player:?}"),
-                                    "structure",
-                                )
-                                .selected_text(format!("{color}"))
-                                .show_ui(ui, |ui| {
-                                    for c in StructureColor::iter() {
-                                        ui.selectable_value(color, c, format!("{c}"));
-                                    }
-                                });
-                            });
-                        }
+    /// Generate a report of the current deductions: Possible clues for every player
+    /// and the players ruling out each tile. The clue of a hidden player is left out.
+    fn report(&self, format: ReportFormat) -> String {
+        let mut out = String::new();
+        let names = |ids: Vec<PlayerID>| {
+            ids.into_iter()
+                .map(|id| self.players.get(id).name.as_str())
+                .join(", ")
+        };
+
+        match format {
+            ReportFormat::Csv => {
+                out.push_str("player,possible clue\r\n");
+                for player in self.players.iter().filter(|p| !self.is_hidden(p.id)) {
+                    for clue in self.possible_clues(player.id) {
+                        out.push_str(&format!(
+                            "{},{}\r\n",
+                            csv_field(&player.name),
+                            csv_field(&clue.to_string())
+                        ));
                     }
-                } else {
-                    // Show deduced clues.
-                    let clues = self.deduced_clues.entry(player).or_default();
-                    egui::CollapsingHeader::new(format!("{} possible clues", clues.len()))
-                        .id_source(player)
-                        .show(ui, |ui| {
-                            for clue in clues {
-                                ui.label(format!("{clue}"));
-                            }
-                        });
+                }
+                out.push_str("\r\ntile,terrain,eliminated by\r\n");
+                for tile in &self.map.tiles {
+                    let eliminated_by = names(self.eliminating_players(tile.position));
+                    out.push_str(&format!(
+                        "{},{},{}\r\n",
+                        csv_field(&coordinate(tile.position)),
+                        csv_field(&tile.terrain.to_string()),
+                        csv_field(&eliminated_by)
+                    ));
+                }
+            }
+            ReportFormat::Markdown => {
+                out.push_str(&format!(
+                    "# {}\n\n## {}\n",
+                    tr("Deduction report"),
+                    tr("Possible clues")
+                ));
+                for player in self.players.iter() {
+                    out.push_str(&format!("\n### {}\n\n", markdown_line(&player.name)));
+                    if self.is_hidden(player.id) {
+                        out.push_str(&format!("{}\n", tr("Hidden")));
+                        continue;
+                    }
+                    for clue in self.possible_clues(player.id) {
+                        out.push_str(&format!("- {}\n", markdown_line(&clue.to_string())));
+                    }
+                }
+                out.push_str(&format!(
+                    "\n## {}\n\n| {} | {} | {} |\n|---|---|---|\n",
+                    tr("Tiles"),
+                    tr("Tile"),
+                    tr("Terrain"),
+                    tr("Eliminated by")
+                ));
+                for tile in &self.map.tiles {
+                    let eliminated_by = names(self.eliminating_players(tile.position));
+                    out.push_str(&format!(
+                        "| {} | {} | {} |\n",
+                        markdown_cell(&coordinate(tile.position)),
+                        markdown_cell(&tr_name(tile.terrain)),
+                        markdown_cell(&eliminated_by)
+                    ));
+                }
+                if !self.questions.is_empty() {
+                    out.push_str(&format!("\n## {}\n\n", tr("Questions")));
+                    for (i, q) in self.questions.iter().enumerate() {
+                        let line = trf(
+                            "{}. {} asked {} on {}: {}",
+                            &[
+                                &(i + 1),
+                                &self.players.get(q.asker).name,
+                                &self.players.get(q.answerer).name,
+                                &coordinate(q.position),
+                                &tr_name(q.answer),
+                            ],
+                        );
+                        out.push_str(&format!("{}\n", markdown_line(&line)));
+                    }
+                }
+            }
+        }
+
+        out
+    }
+
+    /// The answers on every tile, to tell later which of them changed.
+    fn answers(&self) -> Vec<BTreeMap<PlayerID, Answer>> {
+        self.map.tiles.iter().map(|t| t.answers.clone()).collect()
+    }
+
+    /// Add answers that differ from the answers before to the history.
+    fn record_answers(&mut self, answers_before: &[BTreeMap<PlayerID, Answer>]) {
+        for (before, after) in answers_before.iter().zip(&self.map.tiles) {
+            for (&player, &answer) in &after.answers {
+                let answer_before = before.get(&player).copied().unwrap_or_default();
+                if answer != answer_before {
+                    self.history.push(AnswerEvent {
+                        player,
+                        position: after.position,
+                        answer,
+                    });
                 }
             }
         }
     }
 
     fn prefill_answers(&mut self) {
-        for tile in self.map.0.iter_mut() {
+        for tile in self.map.tiles.iter_mut() {
             for player in self.players.iter() {
                 tile.answers.insert(player.id, Answer::Unknown);
             }
@@ -331,14 +2074,46 @@ impl TryingClues {
     /// Build a list of possible clues for each player according to their given answers.
     fn deduce_clues(&mut self) {
         for player in self.players.iter() {
-            let clues = self.map.clues_for_player(player.id, self.with_inverted);
+            let clues = self.possible_masks(player.id).map(|&(c, _)| c).collect();
             self.deduced_clues.insert(player.id, clues);
         }
     }
 
+    /// The clues that fit the answers of the player, with the tiles they apply to.
+    fn possible_masks(&self, player: PlayerID) -> impl Iterator<Item = &(Clue, TileMask)> {
+        let inverted = self.may_be_inverted(player);
+        self.map
+            .allowed(&self.masks, player)
+            .filter(move |(clue, _)| inverted || !clue.inverted)
+    }
+
+    /// True if the clue of the player might be inverted. Never for a hidden player, whose
+    /// setting must not feed into the deduction.
+    fn may_be_inverted(&self, player: PlayerID) -> bool {
+        !self.is_hidden(player) && self.with_inverted.get(&player).copied().unwrap_or_default()
+    }
+
+    /// True if nothing about the clue of the player should be shown.
+    fn is_hidden(&self, player: PlayerID) -> bool {
+        self.hide_own && player == self.user
+    }
+
+    /// True if hints should consider the position.
+    fn is_in_region(&self, position: Hex) -> bool {
+        self.region.is_empty() || self.region.contains(&position)
+    }
+
+    /// The tiles of every clue that is still possible for the player.
+    fn clue_masks(&self, player: PlayerID) -> Vec<TileMask> {
+        self.possible_masks(player)
+            .map(|(_, mask)| mask.clone())
+            .collect()
+    }
+
     /// Calculate hints. This is compute intensive, so don't call it every frame.
     fn calculate_hints(&mut self) {
         self.hints.clear();
+        self.shown.remove(&HighlightChannel::Hint);
 
         /// Helper struct to keep track of how many clues/tiles are affected by asking
         /// a question on a tile.
@@ -353,31 +2128,30 @@ impl TryingClues {
             let mut questions: Vec<Question> = Vec::new();
 
             // Simulate placing answers to find spaces with best chance of reducing clues.
-            let clues_before = self.map.clues_for_player(player.id, self.with_inverted);
+            let clues_before = self.clue_masks(player.id);
             if clues_before.len() == 1 {
                 // Player has only a single clue left. No point in asking any questions.
                 continue;
             }
 
             // Scan all tiles for quality of asking a question there.
-            for i in 0..self.map.0.len() {
-                let answer_before = *self.map.0[i].answers.entry(player.id).or_default();
+            for i in 0..self.map.tiles.len() {
+                if !self.is_in_region(self.map.tiles[i].position) {
+                    continue;
+                }
+
+                let answer_before = *self.map.tiles[i].answers.entry(player.id).or_default();
                 if answer_before != Answer::Unknown {
                     // Player already answered on this tile.
                     continue;
                 }
 
-                self.map.0[i].answers.insert(player.id, Answer::Yes);
-                let clues_with_yes = self.map.clues_for_player(player.id, self.with_inverted);
-                self.map.0[i].answers.insert(player.id, Answer::No);
-                let clues_with_no = self.map.clues_for_player(player.id, self.with_inverted);
-                self.map.0[i].answers.insert(player.id, Answer::Unknown);
-
-                let gain_with_yes = clues_before.len().abs_diff(clues_with_yes.len());
-                let gain_with_no = clues_before.len().abs_diff(clues_with_no.len());
+                // A yes rules out the clues that don't apply here, a no the ones that do.
+                let gain_with_no = clues_before.iter().filter(|m| m.contains(i)).count();
+                let gain_with_yes = clues_before.len() - gain_with_no;
 
                 questions.push(Question {
-                    tile: self.map.0[i].position,
+                    tile: self.map.tiles[i].position,
                     gain_with_yes,
                     gain_with_no,
                 });
@@ -392,11 +2166,14 @@ impl TryingClues {
                 let at_least = q.gain_with_no.min(q.gain_with_yes);
                 let at_most = q.gain_with_no.max(q.gain_with_yes);
                 let text = if at_least == at_most {
-                    format!("Ask {} here to rule out {at_least} clues.", player.name)
+                    trf(
+                        "Ask {} here to rule out {} clues.",
+                        &[&player.name, &at_least],
+                    )
                 } else {
-                    format!(
-                        "Ask {} here to rule out {at_least} to {at_most} clues.",
-                        player.name
+                    trf(
+                        "Ask {} here to rule out {} to {} clues.",
+                        &[&player.name, &at_least, &at_most],
                     )
                 };
                 let tiles = best.into_iter().map(|q| q.tile).collect();
@@ -415,65 +2192,255 @@ impl TryingClues {
             tile: Hex,
         }
         let mut nos = Vec::new();
-        let clues_before = self.map.clues_for_player(self.user, self.with_inverted);
-        for i in 0..self.map.0.len() {
-            let answer_before = *self.map.0[i].answers.entry(self.user).or_default();
+        let clues_before = self.clue_masks(self.user);
+        for i in 0..self.map.tiles.len() {
+            if !self.is_in_region(self.map.tiles[i].position) {
+                continue;
+            }
+
+            let answer_before = *self.map.tiles[i].answers.entry(self.user).or_default();
             if answer_before != Answer::Unknown {
                 // Player already answered on this tile.
                 continue;
             }
 
-            self.map.0[i].answers.insert(self.user, Answer::No);
-            let clues_with_no = self.map.clues_for_player(self.user, self.with_inverted);
-            self.map.0[i].answers.insert(self.user, Answer::Unknown);
-
             nos.push(No {
-                clue_diff: clues_before.len().abs_diff(clues_with_no.len()),
-                tile: self.map.0[i].position,
+                clue_diff: clues_before.iter().filter(|m| m.contains(i)).count(),
+                tile: self.map.tiles[i].position,
             });
         }
         let best = nos.into_iter().min_set_by_key(|n| n.clue_diff);
         if let Some(diff) = best.first().map(|n| n.clue_diff) {
             let text = if diff == 0 {
-                "Place a 'no' here to reveal no new information.".to_owned()
+                tr("Place a 'no' here to reveal no new information.").to_owned()
             } else {
-                format!("Place a 'no' here to rule out {diff} of your clues.")
+                trf("Place a 'no' here to rule out {} of your clues.", &[&diff])
             };
             let tiles = best.into_iter().map(|n| n.tile).collect();
             self.hints.push(Hint { text, tiles });
         }
+
+        if let Some(hint) = self.search_hint() {
+            self.hints.push(hint);
+        }
+
+        if !self.is_hidden(self.user) {
+            self.hints.extend(self.forced_answers());
+        }
+
+        if self.predict_questions {
+            let predictions = self.predicted_questions();
+            self.hints.extend(predictions);
+        }
+    }
+
+    /// Tiles where only one answer of the user fits every answer they gave before, under any
+    /// clue that is still possible for them. Answering otherwise, for example to hide
+    /// information, would give away a contradiction.
+    fn forced_answers(&self) -> Vec<Hint> {
+        let clues = self
+            .possible_masks(self.user)
+            .map(|&(c, _)| c)
+            .collect_vec();
+        if clues.is_empty() {
+            return Vec::new();
+        }
+        let unanswered = self
+            .map
+            .tiles
+            .iter()
+            .filter(|t| t.answers.get(&self.user).copied().unwrap_or_default() == Answer::Unknown)
+            .filter(|t| self.is_in_region(t.position))
+            .map(|t| t.position)
+            .collect_vec();
+        let (mut only_yes, mut only_no) = (Vec::new(), Vec::new());
+        for hex in unanswered {
+            let applies = clues
+                .iter()
+                .filter(|&&c| self.map.clue_applies(c, hex))
+                .count();
+            if applies == clues.len() {
+                only_yes.push(hex);
+            } else if applies == 0 {
+                only_no.push(hex);
+            }
+        }
+
+        let mut hints = Vec::new();
+        if !only_yes.is_empty() {
+            hints.push(Hint {
+                text: tr(
+                    "Your earlier answers only allow a 'yes' here. A 'no' would contradict them.",
+                )
+                .to_owned(),
+                tiles: only_yes,
+            });
+        }
+        if !only_no.is_empty() {
+            hints.push(Hint {
+                text: tr(
+                    "Your earlier answers only allow a 'no' here. A 'yes' would contradict them.",
+                )
+                .to_owned(),
+                tiles: only_no,
+            });
+        }
+        hints
+    }
+
+    /// Where each opponent will probably ask next, if they ask greedily: on a tile where their
+    /// own clue might put the cryptid, about the player whose possible clues that tile splits
+    /// most evenly. Tiles with a cube can't be asked about.
+    fn predicted_questions(&self) -> Vec<Hint> {
+        let possible_clues = self
+            .players
+            .iter()
+            .filter(|p| !self.is_hidden(p.id))
+            .map(|p| (p.id, self.possible_clues(p.id)))
+            .collect_vec();
+        let mut hints = Vec::new();
+        for asker in self.players.iter().filter(|p| p.id != self.user) {
+            let own_clues = self.possible_clues(asker.id);
+            // The best question on every tile: who is asked and how many clues it rules out
+            // for sure, whatever the answer.
+            let questions = self
+                .map
+                .tiles
+                .iter()
+                .filter(|t| !t.answers.values().any(|&a| a == Answer::No))
+                .filter(|t| {
+                    own_clues
+                        .iter()
+                        .any(|&c| self.map.clue_applies(c, t.position))
+                })
+                .filter_map(|t| {
+                    possible_clues
+                        .iter()
+                        .filter(|(id, _)| *id != asker.id)
+                        .filter(|(id, _)| {
+                            t.answers.get(id).copied().unwrap_or_default() == Answer::Unknown
+                        })
+                        .map(|(id, clues)| {
+                            let yes = clues
+                                .iter()
+                                .filter(|&&c| self.map.clue_applies(c, t.position))
+                                .count();
+                            (*id, yes.min(clues.len() - yes))
+                        })
+                        .max_by_key(|(_, gain)| *gain)
+                        .map(|(asked, gain)| (t.position, asked, gain))
+                })
+                .collect_vec();
+            let best = questions.into_iter().max_set_by_key(|(_, _, gain)| *gain);
+            let Some(&(_, asked, gain)) = best.first() else {
+                continue;
+            };
+            if gain == 0 {
+                // No question tells them anything, there is nothing to predict.
+                continue;
+            }
+            hints.push(Hint {
+                text: trf(
+                    "{} will probably ask {} on one of these tiles next.",
+                    &[&asker.name, &self.players.get(asked).name],
+                ),
+                tiles: best.into_iter().map(|(hex, _, _)| hex).collect(),
+            });
+        }
+        hints
+    }
+
+    /// Advise whether the user should search instead of asking a question. Searching needs a
+    /// tile where the clue of the user applies. If the search fails, the others learn that.
+    fn search_hint(&mut self) -> Option<Hint> {
+        if self.hide_own {
+            return None;
+        }
+        if self.analysis.is_none() {
+            self.analyze();
+        }
+        let analysis = self.analysis.as_ref()?;
+
+        let own_clue = self.known_clue(self.user);
+        let (tile, _) = analysis.candidates().into_iter().find(|&(hex, _)| {
+            let own_answer = self.map.get(hex).and_then(|t| t.answers.get(&self.user));
+            let possible = match own_clue {
+                Some(clue) => self.map.clue_applies(clue, hex),
+                None => own_answer != Some(&Answer::No),
+            };
+            possible && self.is_in_region(hex)
+        })?;
+        let chance = analysis.chance(tile);
+
+        // What the others would learn about the clue of the user from the disc of the search.
+        let clues_before = self.possible_masks(self.user).count();
+        let map_tile = self.map.get_mut(tile)?;
+        let answer_before = map_tile.answers.insert(self.user, Answer::Yes);
+        let clues_after = self.possible_masks(self.user).count();
+        let map_tile = self.map.get_mut(tile)?;
+        match answer_before {
+            Some(answer) => map_tile.answers.insert(self.user, answer),
+            None => map_tile.answers.remove(&self.user),
+        };
+        let leaked = clues_before.abs_diff(clues_after);
+
+        let percent = format!("{:.0}", chance * 100.0);
+        let text = if chance >= 1.0 {
+            tr("The cryptid must be here. Search!").to_owned()
+        } else if chance >= SEARCH_CHANCE {
+            trf(
+                "Search here, the cryptid is here in {}% of the combinations. \
+                If the search fails, the others rule out {} of your clues.",
+                &[&percent, &leaked],
+            )
+        } else {
+            trf(
+                "Better ask a question. A search here only finds the cryptid in {}% \
+                of the combinations, and failing rules out {} of your clues for the others.",
+                &[&percent, &leaked],
+            )
+        };
+        Some(Hint {
+            text,
+            tiles: vec![tile],
+        })
     }
 
     /// Go through all tiles and see if any clue applies to them.
     /// If no clue applies to them, they are drawn as small.
     fn update_map_from_clues(&mut self) {
         // Set tile to be big. Should any clue fail, then it will be small.
-        for tile in &mut self.map.0 {
+        for tile in &mut self.map.tiles {
             tile.small = false;
         }
 
         // Mark any tiles as small that violate known clues.
         for known_clue in self.players.iter().filter_map(|p| {
-            if self.known_clues.get(&p.id).copied().unwrap_or_default() {
+            if self.hide_own && p.id == self.user {
+                None
+            } else if self.known_clues.get(&p.id).copied().unwrap_or_default() {
                 self.clues.get(&p.id).copied()
             } else {
                 None
             }
         }) {
-            for i in 0..self.map.0.len() {
-                let position = self.map.0[i].position;
+            for i in 0..self.map.tiles.len() {
+                let position = self.map.tiles[i].position;
                 let found = self.map.clue_applies(known_clue, position);
                 if !found {
-                    self.map.0[i].small = true;
+                    self.map.tiles[i].small = true;
                 }
             }
         }
 
         // Mark any tiles as small that violate deduced clues.
         // This is only the case if no clues for a player apply to the given tile.
-        for i in 0..self.map.0.len() {
-            let position = self.map.0[i].position;
+        for i in 0..self.map.tiles.len() {
+            let position = self.map.tiles[i].position;
             for player in self.players.iter() {
+                if self.hide_own && player.id == self.user {
+                    continue;
+                }
                 let mut found_any = false;
                 for clue in self.deduced_clues.entry(player.id).or_default() {
                     if self.map.clue_applies(*clue, position) {
@@ -482,20 +2449,68 @@ impl TryingClues {
                     }
                 }
                 if !found_any {
-                    self.map.0[i].small = true;
+                    self.map.tiles[i].small = true;
                 }
             }
         }
     }
 }
 
-// Dropdown for switching terrain types.
-fn terrain_switcher(id: impl Hash, ui: &mut egui::Ui, terrain: &mut Terrain) {
-    egui::ComboBox::new(id, "")
-        .selected_text(format!("{terrain}"))
-        .show_ui(ui, |ui| {
-            for t in Terrain::iter() {
-                ui.selectable_value(terrain, t, format!("{t}"));
-            }
-        });
+/// Quote a field for CSV if necessary, following RFC 4180.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Keep a text on one line in Markdown, so that a name with a line break does not end a
+/// heading or a list item.
+fn markdown_line(text: &str) -> String {
+    text.split(['\r', '\n'])
+        .filter(|line| !line.is_empty())
+        .join(" ")
+}
+
+/// A text for a cell of a Markdown table, which can neither contain a pipe nor break lines.
+fn markdown_cell(text: &str) -> String {
+    markdown_line(text).replace('|', "\\|")
+}
+
+/// Show the tiles in the channel, or hide them if they are shown already.
+fn show_button(
+    ui: &mut egui::Ui,
+    shown: &mut HashMap<HighlightChannel, Vec<Hex>>,
+    channel: HighlightChannel,
+    tiles: &[Hex],
+) {
+    let showing = shown.get(&channel).is_some_and(|t| t == tiles);
+    let text = if showing { tr("Hide") } else { tr("Show") };
+    if ui.button(text).clicked() {
+        if showing {
+            shown.remove(&channel);
+        } else {
+            shown.insert(channel, tiles.to_vec());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("Anna"), "Anna");
+        assert_eq!(csv_field("Anna, Bob"), "\"Anna, Bob\"");
+        assert_eq!(csv_field("The \"Bear\""), "\"The \"\"Bear\"\"\"");
+        assert_eq!(csv_field("Anna\r\nBob"), "\"Anna\r\nBob\"");
+    }
+
+    #[test]
+    fn markdown_cells_stay_in_their_cell() {
+        assert_eq!(markdown_cell("Anna|Bob"), "Anna\\|Bob");
+        assert_eq!(markdown_cell("Anna\r\nBob"), "Anna Bob");
+    }
 }
@@ -0,0 +1,383 @@
+use std::collections::{HashMap, HashSet};
+
+use hexx::Hex;
+use itertools::Itertools;
+use notan::egui::{self, Align, Label, Layout};
+use serde::Serialize;
+
+use crate::{
+    houserules::HouseRules,
+    i18n::{tr, tr_name, trf},
+    model::{
+        coordinate, Answer, AnswerEvent, Clue, ClueKind, ClueTarget, Map, Piece, PlayerID,
+        PlayerList, Terrain, Tile,
+    },
+    LAYOUT_SPACE,
+};
+
+use super::{tryingclues::TryingClues, widgets::clue_editor, Common, SubState, Transition};
+
+/// A sub state for checking all answers after the cryptid was found.
+#[derive(Debug, Serialize)]
+pub struct Verifying {
+    map: Map,
+    /// Pieces from the piece editor, for the next game.
+    custom_pieces: Vec<Piece>,
+    players: PlayerList,
+    /// The actual clues of the players.
+    clues: HashMap<PlayerID, Clue>,
+    /// Answers in the order they were given.
+    history: Vec<AnswerEvent>,
+    /// Answers that contradict the actual clues, also those that were changed later.
+    inconsistencies: Vec<AnswerEvent>,
+    /// Tiles that match all actual clues.
+    cryptid: Vec<Hex>,
+    /// What could be deduced after every answer of the game.
+    replay: Vec<ReplayStep>,
+    highlights: Vec<Hex>,
+    /// True for players whose clue might have been inverted.
+    with_inverted: HashMap<PlayerID, bool>,
+    /// True if the next game should use this map. Otherwise only the players are kept.
+    pub same_map: bool,
+}
+
+/// What could be deduced right after an answer was given.
+#[derive(Debug, Serialize)]
+struct ReplayStep {
+    event: AnswerEvent,
+    remaining_tiles: usize,
+    /// Players whose clue could be deduced after this answer.
+    revealed: Vec<PlayerID>,
+}
+
+impl From<&TryingClues> for Verifying {
+    fn from(value: &TryingClues) -> Self {
+        let players = value.players.clone();
+        let clues = players
+            .iter()
+            .map(|p| {
+                let clue = value.known_clue(p.id).unwrap_or_else(|| {
+                    ClueKind::within(ClueTarget::Terrain(Terrain::Desert), &value.map().rules)
+                        .into()
+                });
+                (p.id, clue)
+            })
+            .collect();
+        let replay = replay(value.map(), &players, &value.history, &value.with_inverted);
+
+        let mut s = Self {
+            map: value.map().clone(),
+            custom_pieces: value.custom_pieces().to_vec(),
+            players,
+            clues,
+            history: value.history.clone(),
+            inconsistencies: Vec::new(),
+            cryptid: Vec::new(),
+            replay,
+            highlights: Vec::new(),
+            with_inverted: value.with_inverted.clone(),
+            same_map: false,
+        };
+        s.update();
+        s
+    }
+}
+
+impl Common for Verifying {
+    fn tiles(&self) -> &[Tile] {
+        &self.map.tiles
+    }
+    fn tiles_mut(&mut self) -> &mut [Tile] {
+        &mut self.map.tiles
+    }
+    fn house_rules(&self) -> &HouseRules {
+        &self.map.rules
+    }
+    fn custom_pieces(&self) -> &[Piece] {
+        &self.custom_pieces
+    }
+
+    fn gui(&mut self, ui: &mut egui::Ui) -> Option<Transition> {
+        let clues_before = self.clues.clone();
+
+        ui.heading(tr("Verification"));
+        ui.add(Label::new(tr("Enter the actual clue of every player.")).wrap(true));
+        let rules = &self.map.rules;
+        for player in self.players.iter() {
+            ui.separator();
+            ui.label(&player.name);
+            let clue = self
+                .clues
+                .entry(player.id)
+                .or_insert(ClueKind::within(ClueTarget::Terrain(Terrain::Desert), rules).into());
+            let with_inverted = self.with_inverted.get(&player.id).copied();
+            clue_editor(
+                ui,
+                player.id,
+                clue,
+                with_inverted.unwrap_or_default(),
+                &self.map,
+            );
+        }
+
+        if clues_before != self.clues {
+            self.update();
+        }
+
+        ui.add_space(LAYOUT_SPACE);
+        self.gui_for_result(ui);
+        ui.add_space(LAYOUT_SPACE);
+        self.gui_for_replay(ui);
+
+        let mut new_game = false;
+        ui.add_space(LAYOUT_SPACE);
+        ui.heading(tr("New game"));
+        ui.with_layout(Layout::top_down_justified(Align::Center), |ui| {
+            if ui.button(tr("New game with same map")).clicked() {
+                self.same_map = true;
+                new_game = true;
+            }
+            if ui.button(tr("New game with same players")).clicked() {
+                self.same_map = false;
+                new_game = true;
+            }
+        });
+
+        new_game.then_some(Transition::Next)
+    }
+
+    fn next(&self) -> Result<SubState, String> {
+        Ok(if self.same_map {
+            SubState::PlacingStructures(self.into())
+        } else {
+            SubState::BuildingMap(self.into())
+        })
+    }
+
+    fn confirm_switch(&self) -> Option<String> {
+        Some(tr("The answers and results of this game will be gone.").to_owned())
+    }
+
+    fn map_changed(&mut self) {
+        self.update();
+    }
+
+    fn highlights(&self) -> Vec<Hex> {
+        self.highlights.to_vec()
+    }
+
+    fn click(&mut self, hex: Hex) {
+        self.highlights = self
+            .map
+            .get(hex)
+            .is_some()
+            .then_some(hex)
+            .into_iter()
+            .collect();
+    }
+
+    fn players(&self) -> &PlayerList {
+        &self.players
+    }
+}
+
+impl Verifying {
+    fn gui_for_result(&mut self, ui: &mut egui::Ui) {
+        ui.heading(tr("Result"));
+
+        ui.horizontal(|ui| {
+            if ui.button(tr("Show")).clicked() {
+                self.highlights = self.cryptid.to_vec();
+            }
+            let text = match self.cryptid.as_slice() {
+                [tile] => trf("The cryptid was on {}.", &[&coordinate(*tile)]),
+                tiles => trf("These clues allow {} tiles.", &[&tiles.len()]),
+            };
+            ui.add(Label::new(text).wrap(true));
+        });
+
+        if self.inconsistencies.is_empty() {
+            ui.label(tr("Every answer matches the clues."));
+        }
+        for &event in &self.inconsistencies {
+            let template = if self.was_changed(event) {
+                "{} answered {} on {}, which contradicts their clue. The answer was changed later."
+            } else {
+                "{} answered {} on {}, which contradicts their clue."
+            };
+            ui.horizontal(|ui| {
+                if ui.button(tr("Show")).clicked() {
+                    self.highlights = vec![event.position];
+                }
+                let text = trf(
+                    template,
+                    &[
+                        &self.players.get(event.player).name,
+                        &tr_name(event.answer),
+                        &coordinate(event.position),
+                    ],
+                );
+                ui.add(Label::new(text).wrap(true));
+            });
+        }
+    }
+
+    fn gui_for_replay(&mut self, ui: &mut egui::Ui) {
+        ui.heading(tr("Replay"));
+        if self.replay.is_empty() {
+            ui.label(tr("No answers were given."));
+        }
+
+        for (i, step) in self.replay.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.button(tr("Show")).clicked() {
+                    self.highlights = vec![step.event.position];
+                }
+                let mut text = trf(
+                    "{}. {} answered {} on {}. {} tiles remained.",
+                    &[
+                        &(i + 1),
+                        &self.players.get(step.event.player).name,
+                        &tr_name(step.event.answer),
+                        &coordinate(step.event.position),
+                        &step.remaining_tiles,
+                    ],
+                );
+                if step.remaining_tiles == 1 {
+                    text.push_str(tr(" The cryptid could be found."));
+                }
+                if !step.revealed.is_empty() {
+                    let names = step
+                        .revealed
+                        .iter()
+                        .map(|&p| self.players.get(p).name.as_str())
+                        .join(", ");
+                    text.push_str(&trf(" Known clues: {}.", &[&names]));
+                }
+                ui.add(Label::new(text).wrap(true));
+            });
+        }
+    }
+
+    /// Check the answers against the actual clues.
+    fn update(&mut self) {
+        self.cryptid.clear();
+        for i in 0..self.map.tiles.len() {
+            let position = self.map.tiles[i].position;
+            let is_cryptid = self.players.iter().all(|player| {
+                self.clues
+                    .get(&player.id)
+                    .is_none_or(|&clue| self.map.clue_applies(clue, position))
+            });
+            self.map.tiles[i].small = !is_cryptid;
+            if is_cryptid {
+                self.cryptid.push(position);
+            }
+        }
+
+        // Answers in the order they were given, then any that are not in the history.
+        let current = self.map.tiles.iter().flat_map(|t| {
+            t.answers.iter().map(|(&player, &answer)| AnswerEvent {
+                player,
+                position: t.position,
+                answer,
+            })
+        });
+        let mut inconsistencies = Vec::new();
+        for event in self.history.iter().copied().chain(current) {
+            if self.contradicts(event) && !inconsistencies.contains(&event) {
+                inconsistencies.push(event);
+            }
+        }
+        self.inconsistencies = inconsistencies;
+    }
+
+    fn contradicts(&self, event: AnswerEvent) -> bool {
+        let Some(&clue) = self.clues.get(&event.player) else {
+            return false;
+        };
+        let applies = self.map.clue_applies(clue, event.position);
+        match event.answer {
+            Answer::Unknown => false,
+            Answer::Yes => !applies,
+            Answer::No => applies,
+        }
+    }
+
+    /// True if the player does not give the answer on the tile anymore.
+    fn was_changed(&self, event: AnswerEvent) -> bool {
+        let current = self
+            .map
+            .get(event.position)
+            .and_then(|t| t.answers.get(&event.player));
+        current != Some(&event.answer)
+    }
+}
+
+/// Replay the answers in order and collect what could be deduced after each one.
+fn replay(
+    map: &Map,
+    players: &PlayerList,
+    history: &[AnswerEvent],
+    with_inverted: &HashMap<PlayerID, bool>,
+) -> Vec<ReplayStep> {
+    // Where each clue applies does not change while replaying, so only compute that once.
+    let coverage = map.coverage(true);
+
+    let mut answers: HashMap<PlayerID, HashMap<Hex, Answer>> = HashMap::new();
+    let mut steps = Vec::new();
+
+    for &event in history {
+        answers
+            .entry(event.player)
+            .or_default()
+            .insert(event.position, event.answer);
+
+        let possible_clues: Vec<(PlayerID, Vec<&HashSet<Hex>>)> = players
+            .iter()
+            .map(|player| {
+                let answers = answers.get(&player.id);
+                let inverted = with_inverted.get(&player.id).copied().unwrap_or_default();
+                let clues = coverage
+                    .iter()
+                    .filter(|(clue, _)| inverted || !clue.inverted)
+                    .map(|(_, covered)| covered)
+                    .filter(|covered| {
+                        answers
+                            .into_iter()
+                            .flatten()
+                            .all(|(position, answer)| match answer {
+                                Answer::Unknown => true,
+                                Answer::Yes => covered.contains(position),
+                                Answer::No => !covered.contains(position),
+                            })
+                    })
+                    .collect();
+                (player.id, clues)
+            })
+            .collect();
+
+        let remaining_tiles = map
+            .tiles
+            .iter()
+            .filter(|t| {
+                possible_clues
+                    .iter()
+                    .all(|(_, clues)| clues.iter().any(|c| c.contains(&t.position)))
+            })
+            .count();
+        let revealed = possible_clues
+            .iter()
+            .filter(|(_, clues)| clues.len() == 1)
+            .map(|(player, _)| *player)
+            .collect();
+
+        steps.push(ReplayStep {
+            event,
+            remaining_tiles,
+            revealed,
+        });
+    }
+
+    steps
+}
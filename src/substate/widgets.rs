@@ -0,0 +1,248 @@
+use std::hash::Hash;
+
+use notan::egui;
+use strum::IntoEnumIterator;
+
+use crate::{
+    clueparser::parse_clue,
+    houserules::MAX_CLUE_DISTANCE,
+    i18n::{tr, tr_name, trf},
+    model::{
+        coordinate, Animal, Clue, ClueKind, ClueTarget, Map, Piece, PlayerID, StructureColor,
+        StructureKind, Terrain, Tile,
+    },
+    notation::Board,
+};
+
+/// Widgets to edit the clue of a player, with the animals and house rules of the map. Inverting
+/// the clue is only offered in games with inverted clues, or if the clue is inverted already.
+pub fn clue_editor(
+    ui: &mut egui::Ui,
+    player: PlayerID,
+    clue: &mut Clue,
+    with_inverted: bool,
+    map: &Map,
+) {
+    let rules = &map.rules;
+    let animals = map.animals();
+    // Change clue type
+    egui::ComboBox::new(format!("combobox-clue-{player:?}"), "")
+        .selected_text(tr("Edit type"))
+        .show_ui(ui, |ui| {
+            if ui.button(tr("Near terrain")).clicked() {
+                clue.kind = ClueKind::within(ClueTarget::Terrain(Terrain::Desert), rules);
+            }
+            if ui.button(tr("One of two terrains")).clicked() {
+                clue.kind = ClueKind::TwoTerrains(Terrain::Desert, Terrain::Forest);
+            }
+            if ui.button(tr("Near either animal")).clicked() {
+                clue.kind = ClueKind::within(ClueTarget::EitherAnimal, rules);
+            }
+            if ui.button(tr("Near animal")).clicked() {
+                clue.kind = ClueKind::within(ClueTarget::Animal(Animal::Bear), rules);
+            }
+            if ui.button(tr("Near structure type")).clicked() {
+                clue.kind =
+                    ClueKind::within(ClueTarget::StructureKind(StructureKind::Shack), rules);
+            }
+            if ui.button(tr("Near structure color")).clicked() {
+                clue.kind =
+                    ClueKind::within(ClueTarget::StructureColor(StructureColor::Black), rules);
+            }
+            for &custom in &rules.custom_clues {
+                let custom = ClueKind::Custom(custom);
+                if ui.button(custom.to_string()).clicked() {
+                    clue.kind = custom;
+                }
+            }
+        });
+
+    // Or type the clue. The text is kept by egui, it does not belong to the game.
+    let id = egui::Id::new(("clue-text", player));
+    let (mut text, mut error): (String, Option<String>) =
+        ui.data().get_temp(id).unwrap_or_default();
+    ui.horizontal(|ui| {
+        let response = ui.text_edit_singleline(&mut text).on_hover_text(tr(
+            "Type a clue, like \"within two spaces of a standing stone\"",
+        ));
+        let entered = response.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+        if ui.button(tr("Apply")).clicked() || entered {
+            match parse_clue(&text, map) {
+                Ok(parsed) if parsed.inverted && !with_inverted => {
+                    error = Some(tr("This game has no inverted clues.").to_owned());
+                }
+                Ok(parsed) => {
+                    *clue = parsed;
+                    text.clear();
+                    error = None;
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+    });
+    if let Some(error) = &error {
+        ui.add(egui::Label::new(error).wrap(true));
+    }
+    ui.data().insert_temp(id, (text, error));
+
+    if with_inverted || clue.inverted {
+        ui.checkbox(
+            &mut clue.inverted,
+            tr("Inverted (the cryptid is not there)"),
+        );
+    }
+
+    // Edit clue
+    match &mut clue.kind {
+        ClueKind::WithinN { n, target } => {
+            ui.horizontal(|ui| {
+                ui.label(tr("Within"));
+                ui.add(egui::DragValue::new(n).clamp_range(0..=MAX_CLUE_DISTANCE));
+                ui.label(tr("spaces of"));
+                target_switcher(player, ui, target, &animals);
+            });
+        }
+        ClueKind::TwoTerrains(a, b) => {
+            ui.horizontal(|ui| {
+                ui.label(tr("On"));
+                terrain_switcher(format!("terrain-{player:?}-a"), ui, a);
+                ui.label(tr("or"));
+                terrain_switcher(format!("terrain-{player:?}-b"), ui, b);
+            });
+        }
+        ClueKind::Custom(_) => {
+            ui.add(egui::Label::new(clue.kind.to_string()).wrap(true));
+        }
+    }
+}
+
+/// Dropdown for the target of a clue, keeping the kind of target.
+fn target_switcher(
+    player: PlayerID,
+    ui: &mut egui::Ui,
+    target: &mut ClueTarget,
+    animals: &[Animal],
+) {
+    match target {
+        ClueTarget::Terrain(terrain) => {
+            terrain_switcher(format!("terrain-{player:?}"), ui, terrain);
+        }
+        ClueTarget::EitherAnimal => {
+            ui.label(tr("either animal"));
+        }
+        ClueTarget::Animal(animal) => {
+            egui::ComboBox::new(format!("animal-{player:?}"), tr("Territory"))
+                .selected_text(tr_name(*animal))
+                .show_ui(ui, |ui| {
+                    for &a in animals {
+                        ui.selectable_value(animal, a, tr_name(a));
+                    }
+                });
+        }
+        ClueTarget::StructureKind(kind) => {
+            egui::ComboBox::new(format!("structurekind-{player:?}"), "")
+                .selected_text(tr_name(*kind))
+                .show_ui(ui, |ui| {
+                    for k in StructureKind::iter() {
+                        ui.selectable_value(kind, k, tr_name(k));
+                    }
+                });
+        }
+        ClueTarget::StructureColor(color) => {
+            egui::ComboBox::new(format!("structurecolor-{player:?}"), tr("structure"))
+                .selected_text(tr_name(*color))
+                .show_ui(ui, |ui| {
+                    for c in StructureColor::iter() {
+                        ui.selectable_value(color, c, tr_name(c));
+                    }
+                });
+        }
+    }
+}
+
+/// Widgets to copy the board as a short text, or to set up a board from such a text.
+/// `current` is the board as it is now, `apply` gets an entered board of the pieces and may
+/// reject it.
+pub fn board_code(
+    ui: &mut egui::Ui,
+    current: Result<Board, String>,
+    pieces: &[Piece],
+    apply: impl FnOnce(Board) -> Result<(), String>,
+) {
+    egui::CollapsingHeader::new(tr("Board code"))
+        .id_source("board-code")
+        .show(ui, |ui| {
+            ui.add(
+                egui::Label::new(tr("Share the board as text, or paste a code to set it up."))
+                    .wrap(true),
+            );
+
+            let id = egui::Id::new("board-code-text");
+            let (mut text, mut error): (String, Option<String>) =
+                ui.data().get_temp(id).unwrap_or_default();
+            ui.text_edit_singleline(&mut text);
+            ui.horizontal(|ui| {
+                match &current {
+                    Ok(board) => {
+                        if ui.button(tr("Copy")).clicked() {
+                            text = board.to_string();
+                            ui.output().copied_text = text.clone();
+                            error = None;
+                        }
+                    }
+                    Err(e) => {
+                        ui.add_enabled(false, egui::Button::new(tr("Copy")))
+                            .on_disabled_hover_text(e);
+                    }
+                }
+                if ui.button(tr("Load")).clicked() {
+                    error = Board::parse(&text, pieces).and_then(apply).err();
+                }
+            });
+            if let Some(error) = &error {
+                ui.add(egui::Label::new(error).wrap(true));
+            }
+            ui.data().insert_temp(id, (text, error));
+        });
+}
+
+/// Widgets to correct the terrain and animal territory of a tile, to one of the animals.
+pub fn tile_editor(ui: &mut egui::Ui, tile: &mut Tile, animals: &[Animal]) {
+    ui.horizontal(|ui| {
+        ui.label(tr("Terrain"));
+        terrain_switcher("tile-editor-terrain", ui, &mut tile.terrain);
+    });
+    ui.horizontal(|ui| {
+        ui.label(tr("Territory"));
+        egui::ComboBox::new("tile-editor-animal", "")
+            .selected_text(tile.animal.map_or(tr("None").to_owned(), tr_name))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut tile.animal, None, tr("None"));
+                for &a in animals {
+                    ui.selectable_value(&mut tile.animal, Some(a), tr_name(a));
+                }
+            });
+    });
+}
+
+/// Dropdown for switching terrain types.
+fn terrain_switcher(id: impl Hash, ui: &mut egui::Ui, terrain: &mut Terrain) {
+    egui::ComboBox::new(id, "")
+        .selected_text(tr_name(*terrain))
+        .show_ui(ui, |ui| {
+            for t in Terrain::iter() {
+                ui.selectable_value(terrain, t, tr_name(t));
+            }
+        });
+}
+
+/// Everything about a tile that can be seen on the physical board, like "C4: Forest, Bear".
+pub fn describe_tile(tile: &Tile) -> String {
+    let mut parts = vec![tr_name(tile.terrain)];
+    parts.extend(tile.animal.map(tr_name));
+    parts.extend(
+        tile.structure
+            .map(|s| trf("{} {}", &[&tr_name(s.color), &tr_name(s.kind)])),
+    );
+    format!("{}: {}", coordinate(tile.position), parts.join(", "))
+}
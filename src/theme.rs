@@ -0,0 +1,168 @@
+use notan::{
+    egui::{self, color_picker, Color32, Visuals},
+    prelude::Color,
+};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+use crate::{
+    highlight::HighlightChannel,
+    i18n::{tr, tr_name},
+};
+
+/// Base themes to choose from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Display)]
+pub enum ThemeKind {
+    Dark,
+    Light,
+}
+
+impl ThemeKind {
+    fn background(self) -> Color32 {
+        match self {
+            ThemeKind::Dark => Color32::BLACK,
+            ThemeKind::Light => Color32::from_rgb(230, 230, 225),
+        }
+    }
+
+    fn highlight(self, channel: HighlightChannel) -> Color32 {
+        match (self, channel) {
+            (ThemeKind::Dark, HighlightChannel::Selection) => Color32::YELLOW,
+            (ThemeKind::Dark, HighlightChannel::Hint) => Color32::from_rgb(0, 220, 255),
+            (ThemeKind::Dark, HighlightChannel::Preview) => Color32::from_rgb(255, 130, 40),
+            (ThemeKind::Light, HighlightChannel::Selection) => Color32::from_rgb(200, 30, 160),
+            (ThemeKind::Light, HighlightChannel::Hint) => Color32::from_rgb(0, 110, 200),
+            (ThemeKind::Light, HighlightChannel::Preview) => Color32::from_rgb(210, 90, 0),
+        }
+    }
+}
+
+/// Colors used to style the egui panel and the map.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub kind: ThemeKind,
+    /// Color behind the map. Defaults to the background of the [ThemeKind], but can be customized.
+    pub background: Color32,
+    /// Colors of the highlight channels. Default to the colors of the [ThemeKind].
+    pub highlights: [Color32; 3],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::new(ThemeKind::Dark)
+    }
+}
+
+impl Theme {
+    pub fn new(kind: ThemeKind) -> Self {
+        Self {
+            kind,
+            background: kind.background(),
+            highlights: [
+                HighlightChannel::Selection,
+                HighlightChannel::Hint,
+                HighlightChannel::Preview,
+            ]
+            .map(|channel| kind.highlight(channel)),
+        }
+    }
+
+    /// Color for outlines of structures and answers.
+    pub fn stroke(&self) -> Color {
+        match self.kind {
+            ThemeKind::Dark => Color::BLACK,
+            ThemeKind::Light => Color::from_bytes(40, 40, 40, 255),
+        }
+    }
+
+    /// Color for highlighted tiles.
+    pub fn highlight(&self, channel: HighlightChannel) -> Color {
+        Color::from(self.highlights[channel as usize].to_array())
+    }
+
+    /// Color for overlays drawn on top of the map.
+    pub fn overlay(&self) -> Color {
+        match self.kind {
+            ThemeKind::Dark => Color::WHITE,
+            ThemeKind::Light => Color::BLACK,
+        }
+    }
+
+    /// Color for the tile a dragged structure would be dropped on.
+    pub fn drop_target(&self, valid: bool) -> Color {
+        if valid {
+            Color::from_bytes(80, 220, 80, 255)
+        } else {
+            Color::from_bytes(230, 60, 60, 255)
+        }
+    }
+
+    /// Color for the tiles at the distance to the selected tile.
+    pub fn range_band(&self, distance: u32) -> Color {
+        match distance {
+            0 | 1 => Color::from_bytes(80, 200, 255, 255),
+            2 => Color::from_bytes(180, 120, 255, 255),
+            3 => Color::from_bytes(255, 160, 60, 255),
+            _ => Color::from_bytes(120, 220, 120, 255),
+        }
+    }
+
+    pub fn visuals(&self) -> Visuals {
+        match self.kind {
+            ThemeKind::Dark => Visuals::dark(),
+            ThemeKind::Light => Visuals::light(),
+        }
+    }
+
+    pub fn gui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label(tr("Theme"));
+            let kind_before = self.kind;
+            egui::ComboBox::new("theme-select", "")
+                .selected_text(tr_name(self.kind))
+                .show_ui(ui, |ui| {
+                    for kind in ThemeKind::iter() {
+                        ui.selectable_value(&mut self.kind, kind, tr_name(kind));
+                    }
+                });
+            if kind_before != self.kind {
+                *self = Self::new(self.kind);
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("Background"));
+            color_picker::color_edit_button_srgba(
+                ui,
+                &mut self.background,
+                color_picker::Alpha::Opaque,
+            );
+            if ui.button(tr("Reset")).clicked() {
+                self.background = self.kind.background();
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label(tr("Highlights"));
+            for channel in HighlightChannel::iter() {
+                color_picker::color_edit_button_srgba(
+                    ui,
+                    &mut self.highlights[channel as usize],
+                    color_picker::Alpha::Opaque,
+                )
+                .on_hover_text(tr_name(channel));
+            }
+            if ui.button(tr("Reset")).clicked() {
+                self.highlights = Self::new(self.kind).highlights;
+            }
+        });
+    }
+}
+
+/// Black or white, whichever is easier to read on the background.
+pub fn readable_on(background: Color32) -> Color {
+    let [r, g, b, _] = background.to_array().map(f32::from);
+    let luminance = 0.299 * r + 0.587 * g + 0.114 * b;
+    if luminance > 140.0 {
+        Color::BLACK
+    } else {
+        Color::WHITE
+    }
+}
@@ -0,0 +1,104 @@
+//! Clocks for groups that play with time pressure.
+
+use notan::{
+    egui::{self, Align2, Color32, RichText},
+    prelude::Color,
+};
+
+use crate::{
+    bindings::Input,
+    i18n::{tr, trf},
+    LAYOUT_SPACE,
+};
+
+/// Shows how long the game has been going on and counts down the current turn.
+#[derive(Debug, Clone, Copy)]
+pub struct Timer {
+    pub enabled: bool,
+    /// Length of a turn in seconds. Zero for no countdown.
+    pub turn_length: f32,
+    /// Time since the app started, when the game and the current turn started.
+    game_start: f32,
+    turn_start: f32,
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            turn_length: 60.0,
+            game_start: 0.0,
+            turn_start: 0.0,
+        }
+    }
+}
+
+impl Timer {
+    pub fn gui(&mut self, ui: &mut egui::Ui, now: f32, next_turn: Input) {
+        if ui.checkbox(&mut self.enabled, tr("Show timer")).changed() {
+            self.restart(now);
+        }
+        if !self.enabled {
+            return;
+        }
+        ui.horizontal(|ui| {
+            ui.label(tr("Seconds per turn"));
+            ui.add(egui::DragValue::new(&mut self.turn_length).clamp_range(0.0..=600.0))
+                .on_hover_text(tr("0 for no countdown"));
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .button(tr("Next turn"))
+                .on_hover_text(trf("Or press {}", &[&next_turn]))
+                .clicked()
+            {
+                self.next_turn(now);
+            }
+            if ui.button(tr("Restart")).clicked() {
+                self.restart(now);
+            }
+        });
+    }
+
+    pub fn next_turn(&mut self, now: f32) {
+        self.turn_start = now;
+    }
+
+    fn restart(&mut self, now: f32) {
+        self.game_start = now;
+        self.turn_start = now;
+    }
+
+    /// Show the clocks in a corner of the map.
+    pub fn overlay(&self, ctx: &egui::Context, now: f32, color: Color) {
+        if !self.enabled {
+            return;
+        }
+
+        let [r, g, b, a] = color.rgba_u8();
+        let mut color = Color32::from_rgba_unmultiplied(r, g, b, a);
+        let mut text = trf("Game {}", &[&duration(now - self.game_start)]);
+        if self.turn_length > 0.0 {
+            let remaining = self.turn_length - (now - self.turn_start);
+            if remaining >= 0.0 {
+                text.push_str(&trf(", turn {}", &[&duration(remaining)]));
+            } else {
+                text.push_str(&trf(", turn over by {}", &[&duration(-remaining)]));
+                color = ctx.style().visuals.warn_fg_color;
+            }
+        }
+
+        egui::Area::new("timer")
+            .anchor(Align2::RIGHT_TOP, [-LAYOUT_SPACE, LAYOUT_SPACE])
+            .interactable(false)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(text).color(color));
+            });
+    }
+}
+
+/// Format seconds like "1:05".
+fn duration(seconds: f32) -> String {
+    let seconds = seconds.max(0.0) as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
@@ -0,0 +1,162 @@
+//! A guided tour for new users. Every step explains one part of the app and outlines where
+//! to look. Steps that ask the user to do something end by themselves once it is done.
+
+use notan::egui::{self, Align2, Color32, Id, LayerId, Order, Rect};
+use strum::{EnumIter, IntoEnumIterator};
+
+use crate::{
+    i18n::{tr, trf},
+    substate::SubState,
+    LAYOUT_SPACE,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter)]
+enum Step {
+    Welcome,
+    Pieces,
+    Structures,
+    Answers,
+    Deductions,
+}
+
+/// Where the user should look during a step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Target {
+    Panel,
+    Map,
+}
+
+impl Step {
+    fn text(self) -> &'static str {
+        match self {
+            Step::Welcome => {
+                "This tour shows how to follow a game of Cryptid with the app. \
+                Press Next to go on, or do what a step asks."
+            }
+            Step::Pieces => {
+                "Build the map like the one on the table. Choose the pieces in the panel, \
+                drag pieces on the map to arrange them and click a piece to rotate it. \
+                Add every player, then press Ready."
+            }
+            Step::Structures => {
+                "Drag the structures on the map to where they stand on the table. \
+                Press Ready in the panel when they match."
+            }
+            Step::Answers => {
+                "Click a tile on the map, then set the answers of the players for it in the \
+                panel. Enter every disc and cube that is placed during the game."
+            }
+            Step::Deductions => {
+                "The app rules out tiles that the answers don't allow. Tiles that shrink \
+                can't hold the cryptid. The panel lists the clues each player might have."
+            }
+        }
+    }
+
+    fn target(self) -> Option<Target> {
+        match self {
+            Step::Welcome => None,
+            Step::Pieces | Step::Answers => Some(Target::Panel),
+            Step::Structures | Step::Deductions => Some(Target::Map),
+        }
+    }
+
+    /// True once the user did what the step asks for.
+    fn done(self, sub: &SubState) -> bool {
+        let playing = matches!(sub, SubState::TryingClues(_) | SubState::Verifying(_));
+        match self {
+            Step::Welcome | Step::Deductions => false,
+            Step::Pieces => playing || matches!(sub, SubState::PlacingStructures(_)),
+            Step::Structures => playing,
+            Step::Answers => match sub {
+                SubState::TryingClues(s) => !s.history.is_empty(),
+                SubState::Verifying(_) => true,
+                _ => false,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Tutorial {
+    /// The current step, or None if the tour is not running.
+    step: Option<usize>,
+    /// True if the user already did what the current step asks for when it started. Such a
+    /// step waits for the user to go on, instead of ending by itself.
+    done_on_entry: bool,
+}
+
+impl Tutorial {
+    pub fn gui(&mut self, ui: &mut egui::Ui) {
+        if ui.button(tr("Start the tour")).clicked() {
+            self.step = Some(0);
+            self.done_on_entry = false;
+        }
+    }
+
+    /// Go to the next step that is not done yet.
+    fn forward(&mut self, from: usize, sub: &SubState) {
+        self.step = Step::iter()
+            .enumerate()
+            .skip(from + 1)
+            .find(|(_, step)| !step.done(sub))
+            .map(|(i, _)| i);
+        self.done_on_entry = false;
+    }
+
+    /// Show the current step above everything else and outline its target.
+    pub fn overlay(
+        &mut self,
+        ctx: &egui::Context,
+        sub: &SubState,
+        panel: Option<Rect>,
+        map: Option<Rect>,
+        color: Color32,
+    ) {
+        let steps = Step::iter().collect::<Vec<_>>();
+        let Some(i) = self.step else {
+            return;
+        };
+        let step = steps[i];
+        if !self.done_on_entry && step.done(sub) {
+            self.forward(i, sub);
+            return;
+        }
+
+        let target = match step.target() {
+            Some(Target::Panel) => panel,
+            Some(Target::Map) => map,
+            None => None,
+        };
+        if let Some(rect) = target {
+            ctx.layer_painter(LayerId::new(Order::Foreground, Id::new("tutorial-target")))
+                .rect_stroke(rect.shrink(4.0), 8.0, (4.0, color));
+        }
+
+        egui::Window::new(tr("Tour"))
+            .anchor(Align2::CENTER_BOTTOM, [0.0, -LAYOUT_SPACE])
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(trf("Step {} of {}", &[&(i + 1), &steps.len()]));
+                ui.add(egui::Label::new(tr(step.text())).wrap(true));
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(i > 0, egui::Button::new(tr("Back")))
+                        .clicked()
+                    {
+                        self.step = Some(i - 1);
+                        self.done_on_entry = true;
+                    }
+                    let last = i + 1 == steps.len();
+                    let forward = if last { tr("Finish") } else { tr("Next") };
+                    if ui.button(forward).clicked() {
+                        self.forward(i, sub);
+                    }
+                    if !last && ui.button(tr("End the tour")).clicked() {
+                        self.step = None;
+                    }
+                });
+            });
+    }
+}
@@ -0,0 +1,200 @@
+//! Estimating the chance of every player to win from the current position. The rest of the
+//! game is played many times over with clues that fit the answers so far, and every player
+//! follows the same simple strategy: search when only one tile is left for them, otherwise
+//! ask the question that rules out the most clues for sure.
+
+use itertools::Itertools;
+use notan::random::rand::{seq::SliceRandom, thread_rng, Rng};
+
+use crate::model::{Clue, Map, TileMask};
+
+/// Number of games to play when the user did not choose.
+pub const DEFAULT_SAMPLES: usize = 200;
+/// Number of games to play in one frame.
+pub const GAMES_PER_FRAME: usize = 10;
+/// Games that take longer than this are not counted as a win for anyone.
+const MAX_TURNS: usize = 60;
+/// Tries to find clues that point to exactly one tile, for every game.
+const MAX_TRIES: usize = 100;
+
+#[derive(Debug, Clone, Default)]
+pub struct WinChances {
+    /// Share of the games every player won, in the order of the lists of clues.
+    pub chances: Vec<f64>,
+    /// Number of games that were played.
+    pub games: usize,
+}
+
+/// Games in which every player has one clue out of their list. They are played a few at a
+/// time, so that the app stays responsive.
+#[derive(Debug)]
+pub struct Estimation {
+    possible_clues: Vec<Vec<Clue>>,
+    /// For every player and every clue the tiles it applies to.
+    covered: Vec<Vec<TileMask>>,
+    tiles: usize,
+    samples: usize,
+    /// Samples taken so far, also those where no game was found.
+    sampled: usize,
+    wins: Vec<usize>,
+    games: usize,
+}
+
+impl Estimation {
+    pub fn new(map: &Map, possible_clues: Vec<Vec<Clue>>, samples: usize) -> Self {
+        let covered = possible_clues
+            .iter()
+            .map(|clues| clues.iter().map(|&clue| map.mask(clue)).collect_vec())
+            .collect_vec();
+        Self {
+            wins: vec![0; possible_clues.len()],
+            possible_clues,
+            covered,
+            tiles: map.tiles.len(),
+            samples,
+            sampled: 0,
+            games: 0,
+        }
+    }
+
+    /// Play the next few games. Returns true once all of them are played.
+    pub fn step(&mut self, count: usize) -> bool {
+        let mut rng = thread_rng();
+        for _ in 0..count.min(self.samples - self.sampled) {
+            self.sampled += 1;
+            let Some((truth, cryptid)) =
+                sample(&self.possible_clues, &self.covered, self.tiles, &mut rng)
+            else {
+                continue;
+            };
+            self.games += 1;
+            if let Some(winner) = play(&self.covered, &truth, cryptid, self.tiles, &mut rng) {
+                self.wins[winner] += 1;
+            }
+        }
+        self.sampled == self.samples
+    }
+
+    /// Share of the games that are played.
+    pub fn progress(&self) -> f32 {
+        self.sampled as f32 / self.samples.max(1) as f32
+    }
+
+    pub fn chances(&self) -> WinChances {
+        WinChances {
+            chances: self
+                .wins
+                .iter()
+                .map(|&w| {
+                    if self.games > 0 {
+                        w as f64 / self.games as f64
+                    } else {
+                        0.0
+                    }
+                })
+                .collect(),
+            games: self.games,
+        }
+    }
+}
+
+/// Pick one different clue for every player, so that they point to exactly one tile. Returns
+/// the index of the clue of every player and the tile of the cryptid.
+fn sample(
+    possible_clues: &[Vec<Clue>],
+    covered: &[Vec<TileMask>],
+    tiles: usize,
+    rng: &mut impl Rng,
+) -> Option<(Vec<usize>, usize)> {
+    for _ in 0..MAX_TRIES {
+        let truth = possible_clues
+            .iter()
+            .map(|clues| (!clues.is_empty()).then(|| rng.gen_range(0..clues.len())))
+            .collect::<Option<Vec<_>>>()?;
+        let clues = truth
+            .iter()
+            .zip(possible_clues)
+            .map(|(&i, clues)| clues[i])
+            .collect_vec();
+        if clues.iter().tuple_combinations().any(|(a, b)| a == b) {
+            // Every player has a different clue.
+            continue;
+        }
+        let remaining = truth
+            .iter()
+            .enumerate()
+            .fold(TileMask::from_fn(tiles, |_| true), |remaining, (p, &c)| {
+                remaining.and(&covered[p][c])
+            });
+        if let Some(cryptid) = remaining.single() {
+            return Some((truth, cryptid));
+        }
+    }
+    None
+}
+
+/// Play until somebody finds the cryptid. Returns the winner, if there is one in time.
+fn play(
+    covered: &[Vec<TileMask>],
+    truth: &[usize],
+    cryptid: usize,
+    tiles: usize,
+    rng: &mut impl Rng,
+) -> Option<usize> {
+    let players = covered.len();
+    // Clues every player might have, as far as the others know.
+    let mut known = covered
+        .iter()
+        .map(|clues| (0..clues.len()).collect_vec())
+        .collect_vec();
+    // Tiles that were searched without finding the cryptid.
+    let mut searched = vec![false; tiles];
+    let start = rng.gen_range(0..players);
+
+    for turn in 0..MAX_TURNS {
+        let player = (start + turn) % players;
+        let allowed = (0..tiles)
+            .filter(|&t| covered[player][truth[player]].contains(t) && !searched[t])
+            .filter(|&t| {
+                (0..players)
+                    .filter(|&other| other != player)
+                    .all(|other| known[other].iter().any(|&c| covered[other][c].contains(t)))
+            })
+            .collect_vec();
+        if allowed.len() == 1 {
+            return Some(player);
+        }
+
+        // The question that rules out the most clues of somebody, whatever they answer.
+        let question = allowed
+            .iter()
+            .flat_map(|&t| {
+                let known = &known;
+                (0..players)
+                    .filter(move |&other| other != player)
+                    .map(move |other| {
+                        let yes = known[other]
+                            .iter()
+                            .filter(|&&c| covered[other][c].contains(t))
+                            .count();
+                        (t, other, yes.min(known[other].len() - yes))
+                    })
+            })
+            .max_by_key(|&(_, _, gain)| gain);
+        match question {
+            Some((t, other, gain)) if gain > 0 => {
+                let answer = covered[other][truth[other]].contains(t);
+                known[other].retain(|&c| covered[other][c].contains(t) == answer);
+            }
+            _ => {
+                // Nothing more to learn from questions, so guess.
+                let &t = allowed.choose(rng)?;
+                if t == cryptid {
+                    return Some(player);
+                }
+                searched[t] = true;
+            }
+        }
+    }
+    None
+}